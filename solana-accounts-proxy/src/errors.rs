@@ -1,8 +1,24 @@
-use jsonrpsee::core::Error as JsonrpseeError;
+use jsonrpsee::{
+    core::Error as JsonrpseeError,
+    types::error::{
+        CallError, ErrorObject, INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE, INVALID_REQUEST_CODE,
+    },
+};
+use serde_json::json;
 
 pub(crate) const INTERNAL_SERVER_ERROR: &str =
     "An error occured on the server. Contact admininstrator of check the server logs";
 
+/// The maximum number of bytes of account data that can be encoded as `base58`
+pub const MAX_BASE58_BYTES: usize = 128;
+
+/// Solana's JSON-RPC error code for a node that is behind or unhealthy
+pub const NODE_UNHEALTHY_CODE: i32 = -32005;
+/// Solana's JSON-RPC error code for a key excluded from the account secondary indexes
+pub const KEY_EXCLUDED_FROM_SECONDARY_INDEX_CODE: i32 = -32010;
+/// Solana's JSON-RPC error code for a `minContextSlot` that is ahead of the node
+pub const MIN_CONTEXT_SLOT_NOT_REACHED_CODE: i32 = -32016;
+
 /// Result type for the crate
 pub type ProxyResult<T> = Result<T, ProxyError>;
 
@@ -13,16 +29,94 @@ pub enum ProxyError {
     /// Safely returns a predefined error to the client
     /// while logging the actual error to prevent leaking server information
     Pg(tokio_postgres::Error),
-    /// An error that
-    Client(String),
+    /// The parameters of the method are invalid, maps to `-32602`
+    InvalidParams(String),
+    /// The parameters are valid but cannot be combined, maps to `-32600`
+    InvalidRequest(String),
+    /// The account data is too large for the `base58` and `binary` encodings, maps to `-32600` like Solana
+    Base58DataTooLarge,
+    /// The key has been excluded from the account secondary indexes, maps to `-32010`
+    KeyExcludedFromSecondaryIndex(String),
+    /// The database has not yet reached the `minContextSlot` of the request, maps to `-32016`
+    MinContextSlotNotReached {
+        /// The slot the database is at
+        context_slot: u64,
+    },
+    /// The database is behind the cluster, maps to `-32005`
+    NodeUnhealthy {
+        /// The number of slots the database is behind by, if it is known
+        num_slots_behind: Option<u64>,
+    },
+    /// An error on the server that should not be leaked to the client, maps to `-32603`
+    Internal(String),
+}
+
+impl ProxyError {
+    /// The JSON-RPC error code returned to the client
+    pub fn code(&self) -> i32 {
+        match self {
+            ProxyError::Pg(_) | ProxyError::Internal(_) => INTERNAL_ERROR_CODE,
+            ProxyError::InvalidParams(_) => INVALID_PARAMS_CODE,
+            ProxyError::InvalidRequest(_) | ProxyError::Base58DataTooLarge => INVALID_REQUEST_CODE,
+            ProxyError::KeyExcludedFromSecondaryIndex(_) => KEY_EXCLUDED_FROM_SECONDARY_INDEX_CODE,
+            ProxyError::MinContextSlotNotReached { .. } => MIN_CONTEXT_SLOT_NOT_REACHED_CODE,
+            ProxyError::NodeUnhealthy { .. } => NODE_UNHEALTHY_CODE,
+        }
+    }
 }
 
 impl From<ProxyError> for jsonrpsee::core::Error {
     fn from(error: ProxyError) -> Self {
-        match error {
-            ProxyError::Pg(pg_error) => crate::PgConnection::error_handler(&pg_error),
-            ProxyError::Client(safe_error) => JsonrpseeError::Custom(safe_error),
-        }
+        let code = error.code();
+
+        let error_object = match error {
+            ProxyError::Pg(pg_error) => return crate::PgConnection::error_handler(&pg_error),
+            ProxyError::Internal(internal_error) => {
+                tracing::error!("INTERNAL_ERROR: {}", internal_error);
+
+                ErrorObject::owned(code, INTERNAL_SERVER_ERROR, Option::<()>::None)
+            }
+            ProxyError::InvalidParams(message) | ProxyError::InvalidRequest(message) => {
+                ErrorObject::owned(code, message, Option::<()>::None)
+            }
+            ProxyError::Base58DataTooLarge => ErrorObject::owned(
+                code,
+                format!(
+                    "Encoded binary (base 58) data should be less than {} bytes, please use Base64 encoding.",
+                    MAX_BASE58_BYTES
+                ),
+                Option::<()>::None,
+            ),
+            ProxyError::KeyExcludedFromSecondaryIndex(index_key) => ErrorObject::owned(
+                code,
+                format!(
+                    "{} excluded from account secondary indexes; this RPC method unavailable for key",
+                    index_key
+                ),
+                Option::<()>::None,
+            ),
+            ProxyError::MinContextSlotNotReached { context_slot } => ErrorObject::owned(
+                code,
+                "Minimum context slot has not been reached",
+                Some(json!({ "contextSlot": context_slot })),
+            ),
+            ProxyError::NodeUnhealthy { num_slots_behind } => {
+                let message = match num_slots_behind {
+                    Some(num_slots_behind) => {
+                        format!("Node is behind by {} slots", num_slots_behind)
+                    }
+                    None => "Node is unhealthy".to_owned(),
+                };
+
+                ErrorObject::owned(
+                    code,
+                    message,
+                    Some(json!({ "numSlotsBehind": num_slots_behind })),
+                )
+            }
+        };
+
+        JsonrpseeError::Call(CallError::Custom(error_object))
     }
 }
 
@@ -2,12 +2,9 @@
 #![deny(missing_docs)]
 #![doc = include_str!("../README.md")]
 
-use hyper::Server;
-use jsonrpsee::http_server::{AccessControlBuilder, HttpServerBuilder, HttpServerHandle};
 use lazy_static::lazy_static;
-use std::net::SocketAddr;
-use tokio::sync::RwLock;
-use tokio_postgres::Client;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
 
 mod rpc;
 pub use rpc::*;
@@ -24,13 +21,22 @@ pub use config::*;
 mod errors;
 pub use errors::*;
 
+mod server;
+pub use server::*;
+
+mod parsers;
+pub use parsers::*;
+
+mod upstream;
+pub use upstream::*;
+
 lazy_static! {
     /// Reads the user configuration and stores it in a global static variable
     pub static ref USER_CONFIG: ProxyConfig = load_user_config();
     /// parses a user supplied file and stores it in a global static variable.
     /// Useful for testing purposes
-    /// Stores a global static ref to the postgres database `Client`
-    pub static ref CLIENT: RwLock<Option<Client>> = RwLock::new(Option::None);
+    /// Stores a global static ref to the router of the pools of postgres database connections
+    pub static ref PG_ROUTER: OnceCell<PgRouter> = OnceCell::new();
 }
 
 #[tokio::main]
@@ -40,59 +46,70 @@ async fn main() -> anyhow::Result<()> {
         dbg!(&*USER_CONFIG);
 
         #[cfg(all(debug_assertions, feature = "dangerous_debug",))]
-        println!(
-            "POSTGRES_URL: {}",
-            USER_CONFIG.postgres_config().postgres_url()
-        );
+        for postgres_config in USER_CONFIG.postgres_configs() {
+            println!("POSTGRES_URL: {}", postgres_config.postgres_url());
+        }
     }
 
     log()?;
 
-    match PgConnection::connect(USER_CONFIG.postgres_config()).await {
-        Ok(value) => {
-            CLIENT.write().await.replace(value);
-        }
+    let pg_router =
+        match PgRouter::new(USER_CONFIG.postgres_configs(), USER_CONFIG.routing_config()) {
+            Ok(pg_router) => pg_router,
+            Err(error) => {
+                tracing::error!(
+                    "Invalid configuration of the database connection: `{:?}`",
+                    error.to_string()
+                );
+
+                std::process::exit(1)
+            }
+        };
+
+    if PG_ROUTER.set(pg_router).is_err() {
+        tracing::error!("The pool of connections to the database has already been initialized");
+
+        std::process::exit(1)
+    }
+
+    // The proxy keeps running if a database is unreachable, the pools keep reconnecting in the background
+    // and the health check routes the requests away from the unreachable databases
+    if let Some(pg_router) = PG_ROUTER.get() {
+        pg_router.check_health().await;
+        pg_router.spawn_health_check();
+    }
+
+    // IDL files added or changed while the proxy runs are picked up by the background reload
+    let anchor_config = USER_CONFIG.anchor_config();
+    if anchor_config.is_enabled() {
+        ANCHOR_IDLS.reload(anchor_config);
+        ANCHOR_IDLS.spawn_reload(anchor_config);
+    }
+
+    let upstream = match Upstream::new(USER_CONFIG.upstream_config()) {
+        Ok(upstream) => upstream.map(Arc::new),
         Err(error) => {
             tracing::error!(
-                "Unable to initialize `tokio` runtime: `{:?}`",
+                "Invalid configuration of the upstream Solana RPC node: `{:?}`",
                 error.to_string()
             );
 
             std::process::exit(1)
         }
+    };
+
+    if let Some(upstream) = upstream.as_ref() {
+        upstream.clone().spawn_stats_log();
     }
 
-    let (socket_addr, server) = http_server(USER_CONFIG.get_socketaddr()).await?;
+    let (socket_addr, server) = http_server(USER_CONFIG.get_socketaddr(), upstream).await?;
     tracing::info!("Listening at http://{:?}", socket_addr);
 
-    server.await;
+    server.await??;
 
     Ok(())
 }
 
-/// Create a HTTP server to serve RPC requests
-pub async fn http_server(
-    socket_addr: SocketAddr,
-) -> Result<(SocketAddr, HttpServerHandle), jsonrpsee::core::Error> {
-    let server = Server::bind(&socket_addr);
-
-    let acl = AccessControlBuilder::new()
-        .allow_all_headers()
-        .allow_all_origins()
-        .allow_all_hosts()
-        .build();
-
-    let server = HttpServerBuilder::new()
-        .set_access_control(acl) //TODO
-        .max_response_body_size(u32::MAX)
-        .build_from_hyper(server, socket_addr)?;
-
-    let addr = server.local_addr()?;
-    let handle = server.start(RpcProxyImpl.into_rpc())?;
-
-    Ok((addr, handle))
-}
-
 /// Enable the logger
 pub fn log() -> anyhow::Result<()> {
     tracing_subscriber::FmtSubscriber::builder()
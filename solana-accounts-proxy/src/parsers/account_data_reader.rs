@@ -0,0 +1,216 @@
+use crate::{ParseAccountError, ParseResult};
+
+/// Reads the little-endian fields of an account one after the other,
+/// every read fails with [ParseAccountError::InvalidData] when the data is too short
+#[derive(Debug, Clone)]
+pub struct AccountDataReader<'d> {
+    data: &'d [u8],
+    offset: usize,
+}
+
+impl<'d> AccountDataReader<'d> {
+    /// Start reading at the beginning of the data
+    pub fn new(data: &'d [u8]) -> Self {
+        AccountDataReader { data, offset: 0 }
+    }
+
+    /// The number of bytes read so far
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The bytes that have not been read yet
+    pub fn remaining(&self) -> &'d [u8] {
+        &self.data[self.offset..]
+    }
+
+    /// Read the next `length` bytes
+    pub fn bytes(&mut self, length: usize) -> ParseResult<&'d [u8]> {
+        let end = self
+            .offset
+            .checked_add(length)
+            .filter(|end| *end <= self.data.len())
+            .ok_or(ParseAccountError::InvalidData)?;
+
+        let bytes = &self.data[self.offset..end];
+        self.offset = end;
+
+        Ok(bytes)
+    }
+
+    /// Read the next `N` bytes into an array
+    pub fn array<const N: usize>(&mut self) -> ParseResult<[u8; N]> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.bytes(N)?);
+
+        Ok(array)
+    }
+
+    /// Skip the next `length` bytes
+    pub fn skip(&mut self, length: usize) -> ParseResult<()> {
+        self.bytes(length)?;
+
+        Ok(())
+    }
+
+    /// Read a `u8`
+    pub fn u8(&mut self) -> ParseResult<u8> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    /// Read a `bool` stored as a byte, any value other than `0` or `1` is invalid
+    pub fn bool(&mut self) -> ParseResult<bool> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(ParseAccountError::InvalidData),
+        }
+    }
+
+    /// Read a `u16`
+    pub fn u16(&mut self) -> ParseResult<u16> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    /// Read an `i16`
+    pub fn i16(&mut self) -> ParseResult<i16> {
+        Ok(i16::from_le_bytes(self.array()?))
+    }
+
+    /// Read a `u32`
+    pub fn u32(&mut self) -> ParseResult<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    /// Read a `u64`
+    pub fn u64(&mut self) -> ParseResult<u64> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    /// Read an `i64`
+    pub fn i64(&mut self) -> ParseResult<i64> {
+        Ok(i64::from_le_bytes(self.array()?))
+    }
+
+    /// Read an `f64`
+    pub fn f64(&mut self) -> ParseResult<f64> {
+        Ok(f64::from_le_bytes(self.array()?))
+    }
+
+    /// Read a public key as a `base58` string
+    pub fn pubkey(&mut self) -> ParseResult<String> {
+        Ok(bs58::encode(self.bytes(32)?).into_string())
+    }
+
+    /// Read a 32 byte hash as a `base58` string
+    pub fn hash(&mut self) -> ParseResult<String> {
+        self.pubkey()
+    }
+
+    /// Read a public key where all zeroes means no public key
+    pub fn optional_nonzero_pubkey(&mut self) -> ParseResult<Option<String>> {
+        let pubkey = self.bytes(32)?;
+
+        if pubkey.iter().all(|byte| *byte == 0) {
+            Ok(Option::None)
+        } else {
+            Ok(Some(bs58::encode(pubkey).into_string()))
+        }
+    }
+
+    /// Read a `bincode` `Option<Pubkey>`, a `u8` tag followed by the public key only when it is present
+    pub fn option_pubkey(&mut self) -> ParseResult<Option<String>> {
+        match self.u8()? {
+            0 => Ok(Option::None),
+            1 => Ok(Some(self.pubkey()?)),
+            _ => Err(ParseAccountError::InvalidData),
+        }
+    }
+
+    /// Read a `COption<Pubkey>`, a `u32` tag followed by the public key which is always present
+    pub fn coption_pubkey(&mut self) -> ParseResult<Option<String>> {
+        let tag = self.u32()?;
+        let pubkey = self.pubkey()?;
+
+        match tag {
+            0 => Ok(Option::None),
+            1 => Ok(Some(pubkey)),
+            _ => Err(ParseAccountError::InvalidData),
+        }
+    }
+
+    /// Read a `COption<u64>`, a `u32` tag followed by the value which is always present
+    pub fn coption_u64(&mut self) -> ParseResult<Option<u64>> {
+        let tag = self.u32()?;
+        let value = self.u64()?;
+
+        match tag {
+            0 => Ok(Option::None),
+            1 => Ok(Some(value)),
+            _ => Err(ParseAccountError::InvalidData),
+        }
+    }
+
+    /// Read a `borsh` string, a `u32` length followed by UTF-8 bytes
+    pub fn string(&mut self) -> ParseResult<String> {
+        let length = self.u32()? as usize;
+
+        String::from_utf8(self.bytes(length)?.to_vec()).map_err(|_| ParseAccountError::InvalidData)
+    }
+
+    /// Read a `bincode` string, a `u64` length followed by UTF-8 bytes
+    pub fn bincode_string(&mut self) -> ParseResult<String> {
+        let length = self.bincode_length()?;
+
+        String::from_utf8(self.bytes(length)?.to_vec()).map_err(|_| ParseAccountError::InvalidData)
+    }
+
+    /// Read the `u64` length of a `bincode` collection. The length is checked against the
+    /// remaining bytes since every element is at least one byte long.
+    pub fn bincode_length(&mut self) -> ParseResult<usize> {
+        let length = usize::try_from(self.u64()?).map_err(|_| ParseAccountError::InvalidData)?;
+
+        if length > self.remaining().len() {
+            return Err(ParseAccountError::InvalidData);
+        }
+
+        Ok(length)
+    }
+
+    /// Read a `bincode` `Option<u64>`, a `u8` tag followed by the value only when it is present
+    pub fn option_u64(&mut self) -> ParseResult<Option<u64>> {
+        match self.u8()? {
+            0 => Ok(Option::None),
+            1 => Ok(Some(self.u64()?)),
+            _ => Err(ParseAccountError::InvalidData),
+        }
+    }
+
+    /// Read the compact-u16 length of a `short_vec`, 7 bits per byte in at most 3 bytes
+    pub fn short_vec_length(&mut self) -> ParseResult<usize> {
+        let mut length = 0usize;
+
+        for index in 0..3 {
+            let byte = self.u8()?;
+            length |= ((byte & 0x7f) as usize) << (index * 7);
+
+            if byte & 0x80 == 0 {
+                // A trailing zero byte is a longer encoding of the same length
+                if index > 0 && byte == 0 {
+                    return Err(ParseAccountError::InvalidData);
+                }
+
+                return u16::try_from(length)
+                    .map(usize::from)
+                    .map_err(|_| ParseAccountError::InvalidData);
+            }
+        }
+
+        Err(ParseAccountError::InvalidData)
+    }
+
+    /// Read the next `length` bytes as a `base64` string, used for the encrypted values
+    pub fn base64(&mut self, length: usize) -> ParseResult<String> {
+        Ok(base64::encode(self.bytes(length)?))
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{AccountDataReader, ParseAccountError, ParseResult};
+use serde::Serialize;
+
+/// The length of the metadata of a lookup table, the addresses start right after it
+const LOOKUP_TABLE_META_LENGTH: usize = 56;
+
+/// A parsed account of the Address Lookup Table program
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "type", content = "info")]
+pub enum LookupTableAccountType {
+    /// The account has not been initialized
+    Uninitialized,
+    /// An address lookup table
+    LookupTable(UiLookupTable),
+}
+
+/// An address lookup table
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiLookupTable {
+    /// The slot the table was deactivated at, `u64::MAX` while it is active
+    pub deactivation_slot: String,
+    /// The slot the table was last extended at
+    pub last_extended_slot: String,
+    /// The index of the first address added in `lastExtendedSlot`
+    pub last_extended_slot_start_index: u8,
+    /// The authority allowed to extend and close the table, a frozen table has no authority
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authority: Option<String>,
+    /// The addresses of the table
+    pub addresses: Vec<String>,
+}
+
+/// Parse an account of the Address Lookup Table program, the `bincode` layout of
+/// `ProgramState` followed by the addresses
+pub fn parse_address_lookup_table(data: &[u8]) -> ParseResult<LookupTableAccountType> {
+    let mut reader = AccountDataReader::new(data);
+
+    match reader.u32()? {
+        0 => Ok(LookupTableAccountType::Uninitialized),
+        1 => {
+            let deactivation_slot = reader.u64()?.to_string();
+            let last_extended_slot = reader.u64()?.to_string();
+            let last_extended_slot_start_index = reader.u8()?;
+            let authority = reader.option_pubkey()?;
+
+            let addresses_data = data
+                .get(LOOKUP_TABLE_META_LENGTH..)
+                .filter(|addresses_data| addresses_data.len() % 32 == 0)
+                .ok_or(ParseAccountError::InvalidData)?;
+
+            let addresses = addresses_data
+                .chunks_exact(32)
+                .map(|address| bs58::encode(address).into_string())
+                .collect();
+
+            Ok(LookupTableAccountType::LookupTable(UiLookupTable {
+                deactivation_slot,
+                last_extended_slot,
+                last_extended_slot_start_index,
+                authority,
+                addresses,
+            }))
+        }
+        _ => Err(ParseAccountError::InvalidData),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parse_account_data, AdditionalData, ParsableAccount, ParseAccountError, ParsedAccount,
+        ADDRESS_LOOKUP_TABLE_PROGRAM_ID,
+    };
+    use serde_json::json;
+
+    const ACCOUNT: &str = "YMN9Qj5jPNp7j14VPcML1B6xGgcPWVZUGLFU3Mnyfaf";
+
+    #[test]
+    fn parse_address_lookup_tables() -> anyhow::Result<()> {
+        // An active lookup table
+        let mut data = 1u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&u64::MAX.to_le_bytes());
+        data.extend_from_slice(&250_000_000u64.to_le_bytes());
+        data.push(1);
+        data.push(1);
+        data.extend_from_slice(&[5; 32]);
+        data.extend_from_slice(&[0; 2]);
+        data.extend_from_slice(&[10; 32]);
+        data.extend_from_slice(&[11; 32]);
+        assert_eq!(
+            parse_account_data(
+                ACCOUNT,
+                ADDRESS_LOOKUP_TABLE_PROGRAM_ID,
+                &data,
+                &AdditionalData::default(),
+            ),
+            Ok(ParsedAccount {
+                program: "addressLookupTable".to_owned(),
+                parsed: json!({
+                    "type": "lookupTable",
+                    "info": {
+                        "deactivationSlot": "18446744073709551615",
+                        "lastExtendedSlot": "250000000",
+                        "lastExtendedSlotStartIndex": 1,
+                        "authority": "LbUiWL3xVV8hTFYBVdbTNrpDo41NKS6o3LHHuDzjfcY",
+                        "addresses": [
+                            "gBxS1f6uyyGPuW5MzGBukidSb71jdsCb5fZaoSzULE5",
+                            "k7FaK87WHGVXzkaoHb7CdVPgkKDQhZ29VLDeBVbDfYn"
+                        ]
+                    }
+                }),
+                space: 120,
+            })
+        );
+
+        // A frozen lookup table has no authority
+        let mut data = 1u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&u64::MAX.to_le_bytes());
+        data.extend_from_slice(&250_000_000u64.to_le_bytes());
+        data.extend_from_slice(&[0; 36]);
+        data.extend_from_slice(&[10; 32]);
+        let parsed = parse_account_data(
+            ACCOUNT,
+            ADDRESS_LOOKUP_TABLE_PROGRAM_ID,
+            &data,
+            &AdditionalData::default(),
+        )
+        .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        assert!(parsed.parsed["info"].get("authority").is_none());
+        assert_eq!(
+            parsed.parsed["info"]["addresses"],
+            json!(["gBxS1f6uyyGPuW5MzGBukidSb71jdsCb5fZaoSzULE5"])
+        );
+
+        // The addresses must be whole public keys
+        data.push(0);
+        assert_eq!(
+            parse_account_data(
+                ACCOUNT,
+                ADDRESS_LOOKUP_TABLE_PROGRAM_ID,
+                &data,
+                &AdditionalData::default()
+            ),
+            Err(ParseAccountError::AccountNotParsable(
+                ParsableAccount::AddressLookupTable
+            ))
+        );
+
+        Ok(())
+    }
+}
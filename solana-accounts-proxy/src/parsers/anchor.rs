@@ -0,0 +1,834 @@
+use crate::{
+    AccountDataReader, AnchorConfig, ParsableAccount, ParseAccountError, ParseResult, ParsedAccount,
+};
+use serde_json::{Map, Value as JsonValue};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, SystemTime},
+};
+
+/// The length of the discriminator written before the data of an Anchor account
+pub const ANCHOR_DISCRIMINATOR_LENGTH: usize = 8;
+/// The maximum nesting of the types of an account, guards against recursive type definitions
+const MAX_TYPE_DEPTH: usize = 32;
+
+lazy_static::lazy_static! {
+    /// The Anchor IDLs configured in the `[anchor]` section, keyed by program ID
+    pub static ref ANCHOR_IDLS: AnchorIdls = AnchorIdls::default();
+}
+
+/// The discriminator of an account of an Anchor IDL without explicit discriminators,
+/// the first 8 bytes of `sha256("account:<name>")`
+pub fn anchor_account_discriminator(name: &str) -> [u8; ANCHOR_DISCRIMINATOR_LENGTH] {
+    let hash = Sha256::digest(format!("account:{}", name).as_bytes());
+
+    let mut discriminator = [0u8; ANCHOR_DISCRIMINATOR_LENGTH];
+    discriminator.copy_from_slice(&hash[..ANCHOR_DISCRIMINATOR_LENGTH]);
+
+    discriminator
+}
+
+/// The accounts and types of an Anchor IDL. Both the IDLs of Anchor `0.30` and later, with
+/// explicit discriminators and the account types in `types`, and the older IDLs are supported.
+#[derive(Debug)]
+pub struct AnchorIdl {
+    name: String,
+    accounts: Vec<AnchorIdlAccount>,
+    types: HashMap<String, JsonValue>,
+}
+
+#[derive(Debug)]
+struct AnchorIdlAccount {
+    name: String,
+    discriminator: [u8; ANCHOR_DISCRIMINATOR_LENGTH],
+    type_definition: JsonValue,
+}
+
+impl AnchorIdl {
+    /// Read the IDL from a JSON file
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        AnchorIdl::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Read the IDL from its JSON
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let idl: JsonValue = serde_json::from_str(json)?;
+
+        let name = idl["metadata"]["name"]
+            .as_str()
+            .or_else(|| idl["name"].as_str())
+            .ok_or_else(|| anyhow::anyhow!("The IDL has no `name`"))?
+            .to_owned();
+
+        let mut types = HashMap::new();
+        for type_definition in idl["types"].as_array().into_iter().flatten() {
+            let type_name = type_definition["name"]
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("A type of the IDL has no `name`"))?;
+
+            // The serialization is set next to the type, it is kept with the type to check it when decoding
+            let mut definition = type_definition["type"].clone();
+            if let (Some(definition), Some(serialization)) = (
+                definition.as_object_mut(),
+                type_definition.get("serialization"),
+            ) {
+                definition.insert("serialization".to_owned(), serialization.clone());
+            }
+
+            types.insert(type_name.to_owned(), definition);
+        }
+
+        let mut accounts = Vec::new();
+        for account in idl["accounts"].as_array().into_iter().flatten() {
+            let account_name = account["name"]
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("An account of the IDL has no `name`"))?;
+
+            let discriminator = match account["discriminator"].as_array() {
+                Some(discriminator) => {
+                    let bytes = discriminator
+                        .iter()
+                        .map(|byte| byte.as_u64().and_then(|byte| u8::try_from(byte).ok()))
+                        .collect::<Option<Vec<u8>>>()
+                        .filter(|bytes| bytes.len() == ANCHOR_DISCRIMINATOR_LENGTH)
+                        .ok_or_else(|| {
+                            anyhow::anyhow!("Invalid discriminator of the account `{account_name}`")
+                        })?;
+
+                    let mut discriminator = [0u8; ANCHOR_DISCRIMINATOR_LENGTH];
+                    discriminator.copy_from_slice(&bytes);
+
+                    discriminator
+                }
+                None => anchor_account_discriminator(account_name),
+            };
+
+            // Older IDLs define the account type with the account
+            let type_definition = match account.get("type") {
+                Some(type_definition) => type_definition.clone(),
+                None => types.get(account_name).cloned().ok_or_else(|| {
+                    anyhow::anyhow!("The type of the account `{account_name}` is not defined")
+                })?,
+            };
+
+            accounts.push(AnchorIdlAccount {
+                name: account_name.to_owned(),
+                discriminator,
+                type_definition,
+            });
+        }
+
+        Ok(AnchorIdl {
+            name,
+            accounts,
+            types,
+        })
+    }
+
+    /// The name of the program
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Decode an account into `{"type": "<account name>", "info": {<fields>}}`.
+    /// The account is found by its discriminator and the bytes after its fields are ignored.
+    pub fn parse_account(&self, data: &[u8]) -> ParseResult<JsonValue> {
+        let discriminator = data
+            .get(..ANCHOR_DISCRIMINATOR_LENGTH)
+            .ok_or(ParseAccountError::InvalidData)?;
+
+        let account = self
+            .accounts
+            .iter()
+            .find(|account| account.discriminator == discriminator)
+            .ok_or(ParseAccountError::InvalidData)?;
+
+        let mut decoder = AnchorDecoder {
+            idl: self,
+            reader: AccountDataReader::new(&data[ANCHOR_DISCRIMINATOR_LENGTH..]),
+        };
+
+        let info = decoder.type_definition(&account.type_definition, 0)?;
+
+        let mut parsed = Map::new();
+        parsed.insert("type".to_owned(), account.name.clone().into());
+        parsed.insert("info".to_owned(), info);
+
+        Ok(parsed.into())
+    }
+}
+
+/// Decodes the `borsh` data of an account with the types of an IDL
+struct AnchorDecoder<'i, 'd> {
+    idl: &'i AnchorIdl,
+    reader: AccountDataReader<'d>,
+}
+
+impl<'i, 'd> AnchorDecoder<'i, 'd> {
+    /// Decode a type definition, the value of a `type` key of the IDL
+    fn type_definition(
+        &mut self,
+        type_definition: &JsonValue,
+        depth: usize,
+    ) -> ParseResult<JsonValue> {
+        if depth > MAX_TYPE_DEPTH {
+            return Err(ParseAccountError::InvalidData);
+        }
+
+        // Only the `borsh` serialization is supported, `zero_copy` accounts have a C layout
+        if let Some(serialization) = type_definition["serialization"].as_str() {
+            if serialization != "borsh" {
+                return Err(ParseAccountError::InvalidData);
+            }
+        }
+
+        match type_definition["kind"].as_str() {
+            Some("struct") => self.fields(&type_definition["fields"], depth),
+            Some("enum") => {
+                let index = self.reader.u8()? as usize;
+                let variant = type_definition["variants"]
+                    .get(index)
+                    .ok_or(ParseAccountError::InvalidData)?;
+                let variant_name = variant["name"]
+                    .as_str()
+                    .ok_or(ParseAccountError::InvalidData)?;
+
+                let mut value = Map::new();
+                value.insert(
+                    variant_name.to_owned(),
+                    self.fields(&variant["fields"], depth)?,
+                );
+
+                Ok(value.into())
+            }
+            Some("type") => self.idl_type(&type_definition["alias"], depth + 1),
+            _ => Err(ParseAccountError::InvalidData),
+        }
+    }
+
+    /// Decode named fields into an object and tuple fields into an array
+    fn fields(&mut self, fields: &JsonValue, depth: usize) -> ParseResult<JsonValue> {
+        let fields = match fields.as_array() {
+            Some(fields) => fields,
+            None => return Ok(JsonValue::Object(Map::new())),
+        };
+
+        let is_named = fields
+            .iter()
+            .all(|field| field.get("name").is_some() && field.get("type").is_some());
+
+        if is_named {
+            let mut value = Map::new();
+            for field in fields {
+                let field_name = field["name"]
+                    .as_str()
+                    .ok_or(ParseAccountError::InvalidData)?;
+
+                value.insert(
+                    field_name.to_owned(),
+                    self.idl_type(&field["type"], depth + 1)?,
+                );
+            }
+
+            Ok(value.into())
+        } else {
+            fields
+                .iter()
+                .map(|field| self.idl_type(field, depth + 1))
+                .collect()
+        }
+    }
+
+    /// Decode a type of a field, a primitive, a container or a type defined by the IDL
+    fn idl_type(&mut self, idl_type: &JsonValue, depth: usize) -> ParseResult<JsonValue> {
+        if depth > MAX_TYPE_DEPTH {
+            return Err(ParseAccountError::InvalidData);
+        }
+
+        if let Some(primitive) = idl_type.as_str() {
+            return self.primitive(primitive);
+        }
+
+        if let Some(inner) = idl_type.get("vec") {
+            let length = self.reader.u32()? as usize;
+            if length > self.reader.remaining().len() {
+                return Err(ParseAccountError::InvalidData);
+            }
+
+            return (0..length)
+                .map(|_| self.idl_type(inner, depth + 1))
+                .collect();
+        }
+
+        if let Some(inner) = idl_type.get("option") {
+            return match self.reader.u8()? {
+                0 => Ok(JsonValue::Null),
+                1 => self.idl_type(inner, depth + 1),
+                _ => Err(ParseAccountError::InvalidData),
+            };
+        }
+
+        // The value of a `COption` is always present, like the SPL Token accounts
+        if let Some(inner) = idl_type.get("coption") {
+            let tag = self.reader.u32()?;
+            let value = self.idl_type(inner, depth + 1)?;
+
+            return match tag {
+                0 => Ok(JsonValue::Null),
+                1 => Ok(value),
+                _ => Err(ParseAccountError::InvalidData),
+            };
+        }
+
+        if let Some(array) = idl_type.get("array") {
+            let length = array[1]
+                .as_u64()
+                .and_then(|length| usize::try_from(length).ok())
+                .filter(|length| *length <= self.reader.remaining().len())
+                .ok_or(ParseAccountError::InvalidData)?;
+
+            return (0..length)
+                .map(|_| self.idl_type(&array[0], depth + 1))
+                .collect();
+        }
+
+        if let Some(defined) = idl_type.get("defined") {
+            // Older IDLs name the type directly, newer IDLs use `{"name": "<type>"}`
+            let type_name = defined
+                .as_str()
+                .or_else(|| defined["name"].as_str())
+                .ok_or(ParseAccountError::InvalidData)?;
+
+            let type_definition = self
+                .idl
+                .types
+                .get(type_name)
+                .ok_or(ParseAccountError::InvalidData)?;
+
+            return self.type_definition(type_definition, depth + 1);
+        }
+
+        Err(ParseAccountError::InvalidData)
+    }
+
+    /// Decode a primitive type. Integers of up to 64 bits are JSON numbers,
+    /// larger integers are strings so they are not rounded by JSON parsers.
+    fn primitive(&mut self, primitive: &str) -> ParseResult<JsonValue> {
+        let reader = &mut self.reader;
+
+        let value = match primitive {
+            "bool" => reader.bool()?.into(),
+            "u8" => reader.u8()?.into(),
+            "i8" => i8::from_le_bytes(reader.array()?).into(),
+            "u16" => reader.u16()?.into(),
+            "i16" => reader.i16()?.into(),
+            "u32" => reader.u32()?.into(),
+            "i32" => i32::from_le_bytes(reader.array()?).into(),
+            "u64" => reader.u64()?.into(),
+            "i64" => reader.i64()?.into(),
+            "u128" => u128::from_le_bytes(reader.array()?).to_string().into(),
+            "i128" => i128::from_le_bytes(reader.array()?).to_string().into(),
+            "f32" => f32::from_le_bytes(reader.array()?).into(),
+            "f64" => reader.f64()?.into(),
+            "string" => reader.string()?.into(),
+            "bytes" => {
+                let length = reader.u32()? as usize;
+
+                reader.base64(length)?.into()
+            }
+            "publicKey" | "pubkey" => reader.pubkey()?.into(),
+            _ => return Err(ParseAccountError::InvalidData),
+        };
+
+        Ok(value)
+    }
+}
+
+/// The Anchor IDLs used to parse accounts, they are reloaded in the background when
+/// the IDL files change or are added to the IDL directory
+#[derive(Debug, Default)]
+pub struct AnchorIdls {
+    idls: RwLock<HashMap<String, Arc<AnchorIdl>>>,
+    // The file of each program ID and its modification time when it was last read
+    files: Mutex<HashMap<String, (PathBuf, Option<SystemTime>)>>,
+}
+
+impl AnchorIdls {
+    /// Register the IDL of a program, replacing its previous IDL
+    pub fn insert(&self, program_id: &str, idl: AnchorIdl) {
+        self.idls
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(program_id.to_owned(), Arc::new(idl));
+    }
+
+    /// Remove the IDL of a program
+    pub fn remove(&self, program_id: &str) {
+        self.idls
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(program_id);
+    }
+
+    /// The IDL of a program
+    pub fn get(&self, program_id: &str) -> Option<Arc<AnchorIdl>> {
+        self.idls
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(program_id)
+            .cloned()
+    }
+
+    /// Parse an account owned by a program with an IDL, the `program` of the
+    /// parsed account is the name of the program in its IDL
+    pub fn parse_account_data(&self, owner: &str, data: &[u8]) -> ParseResult<ParsedAccount> {
+        let idl = self
+            .get(owner)
+            .ok_or(ParseAccountError::ProgramNotParsable)?;
+
+        let parsed = idl
+            .parse_account(data)
+            .map_err(|_| ParseAccountError::AccountNotParsable(ParsableAccount::Anchor))?;
+
+        Ok(ParsedAccount {
+            program: idl.name().to_owned(),
+            parsed,
+            space: data.len() as u64,
+        })
+    }
+
+    /// Load the IDLs that are new or changed since the last reload and remove the IDLs
+    /// that are no longer configured. An IDL that cannot be read keeps its previous version.
+    pub fn reload(&self, anchor_config: &AnchorConfig) {
+        let configured_files = AnchorIdls::configured_files(anchor_config);
+
+        let mut files = self
+            .files
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        files.retain(|program_id, _| {
+            let is_configured = configured_files.contains_key(program_id);
+
+            if !is_configured {
+                self.remove(program_id);
+
+                tracing::info!("Removed the Anchor IDL of `{}`", program_id);
+            }
+
+            is_configured
+        });
+
+        for (program_id, path) in configured_files {
+            let modified = fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+
+            if files.get(&program_id) == Some(&(path.clone(), modified)) {
+                continue;
+            }
+
+            match AnchorIdl::load(&path) {
+                Ok(idl) => {
+                    tracing::info!(
+                        "Loaded the Anchor IDL of `{}` from `{}`",
+                        program_id,
+                        path.display()
+                    );
+
+                    self.insert(&program_id, idl);
+                }
+                Err(error) => tracing::warn!(
+                    "Invalid Anchor IDL of `{}` at `{}`: {}",
+                    program_id,
+                    path.display(),
+                    error
+                ),
+            }
+
+            // A file that cannot be read is only read again once it changes
+            files.insert(program_id, (path, modified));
+        }
+    }
+
+    /// Run [AnchorIdls::reload] every `reload_interval` seconds in the background
+    pub fn spawn_reload(&'static self, anchor_config: &'static AnchorConfig) {
+        let reload_interval = Duration::from_secs(anchor_config.reload_interval());
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(reload_interval).await;
+
+                if let Err(error) =
+                    tokio::task::spawn_blocking(move || self.reload(anchor_config)).await
+                {
+                    tracing::error!("Failed to reload the Anchor IDLs: {}", error);
+                }
+            }
+        });
+    }
+
+    /// The IDL file of every program, the files named `<program ID>.json` in the IDL directory
+    /// and the files configured for a program ID, which take precedence
+    fn configured_files(anchor_config: &AnchorConfig) -> HashMap<String, PathBuf> {
+        let mut files = HashMap::new();
+
+        if let Some(idl_dir) = anchor_config.idl_dir() {
+            match fs::read_dir(idl_dir) {
+                Ok(entries) => {
+                    for path in entries.flatten().map(|entry| entry.path()) {
+                        if path.extension().and_then(|extension| extension.to_str()) != Some("json")
+                        {
+                            continue;
+                        }
+
+                        if let Some(program_id) = path.file_stem().and_then(|stem| stem.to_str()) {
+                            files.insert(program_id.to_owned(), path.clone());
+                        }
+                    }
+                }
+                Err(error) => tracing::warn!(
+                    "Cannot read the Anchor IDL directory `{}`: {}",
+                    idl_dir.display(),
+                    error
+                ),
+            }
+        }
+
+        for (program_id, path) in anchor_config.idls() {
+            files.insert(program_id.clone(), path.clone());
+        }
+
+        files.retain(|program_id, _| {
+            let is_public_key = bs58::decode(program_id)
+                .into_vec()
+                .map(|bytes| bytes.len() == 32)
+                .unwrap_or(false);
+
+            if !is_public_key {
+                tracing::warn!(
+                    "Ignored the Anchor IDL of the invalid program ID `{}`",
+                    program_id
+                );
+            }
+
+            is_public_key
+        });
+
+        files
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        anchor_account_discriminator, parse_account_data, Account, AdditionalData, AnchorConfig,
+        AnchorIdl, Encoding, ParsableAccount, ParseAccountError, ANCHOR_IDLS,
+    };
+    use serde_json::json;
+    use std::{
+        fs,
+        time::{Duration, SystemTime},
+    };
+
+    const ACCOUNT: &str = "YMN9Qj5jPNp7j14VPcML1B6xGgcPWVZUGLFU3Mnyfaf";
+    /// The program of the IDL in the old format
+    const LEGACY_PROGRAM: [u8; 32] = [20; 32];
+    /// The program of the IDL in the format of Anchor `0.30`
+    const PROGRAM: [u8; 32] = [21; 32];
+    /// The program of the IDLs loaded from the IDL directory
+    const DIR_PROGRAM: [u8; 32] = [22; 32];
+    const AUTHORITY: [u8; 32] = [23; 32];
+
+    const LEGACY_IDL: &str = r#"{
+        "version": "0.1.0",
+        "name": "counter",
+        "accounts": [
+            {
+                "name": "Counter",
+                "type": {
+                    "kind": "struct",
+                    "fields": [
+                        { "name": "authority", "type": "publicKey" },
+                        { "name": "count", "type": "u64" },
+                        { "name": "total", "type": "u128" },
+                        { "name": "label", "type": "string" },
+                        { "name": "bump", "type": { "option": "u8" } },
+                        { "name": "history", "type": { "vec": "i16" } },
+                        { "name": "seed", "type": { "array": ["u8", 4] } },
+                        { "name": "state", "type": { "defined": "State" } }
+                    ]
+                }
+            }
+        ],
+        "types": [
+            {
+                "name": "State",
+                "type": {
+                    "kind": "enum",
+                    "variants": [
+                        { "name": "Idle" },
+                        { "name": "Locked", "fields": [{ "name": "until", "type": "i64" }] },
+                        { "name": "Moved", "fields": ["publicKey", "bool"] }
+                    ]
+                }
+            }
+        ]
+    }"#;
+
+    const IDL: &str = r#"{
+        "address": "2Ue6C4FB3sAtbi8mmLt8s7L4g6vGgEEeSTcnHfqSzcdg",
+        "metadata": { "name": "vault", "version": "0.1.0", "spec": "0.1.0" },
+        "instructions": [],
+        "accounts": [
+            { "name": "Vault", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8] },
+            { "name": "Position", "discriminator": [8, 7, 6, 5, 4, 3, 2, 1] }
+        ],
+        "types": [
+            {
+                "name": "Vault",
+                "type": {
+                    "kind": "struct",
+                    "fields": [
+                        { "name": "owner", "type": "pubkey" },
+                        { "name": "fee", "type": { "defined": { "name": "Fee" } } },
+                        { "name": "data", "type": "bytes" }
+                    ]
+                }
+            },
+            {
+                "name": "Fee",
+                "type": { "kind": "struct", "fields": ["u16", "i32"] }
+            },
+            {
+                "name": "Position",
+                "serialization": "bytemuck",
+                "repr": { "kind": "c" },
+                "type": { "kind": "struct", "fields": [{ "name": "amount", "type": "u64" }] }
+            }
+        ]
+    }"#;
+
+    /// The decoding of Anchor accounts with the IDLs of both formats and the reload of the IDL directory,
+    /// both change the global [ANCHOR_IDLS] so they run in the same test
+    #[test]
+    fn decode_anchor_accounts() -> anyhow::Result<()> {
+        ANCHOR_IDLS.insert(&key(LEGACY_PROGRAM), AnchorIdl::from_json(LEGACY_IDL)?);
+        ANCHOR_IDLS.insert(&key(PROGRAM), AnchorIdl::from_json(IDL)?);
+
+        // An IDL without explicit discriminators
+        let mut data = anchor_account_discriminator("Counter").to_vec();
+        data.extend_from_slice(&AUTHORITY);
+        data.extend_from_slice(&42u64.to_le_bytes());
+        data.extend_from_slice(&u128::MAX.to_le_bytes());
+        data.extend_from_slice(&5u32.to_le_bytes());
+        data.extend_from_slice(b"hello");
+        data.extend_from_slice(&[1, 254]);
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&(-1i16).to_le_bytes());
+        data.extend_from_slice(&7i16.to_le_bytes());
+        data.extend_from_slice(&[9, 8, 7, 6]);
+        data.push(1);
+        data.extend_from_slice(&(-5i64).to_le_bytes());
+        // Space reserved after the fields is ignored
+        data.extend_from_slice(&[0; 16]);
+
+        assert_eq!(
+            parse(&key(LEGACY_PROGRAM), &data)?,
+            json!({
+                "program": "counter",
+                "parsed": {
+                    "type": "Counter",
+                    "info": {
+                        "authority": key(AUTHORITY),
+                        "count": 42,
+                        "total": u128::MAX.to_string(),
+                        "label": "hello",
+                        "bump": 254,
+                        "history": [-1, 7],
+                        "seed": [9, 8, 7, 6],
+                        "state": { "Locked": { "until": -5 } }
+                    }
+                },
+                "space": data.len()
+            })
+        );
+
+        // Enum variants with tuple fields and without fields
+        let state_offset = data.len() - 16 - 9;
+        let mut moved = data[..state_offset].to_vec();
+        moved.push(2);
+        moved.extend_from_slice(&AUTHORITY);
+        moved.push(0);
+        assert_eq!(
+            parse(&key(LEGACY_PROGRAM), &moved)?["parsed"]["info"]["state"],
+            json!({ "Moved": [key(AUTHORITY), false] })
+        );
+        let mut idle = data[..state_offset].to_vec();
+        idle.push(0);
+        assert_eq!(
+            parse(&key(LEGACY_PROGRAM), &idle)?["parsed"]["info"]["state"],
+            json!({ "Idle": {} })
+        );
+
+        // An IDL with explicit discriminators and the account types in `types`
+        let mut data = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        data.extend_from_slice(&AUTHORITY);
+        data.extend_from_slice(&300u16.to_le_bytes());
+        data.extend_from_slice(&(-2i32).to_le_bytes());
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.extend_from_slice(&[1, 2, 3]);
+        assert_eq!(
+            parse(&key(PROGRAM), &data)?,
+            json!({
+                "program": "vault",
+                "parsed": {
+                    "type": "Vault",
+                    "info": {
+                        "owner": key(AUTHORITY),
+                        "fee": [300, -2],
+                        "data": "AQID"
+                    }
+                },
+                "space": 53
+            })
+        );
+
+        let not_parsable = Err(ParseAccountError::AccountNotParsable(
+            ParsableAccount::Anchor,
+        ));
+
+        // Truncated data
+        assert_eq!(
+            parse_account_data(
+                ACCOUNT,
+                &key(PROGRAM),
+                &data[..data.len() - 1],
+                &AdditionalData::default()
+            ),
+            not_parsable
+        );
+
+        // Accounts that are not `borsh` serialized are not supported
+        let mut position = vec![8, 7, 6, 5, 4, 3, 2, 1];
+        position.extend_from_slice(&1u64.to_le_bytes());
+        assert_eq!(
+            parse_account_data(
+                ACCOUNT,
+                &key(PROGRAM),
+                &position,
+                &AdditionalData::default()
+            ),
+            not_parsable
+        );
+
+        // An unknown discriminator is returned as `base64`
+        let unknown = vec![0u8; 16];
+        assert_eq!(
+            parse_account_data(ACCOUNT, &key(PROGRAM), &unknown, &AdditionalData::default()),
+            not_parsable
+        );
+        let account = Account {
+            data: unknown,
+            executable: false,
+            lamports: 1,
+            owner: key(PROGRAM),
+            rent_epoch: 0,
+        };
+        let encoded = account
+            .to_json_value(ACCOUNT, Encoding::JsonParsed, &AdditionalData::default())
+            .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        assert_eq!(
+            encoded["data"],
+            json!(["AAAAAAAAAAAAAAAAAAAAAA==", "base64"])
+        );
+
+        ANCHOR_IDLS.remove(&key(PROGRAM));
+        assert_eq!(
+            parse_account_data(ACCOUNT, &key(PROGRAM), &data, &AdditionalData::default()),
+            Err(ParseAccountError::ProgramNotParsable)
+        );
+
+        idl_dir_reload()?;
+
+        Ok(())
+    }
+
+    /// IDLs added, changed and removed from the IDL directory are picked up by a reload
+    fn idl_dir_reload() -> anyhow::Result<()> {
+        let idl_dir = std::env::temp_dir().join(format!("anchor-idls-{}", std::process::id()));
+        fs::create_dir_all(&idl_dir)?;
+
+        let anchor_config: AnchorConfig = toml::from_str(&format!(
+            "idl_dir = {:?}\nreload_interval = 1",
+            idl_dir.display().to_string()
+        ))?;
+        assert!(anchor_config.is_enabled());
+
+        let program = key(DIR_PROGRAM);
+        let idl_path = idl_dir.join(format!("{}.json", program));
+        let mut data = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        data.extend_from_slice(&AUTHORITY);
+        data.extend_from_slice(&[0; 6]);
+        data.extend_from_slice(&0u32.to_le_bytes());
+
+        ANCHOR_IDLS.reload(&anchor_config);
+        assert!(ANCHOR_IDLS.get(&program).is_none());
+
+        // A new IDL
+        fs::write(&idl_path, IDL)?;
+        // Files that are not IDLs of a program are ignored
+        fs::write(idl_dir.join("notes.txt"), "")?;
+        fs::write(idl_dir.join("not-a-program-id.json"), IDL)?;
+        ANCHOR_IDLS.reload(&anchor_config);
+        assert_eq!(parse(&program, &data)?["program"], "vault");
+        assert!(ANCHOR_IDLS.get("not-a-program-id").is_none());
+
+        // An invalid IDL keeps the previous IDL
+        fs::write(&idl_path, "{")?;
+        touch(&idl_path, 10)?;
+        ANCHOR_IDLS.reload(&anchor_config);
+        assert_eq!(parse(&program, &data)?["program"], "vault");
+
+        // A changed IDL
+        fs::write(&idl_path, IDL.replace("\"vault\"", "\"vault_v2\""))?;
+        touch(&idl_path, 20)?;
+        ANCHOR_IDLS.reload(&anchor_config);
+        assert_eq!(parse(&program, &data)?["program"], "vault_v2");
+
+        // A removed IDL
+        fs::remove_file(&idl_path)?;
+        ANCHOR_IDLS.reload(&anchor_config);
+        assert!(ANCHOR_IDLS.get(&program).is_none());
+
+        fs::remove_dir_all(&idl_dir)?;
+
+        Ok(())
+    }
+
+    /// Move the modification time of a file forward, files written within the
+    /// resolution of the file system clock would look unchanged otherwise
+    fn touch(path: &std::path::Path, seconds: u64) -> anyhow::Result<()> {
+        fs::File::options()
+            .write(true)
+            .open(path)?
+            .set_modified(SystemTime::now() + Duration::from_secs(seconds))?;
+
+        Ok(())
+    }
+
+    fn parse(owner: &str, data: &[u8]) -> anyhow::Result<serde_json::Value> {
+        let parsed = parse_account_data(ACCOUNT, owner, data, &AdditionalData::default())
+            .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+
+        Ok(serde_json::to_value(parsed)?)
+    }
+
+    fn key(bytes: [u8; 32]) -> String {
+        bs58::encode(bytes).into_string()
+    }
+}
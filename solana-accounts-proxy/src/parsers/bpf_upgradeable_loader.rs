@@ -0,0 +1,215 @@
+use crate::{AccountDataReader, ParseAccountError, ParseResult};
+use serde::Serialize;
+
+/// The length of the metadata of a buffer account with an authority
+const BUFFER_METADATA_LENGTH: usize = 37;
+/// The length of the metadata of a program data account with an authority
+const PROGRAM_DATA_METADATA_LENGTH: usize = 45;
+/// The length of the public key of an authority
+const AUTHORITY_LENGTH: usize = 32;
+
+/// A parsed account of the BPF upgradeable loader
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "type", content = "info")]
+pub enum BpfUpgradeableLoaderAccountType {
+    /// The account has not been initialized
+    Uninitialized,
+    /// A buffer the program is written to before it is deployed
+    Buffer(UiBuffer),
+    /// A program, its executable is in the program data account
+    Program(UiProgram),
+    /// The executable of a program
+    ProgramData(UiProgramData),
+}
+
+/// A buffer account
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiBuffer {
+    /// The authority allowed to write to the buffer
+    pub authority: Option<String>,
+    /// The data written to the buffer, encoded as `[<base64 data>, "base64"]`
+    pub data: (String, &'static str),
+}
+
+/// A program account
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiProgram {
+    /// The program data account holding the executable
+    pub program_data: String,
+}
+
+/// A program data account
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiProgramData {
+    /// The slot the program was last deployed at
+    pub slot: u64,
+    /// The authority allowed to upgrade the program, `null` if it is immutable
+    pub authority: Option<String>,
+    /// The executable of the program, encoded as `[<base64 data>, "base64"]`
+    pub data: (String, &'static str),
+}
+
+/// Parse an account of the BPF upgradeable loader, the `bincode` layout of `UpgradeableLoaderState`
+pub fn parse_bpf_upgradeable_loader(data: &[u8]) -> ParseResult<BpfUpgradeableLoaderAccountType> {
+    let mut reader = AccountDataReader::new(data);
+
+    let account = match reader.u32()? {
+        0 => BpfUpgradeableLoaderAccountType::Uninitialized,
+        1 => {
+            let authority = reader.option_pubkey()?;
+
+            BpfUpgradeableLoaderAccountType::Buffer(UiBuffer {
+                data: trailing_data(data, BUFFER_METADATA_LENGTH, authority.is_some())?,
+                authority,
+            })
+        }
+        2 => BpfUpgradeableLoaderAccountType::Program(UiProgram {
+            program_data: reader.pubkey()?,
+        }),
+        3 => {
+            let slot = reader.u64()?;
+            let authority = reader.option_pubkey()?;
+
+            BpfUpgradeableLoaderAccountType::ProgramData(UiProgramData {
+                slot,
+                data: trailing_data(data, PROGRAM_DATA_METADATA_LENGTH, authority.is_some())?,
+                authority,
+            })
+        }
+        _ => return Err(ParseAccountError::InvalidData),
+    };
+
+    Ok(account)
+}
+
+/// The data after the metadata. Like the Solana account-decoder, the metadata without an
+/// authority ends where the public key of the authority would start.
+fn trailing_data(
+    data: &[u8],
+    metadata_length: usize,
+    has_authority: bool,
+) -> ParseResult<(String, &'static str)> {
+    let offset = if has_authority {
+        metadata_length
+    } else {
+        metadata_length - AUTHORITY_LENGTH
+    };
+
+    let trailing_data = data.get(offset..).ok_or(ParseAccountError::InvalidData)?;
+
+    Ok((base64::encode(trailing_data), "base64"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parse_account_data, AdditionalData, ParsedAccount, BPF_UPGRADEABLE_LOADER_PROGRAM_ID,
+    };
+    use serde_json::json;
+
+    const ACCOUNT: &str = "YMN9Qj5jPNp7j14VPcML1B6xGgcPWVZUGLFU3Mnyfaf";
+
+    #[test]
+    fn parse_bpf_upgradeable_loader_accounts() -> anyhow::Result<()> {
+        let executable = b"\x7fELF";
+
+        let mut data = 2u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&[9; 32]);
+        assert_eq!(
+            parse_account_data(
+                ACCOUNT,
+                BPF_UPGRADEABLE_LOADER_PROGRAM_ID,
+                &data,
+                &AdditionalData::default(),
+            ),
+            Ok(ParsedAccount {
+                program: "bpfUpgradeableLoader".to_owned(),
+                parsed: json!({
+                    "type": "program",
+                    "info": { "programData": "cGfHiC6Kgg3FpFZvgwGcswsCRtp4aBP2fzuXRQPizuN" }
+                }),
+                space: 36,
+            })
+        );
+
+        // An upgradeable program
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&250_000_000u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(&[5; 32]);
+        data.extend_from_slice(executable);
+        let parsed = parse_account_data(
+            ACCOUNT,
+            BPF_UPGRADEABLE_LOADER_PROGRAM_ID,
+            &data,
+            &AdditionalData::default(),
+        )
+        .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        assert_eq!(
+            parsed.parsed,
+            json!({
+                "type": "programData",
+                "info": {
+                    "slot": 250000000,
+                    "authority": "LbUiWL3xVV8hTFYBVdbTNrpDo41NKS6o3LHHuDzjfcY",
+                    "data": [base64::encode(executable), "base64"]
+                }
+            })
+        );
+
+        // An immutable program, the data starts where the authority would be
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&250_000_000u64.to_le_bytes());
+        data.push(0);
+        data.extend_from_slice(&[0; 32]);
+        data.extend_from_slice(executable);
+        let parsed = parse_account_data(
+            ACCOUNT,
+            BPF_UPGRADEABLE_LOADER_PROGRAM_ID,
+            &data,
+            &AdditionalData::default(),
+        )
+        .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        assert_eq!(parsed.parsed["info"]["authority"], serde_json::Value::Null);
+        assert_eq!(
+            parsed.parsed["info"]["data"],
+            json!([base64::encode(&data[13..]), "base64"])
+        );
+
+        let mut data = 1u32.to_le_bytes().to_vec();
+        data.push(1);
+        data.extend_from_slice(&[5; 32]);
+        data.extend_from_slice(executable);
+        let parsed = parse_account_data(
+            ACCOUNT,
+            BPF_UPGRADEABLE_LOADER_PROGRAM_ID,
+            &data,
+            &AdditionalData::default(),
+        )
+        .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        assert_eq!(
+            parsed.parsed,
+            json!({
+                "type": "buffer",
+                "info": {
+                    "authority": "LbUiWL3xVV8hTFYBVdbTNrpDo41NKS6o3LHHuDzjfcY",
+                    "data": [base64::encode(executable), "base64"]
+                }
+            })
+        );
+
+        let parsed = parse_account_data(
+            ACCOUNT,
+            BPF_UPGRADEABLE_LOADER_PROGRAM_ID,
+            &[0; 4],
+            &AdditionalData::default(),
+        )
+        .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        assert_eq!(parsed.parsed, json!({ "type": "uninitialized" }));
+
+        Ok(())
+    }
+}
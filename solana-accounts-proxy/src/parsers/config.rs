@@ -0,0 +1,161 @@
+use crate::{AccountDataReader, ParseAccountError, ParseResult};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+/// The public key of the stake config account
+pub const STAKE_CONFIG: &str = "StakeConfig11111111111111111111111111111111";
+/// The first key of the validator info accounts
+pub const VALIDATOR_INFO: &str = "Va1idator1nfo111111111111111111111111111111";
+
+/// A parsed Config program account
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "type", content = "info")]
+pub enum ConfigAccountType {
+    /// The stake config account
+    StakeConfig(UiStakeConfig),
+    /// The information published by a validator
+    ValidatorInfo(UiConfig),
+}
+
+/// The stake config
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiStakeConfig {
+    /// The rate at which stake is activated and deactivated
+    pub warmup_cooldown_rate: f64,
+    /// The percentage of stake slashed
+    pub slash_penalty: u8,
+}
+
+/// A config account with its data as JSON
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiConfig {
+    /// The keys of the config account
+    pub keys: Vec<UiConfigKey>,
+    /// The JSON data of the config account
+    pub config_data: JsonValue,
+}
+
+/// A key of a config account
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiConfigKey {
+    /// The public key
+    pub pubkey: String,
+    /// Whether the key must sign to update the account
+    pub signer: bool,
+}
+
+/// Parse a Config program account, the stake config is identified by the `pubkey`
+/// of the account and the validator info by its first key
+pub fn parse_config(pubkey: &str, data: &[u8]) -> ParseResult<ConfigAccountType> {
+    let mut reader = AccountDataReader::new(data);
+    let keys = config_keys(&mut reader)?;
+
+    if pubkey == STAKE_CONFIG {
+        return Ok(ConfigAccountType::StakeConfig(UiStakeConfig {
+            warmup_cooldown_rate: reader.f64()?,
+            slash_penalty: reader.u8()?,
+        }));
+    }
+
+    match keys.first() {
+        Some(key) if key.pubkey == VALIDATOR_INFO => {
+            let config_data = serde_json::from_str(&reader.bincode_string()?)
+                .map_err(|_| ParseAccountError::InvalidData)?;
+
+            Ok(ConfigAccountType::ValidatorInfo(UiConfig {
+                keys,
+                config_data,
+            }))
+        }
+        _ => Err(ParseAccountError::InvalidData),
+    }
+}
+
+/// The `ConfigKeys` written before the data of every config account, a `short_vec`
+/// of public keys with whether they are signers
+fn config_keys(reader: &mut AccountDataReader) -> ParseResult<Vec<UiConfigKey>> {
+    let mut keys = Vec::new();
+    for _ in 0..reader.short_vec_length()? {
+        keys.push(UiConfigKey {
+            pubkey: reader.pubkey()?,
+            signer: reader.bool()?,
+        });
+    }
+
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parse_account_data, AdditionalData, ParsableAccount, ParseAccountError, ParsedAccount,
+        CONFIG_PROGRAM_ID,
+    };
+    use serde_json::json;
+
+    const ACCOUNT: &str = "YMN9Qj5jPNp7j14VPcML1B6xGgcPWVZUGLFU3Mnyfaf";
+
+    #[test]
+    fn parse_config_accounts() -> anyhow::Result<()> {
+        // The stake config is identified by its public key
+        assert_eq!(
+            parse_account_data(
+                "StakeConfig11111111111111111111111111111111",
+                CONFIG_PROGRAM_ID,
+                &base64::decode("AAAAAAAAANA/DA==")?,
+                &AdditionalData::default()
+            ),
+            Ok(ParsedAccount {
+                program: "config".to_owned(),
+                parsed: json!({
+                    "type": "stakeConfig",
+                    "info": { "warmupCooldownRate": 0.25, "slashPenalty": 12 }
+                }),
+                space: 10,
+            })
+        );
+
+        // A validator info is identified by its first key
+        let validator_info = "AgdRlwF0SPKsXcI8nrx6x4wKJyV6xhRFjeCk8W+AAAAAAAUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFAToAAAAAAAAAeyJuYW1lIjoiUHJveHkgVmFsaWRhdG9yIiwid2Vic2l0ZSI6Imh0dHBzOi8vZXhhbXBsZS5jb20ifQ==";
+        let parsed = parse_account_data(
+            ACCOUNT,
+            CONFIG_PROGRAM_ID,
+            &base64::decode(validator_info)?,
+            &AdditionalData::default(),
+        )
+        .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        assert_eq!(
+            parsed.parsed,
+            json!({
+                "type": "validatorInfo",
+                "info": {
+                    "keys": [
+                        { "pubkey": "Va1idator1nfo111111111111111111111111111111", "signer": false },
+                        { "pubkey": "LbUiWL3xVV8hTFYBVdbTNrpDo41NKS6o3LHHuDzjfcY", "signer": true }
+                    ],
+                    "configData": { "name": "Proxy Validator", "website": "https://example.com" }
+                }
+            })
+        );
+
+        // Any other config account cannot be parsed
+        let mut data = base64::decode(validator_info)?;
+        data[1..33].copy_from_slice(&[5; 32]);
+        assert_eq!(
+            parse_account_data(
+                ACCOUNT,
+                CONFIG_PROGRAM_ID,
+                &data,
+                &AdditionalData::default()
+            ),
+            Err(ParseAccountError::AccountNotParsable(
+                ParsableAccount::Config
+            ))
+        );
+
+        Ok(())
+    }
+}
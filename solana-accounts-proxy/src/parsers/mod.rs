@@ -0,0 +1,221 @@
+mod account_data_reader;
+pub use account_data_reader::*;
+
+mod anchor;
+pub use anchor::*;
+
+mod address_lookup_table;
+pub use address_lookup_table::*;
+
+mod bpf_upgradeable_loader;
+pub use bpf_upgradeable_loader::*;
+
+mod config;
+pub use config::*;
+
+mod nonce;
+pub use nonce::*;
+
+mod spl_token;
+pub use spl_token::*;
+
+mod stake;
+pub use stake::*;
+
+mod sysvar;
+pub use sysvar::*;
+
+mod token_metadata;
+pub use token_metadata::*;
+
+mod token_2022_extensions;
+pub use token_2022_extensions::*;
+
+mod vote;
+pub use vote::*;
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+
+/// The program ID of the SPL Token program
+pub const SPL_TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+/// The program ID of the SPL Token-2022 program
+pub const SPL_TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+/// The program ID of the System program, the owner of durable nonce accounts
+pub const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+/// The program ID of the Stake program
+pub const STAKE_PROGRAM_ID: &str = "Stake11111111111111111111111111111111111111";
+/// The program ID of the Vote program
+pub const VOTE_PROGRAM_ID: &str = "Vote111111111111111111111111111111111111111";
+/// The owner of the sysvar accounts
+pub const SYSVAR_PROGRAM_ID: &str = "Sysvar1111111111111111111111111111111111111";
+/// The program ID of the Config program
+pub const CONFIG_PROGRAM_ID: &str = "Config1111111111111111111111111111111111111";
+/// The program ID of the BPF upgradeable loader
+pub const BPF_UPGRADEABLE_LOADER_PROGRAM_ID: &str = "BPFLoaderUpgradeab1e11111111111111111111111";
+/// The program ID of the Address Lookup Table program
+pub const ADDRESS_LOOKUP_TABLE_PROGRAM_ID: &str = "AddressLookupTab1e1111111111111111111111111";
+/// The program ID of the Metaplex Token Metadata program
+pub const TOKEN_METADATA_PROGRAM_ID: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
+
+lazy_static::lazy_static! {
+    /// The programs whose accounts can be returned with the `jsonParsed` encoding, keyed by the program ID
+    pub static ref PARSABLE_PROGRAM_IDS: HashMap<&'static str, ParsableAccount> = {
+        let mut programs = HashMap::new();
+        programs.insert(SPL_TOKEN_PROGRAM_ID, ParsableAccount::SplToken);
+        programs.insert(SPL_TOKEN_2022_PROGRAM_ID, ParsableAccount::SplToken2022);
+        programs.insert(SYSTEM_PROGRAM_ID, ParsableAccount::Nonce);
+        programs.insert(STAKE_PROGRAM_ID, ParsableAccount::Stake);
+        programs.insert(VOTE_PROGRAM_ID, ParsableAccount::Vote);
+        programs.insert(SYSVAR_PROGRAM_ID, ParsableAccount::Sysvar);
+        programs.insert(CONFIG_PROGRAM_ID, ParsableAccount::Config);
+        programs.insert(
+            BPF_UPGRADEABLE_LOADER_PROGRAM_ID,
+            ParsableAccount::BpfUpgradeableLoader,
+        );
+        programs.insert(
+            ADDRESS_LOOKUP_TABLE_PROGRAM_ID,
+            ParsableAccount::AddressLookupTable,
+        );
+        programs.insert(TOKEN_METADATA_PROGRAM_ID, ParsableAccount::TokenMetadata);
+
+        programs
+    };
+}
+
+/// Result type of the account parsers
+pub type ParseResult<T> = Result<T, ParseAccountError>;
+
+/// The reason an account could not be parsed, the account is then returned as `base64`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseAccountError {
+    /// The owner of the account has no parser
+    ProgramNotParsable,
+    /// The data is not one of the accounts of the program
+    AccountNotParsable(ParsableAccount),
+    /// Parsing the account requires another account which could not be loaded
+    AdditionalDataMissing(&'static str),
+    /// The data is shorter than the layout or holds an invalid value
+    InvalidData,
+}
+
+/// The programs with a `jsonParsed` parser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParsableAccount {
+    /// The SPL Token program
+    SplToken,
+    /// The SPL Token-2022 program
+    SplToken2022,
+    /// Durable nonce accounts of the System program
+    Nonce,
+    /// The Stake program
+    Stake,
+    /// The Vote program
+    Vote,
+    /// The sysvars
+    Sysvar,
+    /// The Config program
+    Config,
+    /// The BPF upgradeable loader
+    BpfUpgradeableLoader,
+    /// The Address Lookup Table program
+    AddressLookupTable,
+    /// The Metaplex Token Metadata program
+    TokenMetadata,
+    /// A program with an Anchor IDL in the `[anchor]` section
+    Anchor,
+}
+
+impl ParsableAccount {
+    /// The name of the program in the `program` field of a parsed account
+    pub fn program_name(&self) -> &'static str {
+        match self {
+            Self::SplToken => "spl-token",
+            Self::SplToken2022 => "spl-token-2022",
+            Self::Nonce => "nonce",
+            Self::Stake => "stake",
+            Self::Vote => "vote",
+            Self::Sysvar => "sysvar",
+            Self::Config => "config",
+            Self::BpfUpgradeableLoader => "bpfUpgradeableLoader",
+            Self::AddressLookupTable => "addressLookupTable",
+            Self::TokenMetadata => "tokenMetadata",
+            Self::Anchor => "anchor",
+        }
+    }
+}
+
+/// Accounts other than the one being parsed that are needed by some parsers
+#[derive(Debug, Default, Clone)]
+pub struct AdditionalData {
+    /// The mint of a token account
+    pub spl_token: Option<SplTokenAdditionalData>,
+}
+
+/// The account data in the `jsonParsed` encoding, same shape as the Solana account-decoder
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ParsedAccount {
+    /// The name of the program that owns the account
+    pub program: String,
+    /// The decoded account
+    pub parsed: JsonValue,
+    /// The length of the account data in bytes
+    pub space: u64,
+}
+
+/// Parse the data of an account owned by one of the [PARSABLE_PROGRAM_IDS] or by a program
+/// with an Anchor IDL, the `pubkey` of the account tells the sysvars and config accounts apart
+pub fn parse_account_data(
+    pubkey: &str,
+    owner: &str,
+    data: &[u8],
+    additional_data: &AdditionalData,
+) -> ParseResult<ParsedAccount> {
+    // The accounts of the other programs can only be parsed with an Anchor IDL
+    let program = PARSABLE_PROGRAM_IDS
+        .get(owner)
+        .copied()
+        .unwrap_or(ParsableAccount::Anchor);
+
+    // Data that does not match the layouts of the program is reported as not parsable
+    let not_parsable = |error| match error {
+        ParseAccountError::InvalidData => ParseAccountError::AccountNotParsable(program),
+        error => error,
+    };
+
+    let parsed = match program {
+        ParsableAccount::SplToken | ParsableAccount::SplToken2022 => {
+            let token_account =
+                parse_token(data, additional_data.spl_token.as_ref()).map_err(not_parsable)?;
+
+            serde_json::to_value(token_account)
+        }
+        ParsableAccount::Nonce => serde_json::to_value(parse_nonce(data).map_err(not_parsable)?),
+        ParsableAccount::Stake => serde_json::to_value(parse_stake(data).map_err(not_parsable)?),
+        ParsableAccount::Vote => serde_json::to_value(parse_vote(data).map_err(not_parsable)?),
+        ParsableAccount::Sysvar => {
+            serde_json::to_value(parse_sysvar(pubkey, data).map_err(not_parsable)?)
+        }
+        ParsableAccount::Config => {
+            serde_json::to_value(parse_config(pubkey, data).map_err(not_parsable)?)
+        }
+        ParsableAccount::BpfUpgradeableLoader => {
+            serde_json::to_value(parse_bpf_upgradeable_loader(data).map_err(not_parsable)?)
+        }
+        ParsableAccount::AddressLookupTable => {
+            serde_json::to_value(parse_address_lookup_table(data).map_err(not_parsable)?)
+        }
+        ParsableAccount::TokenMetadata => {
+            serde_json::to_value(parse_token_metadata(data).map_err(not_parsable)?)
+        }
+        ParsableAccount::Anchor => return ANCHOR_IDLS.parse_account_data(owner, data),
+    }
+    .map_err(|_| ParseAccountError::AccountNotParsable(program))?;
+
+    Ok(ParsedAccount {
+        program: program.program_name().to_owned(),
+        parsed,
+        space: data.len() as u64,
+    })
+}
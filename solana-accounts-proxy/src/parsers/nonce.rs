@@ -0,0 +1,103 @@
+use crate::{AccountDataReader, ParseAccountError, ParseResult};
+use serde::Serialize;
+
+/// A parsed durable nonce account
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "type", content = "info")]
+pub enum UiNonceState {
+    /// An initialized nonce account. An uninitialized nonce account cannot be told apart
+    /// from any other System account of the same length so it is never parsed.
+    Initialized(UiNonceData),
+}
+
+/// The state of an initialized nonce account
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiNonceData {
+    /// The authority allowed to advance the nonce
+    pub authority: String,
+    /// The durable nonce
+    pub blockhash: String,
+    /// The fee of the transactions using the nonce
+    pub fee_calculator: UiFeeCalculator,
+}
+
+/// The fee of a transaction
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiFeeCalculator {
+    /// The lamports paid for each signature
+    pub lamports_per_signature: String,
+}
+
+/// Parse a durable nonce account, the `bincode` layout of `nonce::state::Versions`
+pub fn parse_nonce(data: &[u8]) -> ParseResult<UiNonceState> {
+    let mut reader = AccountDataReader::new(data);
+
+    // Both the `Legacy` and the `Current` versions have the same layout
+    if reader.u32()? > 1 {
+        return Err(ParseAccountError::InvalidData);
+    }
+
+    match reader.u32()? {
+        1 => Ok(UiNonceState::Initialized(UiNonceData {
+            authority: reader.pubkey()?,
+            blockhash: reader.hash()?,
+            fee_calculator: UiFeeCalculator {
+                lamports_per_signature: reader.u64()?.to_string(),
+            },
+        })),
+        _ => Err(ParseAccountError::InvalidData),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parse_account_data, AdditionalData, ParsableAccount, ParseAccountError, ParsedAccount,
+        SYSTEM_PROGRAM_ID,
+    };
+    use serde_json::json;
+
+    const ACCOUNT: &str = "YMN9Qj5jPNp7j14VPcML1B6xGgcPWVZUGLFU3Mnyfaf";
+
+    #[test]
+    fn parse_nonce_accounts() -> anyhow::Result<()> {
+        // Durable nonce accounts are owned by the System program
+        let nonce = "AQAAAAEAAAAFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJiBMAAAAAAAA=";
+        assert_eq!(
+            parse_account_data(
+                ACCOUNT,
+                SYSTEM_PROGRAM_ID,
+                &base64::decode(nonce)?,
+                &AdditionalData::default()
+            ),
+            Ok(ParsedAccount {
+                program: "nonce".to_owned(),
+                parsed: json!({
+                    "type": "initialized",
+                    "info": {
+                        "authority": "LbUiWL3xVV8hTFYBVdbTNrpDo41NKS6o3LHHuDzjfcY",
+                        "blockhash": "cGfHiC6Kgg3FpFZvgwGcswsCRtp4aBP2fzuXRQPizuN",
+                        "feeCalculator": { "lamportsPerSignature": "5000" }
+                    }
+                }),
+                space: 80,
+            })
+        );
+        // An uninitialized nonce account is any System account of 80 zero bytes
+        assert_eq!(
+            parse_account_data(
+                ACCOUNT,
+                SYSTEM_PROGRAM_ID,
+                &[0; 80],
+                &AdditionalData::default()
+            ),
+            Err(ParseAccountError::AccountNotParsable(
+                ParsableAccount::Nonce
+            ))
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,727 @@
+use crate::{
+    parse_extensions, AccountDataReader, ParsableAccount, ParseAccountError, ParseResult,
+    PARSABLE_PROGRAM_IDS,
+};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+/// The length of an SPL Token account without extensions
+pub const TOKEN_ACCOUNT_LENGTH: usize = 165;
+/// The length of an SPL Token mint without extensions
+pub const MINT_LENGTH: usize = 82;
+/// The length of an SPL Token multisig
+pub const MULTISIG_LENGTH: usize = 355;
+/// The maximum number of signers of a multisig
+pub const MAX_SIGNERS: usize = 11;
+/// The offset of the mint in a token account
+pub const TOKEN_ACCOUNT_MINT_OFFSET: usize = 0;
+/// The offset of the owner in a token account
+pub const TOKEN_ACCOUNT_OWNER_OFFSET: usize = 32;
+/// The offset of the amount in a token account
+pub const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+/// The offset of the `COption` tag of the delegate in a token account, the delegate follows the 4 byte tag
+pub const TOKEN_ACCOUNT_DELEGATE_OFFSET: usize = 72;
+/// The offset of the state in a token account, `0` is an uninitialized account
+pub const TOKEN_ACCOUNT_STATE_OFFSET: usize = 108;
+
+/// The `AccountType` byte written after the base state of a Token-2022 mint with extensions
+const ACCOUNT_TYPE_MINT: u8 = 1;
+/// The `AccountType` byte written after the base state of a Token-2022 account with extensions
+pub const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+/// The data of the mint needed to parse a token account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplTokenAdditionalData {
+    /// The number of decimals of the mint
+    pub decimals: u8,
+}
+
+/// A parsed SPL Token or Token-2022 account
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "type", content = "info")]
+pub enum TokenAccountType {
+    /// A token account
+    Account(Box<UiTokenAccount>),
+    /// A mint
+    Mint(UiMint),
+    /// A multisig
+    Multisig(UiMultisig),
+}
+
+/// A parsed token account
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiTokenAccount {
+    /// The mint of the tokens held by the account
+    pub mint: String,
+    /// The owner of the tokens
+    pub owner: String,
+    /// The number of tokens held by the account
+    pub token_amount: UiTokenAmount,
+    /// The delegate allowed to transfer `delegatedAmount` tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delegate: Option<String>,
+    /// The state of the account
+    pub state: UiAccountState,
+    /// Whether the account holds wrapped SOL
+    pub is_native: bool,
+    /// The lamports kept for rent exemption by a wrapped SOL account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rent_exempt_reserve: Option<UiTokenAmount>,
+    /// The number of tokens the delegate is allowed to transfer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delegated_amount: Option<UiTokenAmount>,
+    /// The authority allowed to close the account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub close_authority: Option<String>,
+    /// The Token-2022 extensions of the account
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<JsonValue>,
+}
+
+/// The state of a token account
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum UiAccountState {
+    /// The account has not been initialized
+    Uninitialized,
+    /// The account is initialized
+    Initialized,
+    /// The account is frozen by the freeze authority of the mint
+    Frozen,
+}
+
+impl UiAccountState {
+    /// Convert the `AccountState` byte of the SPL Token program
+    pub fn from_u8(state: u8) -> ParseResult<Self> {
+        match state {
+            0 => Ok(Self::Uninitialized),
+            1 => Ok(Self::Initialized),
+            2 => Ok(Self::Frozen),
+            _ => Err(ParseAccountError::InvalidData),
+        }
+    }
+}
+
+/// A parsed mint
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiMint {
+    /// The authority allowed to mint new tokens
+    pub mint_authority: Option<String>,
+    /// The total supply of tokens
+    pub supply: String,
+    /// The number of decimals of the token amounts
+    pub decimals: u8,
+    /// Whether the mint is initialized
+    pub is_initialized: bool,
+    /// The authority allowed to freeze the token accounts
+    pub freeze_authority: Option<String>,
+    /// The Token-2022 extensions of the mint
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<JsonValue>,
+}
+
+/// A parsed multisig
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiMultisig {
+    /// The number of signers required
+    pub num_required_signers: u8,
+    /// The number of valid signers
+    pub num_valid_signers: u8,
+    /// Whether the multisig is initialized
+    pub is_initialized: bool,
+    /// The signers of the multisig
+    pub signers: Vec<String>,
+}
+
+/// A token amount, in the base units and in the units of the mint
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiTokenAmount {
+    /// The amount as a float, `null` if the decimals overflow
+    pub ui_amount: Option<f64>,
+    /// The number of decimals of the mint
+    pub decimals: u8,
+    /// The raw amount in the base units
+    pub amount: String,
+    /// The amount with the decimals applied and without trailing zeros
+    pub ui_amount_string: String,
+}
+
+impl UiTokenAmount {
+    /// Convert an amount in the base units with the decimals of the mint
+    pub fn new(amount: u64, decimals: u8) -> Self {
+        let ui_amount = 10u64
+            .checked_pow(decimals as u32)
+            .map(|divisor| amount as f64 / divisor as f64);
+
+        UiTokenAmount {
+            ui_amount,
+            decimals,
+            amount: amount.to_string(),
+            ui_amount_string: real_number_string_trimmed(amount, decimals),
+        }
+    }
+}
+
+/// Format an amount in the base units with `decimals` digits after the decimal point
+pub fn real_number_string(amount: u64, decimals: u8) -> String {
+    let decimals = decimals as usize;
+
+    if decimals == 0 {
+        return amount.to_string();
+    }
+
+    let mut number = format!("{:0width$}", amount, width = decimals + 1);
+    number.insert(number.len() - decimals, '.');
+
+    number
+}
+
+/// [real_number_string] without the trailing zeros and decimal point
+pub fn real_number_string_trimmed(amount: u64, decimals: u8) -> String {
+    let number = real_number_string(amount, decimals);
+
+    if decimals == 0 {
+        return number;
+    }
+
+    number
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_owned()
+}
+
+/// Parse an SPL Token or Token-2022 account, a token account needs the decimals of its mint
+pub fn parse_token(
+    data: &[u8],
+    additional_data: Option<&SplTokenAdditionalData>,
+) -> ParseResult<TokenAccountType> {
+    if let Ok((account, tlv_data)) = TokenAccount::unpack(data) {
+        let decimals = additional_data
+            .ok_or(ParseAccountError::AdditionalDataMissing(
+                "no mint_decimals provided to parse spl-token account",
+            ))?
+            .decimals;
+
+        return Ok(TokenAccountType::Account(Box::new(UiTokenAccount {
+            mint: account.mint,
+            owner: account.owner,
+            token_amount: UiTokenAmount::new(account.amount, decimals),
+            delegated_amount: account
+                .delegate
+                .as_ref()
+                .map(|_| UiTokenAmount::new(account.delegated_amount, decimals)),
+            delegate: account.delegate,
+            state: account.state,
+            is_native: account.is_native.is_some(),
+            rent_exempt_reserve: account
+                .is_native
+                .map(|reserve| UiTokenAmount::new(reserve, decimals)),
+            close_authority: account.close_authority,
+            extensions: parse_extensions(tlv_data),
+        })));
+    }
+
+    if let Ok((mint, tlv_data)) = Mint::unpack(data) {
+        return Ok(TokenAccountType::Mint(UiMint {
+            mint_authority: mint.mint_authority,
+            supply: mint.supply.to_string(),
+            decimals: mint.decimals,
+            is_initialized: mint.is_initialized,
+            freeze_authority: mint.freeze_authority,
+            extensions: parse_extensions(tlv_data),
+        }));
+    }
+
+    if data.len() == MULTISIG_LENGTH {
+        let mut reader = AccountDataReader::new(data);
+        let num_required_signers = reader.u8()?;
+        let num_valid_signers = reader.u8()?;
+        let is_initialized = reader.bool()?;
+
+        if !is_initialized {
+            return Err(ParseAccountError::InvalidData);
+        }
+
+        let mut signers = Vec::new();
+        for _ in 0..MAX_SIGNERS {
+            if let Some(signer) = reader.optional_nonzero_pubkey()? {
+                signers.push(signer);
+            }
+        }
+
+        return Ok(TokenAccountType::Multisig(UiMultisig {
+            num_required_signers,
+            num_valid_signers,
+            is_initialized,
+            signers,
+        }));
+    }
+
+    Err(ParseAccountError::InvalidData)
+}
+
+/// The mint of a token account, [Option::None] if the account is not an initialized
+/// token account owned by the SPL Token or Token-2022 program
+pub fn token_account_mint(owner: &str, data: &[u8]) -> Option<String> {
+    if !is_token_program(owner) {
+        return Option::None;
+    }
+
+    TokenAccount::unpack(data)
+        .ok()
+        .map(|(token_account, _)| token_account.mint)
+}
+
+/// The decimals of a mint, [Option::None] if the account is not an initialized
+/// mint owned by the SPL Token or Token-2022 program
+pub fn mint_decimals(owner: &str, data: &[u8]) -> Option<u8> {
+    if !is_token_program(owner) {
+        return Option::None;
+    }
+
+    Mint::unpack(data).ok().map(|(mint, _)| mint.decimals)
+}
+
+/// Whether `owner` is the SPL Token or the Token-2022 program
+pub fn is_token_program(owner: &str) -> bool {
+    matches!(
+        PARSABLE_PROGRAM_IDS.get(owner),
+        Some(ParsableAccount::SplToken | ParsableAccount::SplToken2022)
+    )
+}
+
+/// The base state of a token account
+#[derive(Debug)]
+pub(crate) struct TokenAccount {
+    pub(crate) mint: String,
+    pub(crate) owner: String,
+    pub(crate) amount: u64,
+    pub(crate) delegate: Option<String>,
+    pub(crate) state: UiAccountState,
+    pub(crate) is_native: Option<u64>,
+    pub(crate) delegated_amount: u64,
+    pub(crate) close_authority: Option<String>,
+}
+
+impl TokenAccount {
+    /// Unpack an initialized token account and return the TLV data of its extensions
+    pub(crate) fn unpack(data: &[u8]) -> ParseResult<(Self, &[u8])> {
+        let tlv_data = extensions_data(data, TOKEN_ACCOUNT_LENGTH, ACCOUNT_TYPE_ACCOUNT)?;
+
+        let mut reader = AccountDataReader::new(data);
+        let account = TokenAccount {
+            mint: reader.pubkey()?,
+            owner: reader.pubkey()?,
+            amount: reader.u64()?,
+            delegate: reader.coption_pubkey()?,
+            state: UiAccountState::from_u8(reader.u8()?)?,
+            is_native: reader.coption_u64()?,
+            delegated_amount: reader.u64()?,
+            close_authority: reader.coption_pubkey()?,
+        };
+
+        if account.state == UiAccountState::Uninitialized {
+            return Err(ParseAccountError::InvalidData);
+        }
+
+        Ok((account, tlv_data))
+    }
+}
+
+/// The base state of a mint
+#[derive(Debug)]
+pub(crate) struct Mint {
+    pub(crate) mint_authority: Option<String>,
+    pub(crate) supply: u64,
+    pub(crate) decimals: u8,
+    pub(crate) is_initialized: bool,
+    pub(crate) freeze_authority: Option<String>,
+}
+
+impl Mint {
+    /// Unpack an initialized mint and return the TLV data of its extensions
+    pub(crate) fn unpack(data: &[u8]) -> ParseResult<(Self, &[u8])> {
+        let tlv_data = extensions_data(data, MINT_LENGTH, ACCOUNT_TYPE_MINT)?;
+
+        let mut reader = AccountDataReader::new(data);
+        let mint = Mint {
+            mint_authority: reader.coption_pubkey()?,
+            supply: reader.u64()?,
+            decimals: reader.u8()?,
+            is_initialized: reader.bool()?,
+            freeze_authority: reader.coption_pubkey()?,
+        };
+
+        if !mint.is_initialized {
+            return Err(ParseAccountError::InvalidData);
+        }
+
+        Ok((mint, tlv_data))
+    }
+}
+
+/// The TLV data after the base state of `base_length` bytes.
+/// Token-2022 pads the base state to the length of a token account and writes the
+/// `AccountType` byte after it, the length of a multisig is never an account with extensions.
+fn extensions_data(data: &[u8], base_length: usize, account_type: u8) -> ParseResult<&[u8]> {
+    if data.len() == base_length {
+        return Ok(&[]);
+    }
+
+    if data.len() <= TOKEN_ACCOUNT_LENGTH || data.len() == MULTISIG_LENGTH {
+        return Err(ParseAccountError::InvalidData);
+    }
+
+    if data[base_length..TOKEN_ACCOUNT_LENGTH]
+        .iter()
+        .any(|padding| *padding != 0)
+        || data[TOKEN_ACCOUNT_LENGTH] != account_type
+    {
+        return Err(ParseAccountError::InvalidData);
+    }
+
+    Ok(&data[TOKEN_ACCOUNT_LENGTH + 1..])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parse_account_data, AdditionalData, ParsableAccount, ParseAccountError, ParsedAccount,
+        SplTokenAdditionalData, SPL_TOKEN_2022_PROGRAM_ID, SPL_TOKEN_PROGRAM_ID,
+    };
+    use serde_json::json;
+
+    const ACCOUNT: &str = "YMN9Qj5jPNp7j14VPcML1B6xGgcPWVZUGLFU3Mnyfaf";
+    const MINT: [u8; 32] = [1; 32];
+    const OWNER: [u8; 32] = [2; 32];
+    const DELEGATE: [u8; 32] = [3; 32];
+    const AUTHORITY: [u8; 32] = [4; 32];
+
+    #[test]
+    fn parse_spl_token_accounts() -> anyhow::Result<()> {
+        let [mint_address, owner, delegate, authority] = [MINT, OWNER, DELEGATE, AUTHORITY]
+            .map(|public_key| bs58::encode(public_key).into_string());
+
+        let decimals = AdditionalData {
+            spl_token: Some(SplTokenAdditionalData { decimals: 6 }),
+        };
+
+        // A delegated token account
+        let data = token_account(1_500_000, Some(DELEGATE), 1, Option::None, 250_000);
+        assert_eq!(
+            parse_account_data(ACCOUNT, SPL_TOKEN_PROGRAM_ID, &data, &decimals),
+            Ok(ParsedAccount {
+                program: "spl-token".to_owned(),
+                parsed: json!({
+                    "type": "account",
+                    "info": {
+                        "mint": mint_address,
+                        "owner": owner,
+                        "tokenAmount": {
+                            "uiAmount": 1.5,
+                            "decimals": 6,
+                            "amount": "1500000",
+                            "uiAmountString": "1.5"
+                        },
+                        "delegate": delegate,
+                        "delegatedAmount": {
+                            "uiAmount": 0.25,
+                            "decimals": 6,
+                            "amount": "250000",
+                            "uiAmountString": "0.25"
+                        },
+                        "state": "initialized",
+                        "isNative": false
+                    }
+                }),
+                space: 165,
+            })
+        );
+
+        // A frozen wrapped SOL account
+        let data = token_account(2_039_280, Option::None, 2, Some(2_039_280), 0);
+        let parsed = parse_account_data(
+            ACCOUNT,
+            SPL_TOKEN_PROGRAM_ID,
+            &data,
+            &AdditionalData {
+                spl_token: Some(SplTokenAdditionalData { decimals: 9 }),
+            },
+        )
+        .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        assert_eq!(parsed.parsed["info"]["state"], "frozen");
+        assert_eq!(parsed.parsed["info"]["isNative"], true);
+        assert_eq!(
+            parsed.parsed["info"]["rentExemptReserve"]["uiAmountString"],
+            "0.00203928"
+        );
+        assert!(parsed.parsed["info"].get("delegate").is_none());
+
+        // A token account cannot be parsed without the decimals of its mint
+        assert_eq!(
+            parse_account_data(
+                ACCOUNT,
+                SPL_TOKEN_PROGRAM_ID,
+                &data,
+                &AdditionalData::default()
+            ),
+            Err(ParseAccountError::AdditionalDataMissing(
+                "no mint_decimals provided to parse spl-token account"
+            ))
+        );
+
+        // A mint
+        let data = mint(Some(AUTHORITY), 10_000_000_000, 6, Option::None);
+        assert_eq!(
+            parse_account_data(
+                ACCOUNT,
+                SPL_TOKEN_PROGRAM_ID,
+                &data,
+                &AdditionalData::default(),
+            ),
+            Ok(ParsedAccount {
+                program: "spl-token".to_owned(),
+                parsed: json!({
+                    "type": "mint",
+                    "info": {
+                        "mintAuthority": authority,
+                        "supply": "10000000000",
+                        "decimals": 6,
+                        "isInitialized": true,
+                        "freezeAuthority": null
+                    }
+                }),
+                space: 82,
+            })
+        );
+
+        // A 2 of 3 multisig
+        let mut data = vec![2u8, 3, 1];
+        for signer in [OWNER, DELEGATE, AUTHORITY] {
+            data.extend_from_slice(&signer);
+        }
+        data.resize(355, 0);
+        let parsed = parse_account_data(
+            ACCOUNT,
+            SPL_TOKEN_PROGRAM_ID,
+            &data,
+            &AdditionalData::default(),
+        )
+        .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        assert_eq!(
+            parsed.parsed,
+            json!({
+                "type": "multisig",
+                "info": {
+                    "numRequiredSigners": 2,
+                    "numValidSigners": 3,
+                    "isInitialized": true,
+                    "signers": [owner, delegate, authority]
+                }
+            })
+        );
+
+        // A Token-2022 mint with extensions, an unknown extension and a malformed extension
+        let mut data = mint(Some(AUTHORITY), 42, 0, Some(AUTHORITY));
+        data.resize(165, 0);
+        data.push(1);
+        extension(&mut data, 3, &AUTHORITY);
+        extension(&mut data, 1, &transfer_fee_config());
+        extension(&mut data, 18, &[AUTHORITY, MINT].concat());
+        extension(&mut data, 19, &token_metadata());
+        extension(&mut data, 9, &[]);
+        extension(&mut data, 1000, &[1, 2, 3]);
+        extension(&mut data, 2, &[0; 4]);
+        // The space left for extensions that are not initialized yet
+        data.extend_from_slice(&[0; 16]);
+
+        let parsed = parse_account_data(
+            ACCOUNT,
+            SPL_TOKEN_2022_PROGRAM_ID,
+            &data,
+            &AdditionalData::default(),
+        )
+        .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        assert_eq!(parsed.program, "spl-token-2022");
+        assert_eq!(parsed.space, data.len() as u64);
+        assert_eq!(parsed.parsed["info"]["supply"], "42");
+        assert_eq!(parsed.parsed["info"]["freezeAuthority"], authority);
+        assert_eq!(
+            parsed.parsed["info"]["extensions"],
+            json!([
+                {
+                    "extension": "mintCloseAuthority",
+                    "state": { "closeAuthority": authority }
+                },
+                {
+                    "extension": "transferFeeConfig",
+                    "state": {
+                        "transferFeeConfigAuthority": authority,
+                        "withdrawWithheldAuthority": null,
+                        "withheldAmount": 7,
+                        "olderTransferFee": {
+                            "epoch": 100,
+                            "maximumFee": 5000,
+                            "transferFeeBasisPoints": 25
+                        },
+                        "newerTransferFee": {
+                            "epoch": 101,
+                            "maximumFee": 10000,
+                            "transferFeeBasisPoints": 50
+                        }
+                    }
+                },
+                {
+                    "extension": "metadataPointer",
+                    "state": { "authority": authority, "metadataAddress": mint_address }
+                },
+                {
+                    "extension": "tokenMetadata",
+                    "state": {
+                        "updateAuthority": authority,
+                        "mint": mint_address,
+                        "name": "Proxy Token",
+                        "symbol": "PRX",
+                        "uri": "https://example.com/prx.json",
+                        "additionalMetadata": [["color", "blue"]]
+                    }
+                },
+                { "extension": "nonTransferable" },
+                { "extension": "unparseableExtension" },
+                { "extension": "unparseableExtension" }
+            ])
+        );
+
+        // A Token-2022 account with extensions
+        let mut data = token_account(5, Option::None, 1, Option::None, 0);
+        data.push(2);
+        extension(&mut data, 7, &[]);
+        extension(&mut data, 2, &9u64.to_le_bytes());
+        extension(&mut data, 8, &[1]);
+        let parsed = parse_account_data(
+            ACCOUNT,
+            SPL_TOKEN_2022_PROGRAM_ID,
+            &data,
+            &AdditionalData {
+                spl_token: Some(SplTokenAdditionalData { decimals: 0 }),
+            },
+        )
+        .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        assert_eq!(parsed.parsed["info"]["tokenAmount"]["uiAmountString"], "5");
+        assert_eq!(
+            parsed.parsed["info"]["extensions"],
+            json!([
+                { "extension": "immutableOwner" },
+                { "extension": "transferFeeAmount", "state": { "withheldAmount": 9 } },
+                { "extension": "memoTransfer", "state": { "requireIncomingTransferMemos": true } }
+            ])
+        );
+
+        // The wrong `AccountType` byte after a token account
+        let mut data = token_account(5, Option::None, 1, Option::None, 0);
+        data.push(1);
+        assert_eq!(
+            parse_account_data(
+                ACCOUNT,
+                SPL_TOKEN_2022_PROGRAM_ID,
+                &data,
+                &AdditionalData::default()
+            ),
+            Err(ParseAccountError::AccountNotParsable(
+                ParsableAccount::SplToken2022
+            ))
+        );
+
+        Ok(())
+    }
+
+    fn coption_key(data: &mut Vec<u8>, key: Option<[u8; 32]>) {
+        match key {
+            Some(key) => {
+                data.extend_from_slice(&1u32.to_le_bytes());
+                data.extend_from_slice(&key);
+            }
+            None => data.extend_from_slice(&[0; 36]),
+        }
+    }
+
+    fn token_account(
+        amount: u64,
+        delegate: Option<[u8; 32]>,
+        state: u8,
+        is_native: Option<u64>,
+        delegated_amount: u64,
+    ) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&MINT);
+        data.extend_from_slice(&OWNER);
+        data.extend_from_slice(&amount.to_le_bytes());
+        coption_key(&mut data, delegate);
+        data.push(state);
+        data.extend_from_slice(&(is_native.is_some() as u32).to_le_bytes());
+        data.extend_from_slice(&is_native.unwrap_or_default().to_le_bytes());
+        data.extend_from_slice(&delegated_amount.to_le_bytes());
+        coption_key(&mut data, Option::None);
+
+        data
+    }
+
+    fn mint(
+        mint_authority: Option<[u8; 32]>,
+        supply: u64,
+        decimals: u8,
+        freeze_authority: Option<[u8; 32]>,
+    ) -> Vec<u8> {
+        let mut data = Vec::new();
+        coption_key(&mut data, mint_authority);
+        data.extend_from_slice(&supply.to_le_bytes());
+        data.push(decimals);
+        data.push(1);
+        coption_key(&mut data, freeze_authority);
+
+        data
+    }
+
+    fn extension(data: &mut Vec<u8>, extension_type: u16, value: &[u8]) {
+        data.extend_from_slice(&extension_type.to_le_bytes());
+        data.extend_from_slice(&(value.len() as u16).to_le_bytes());
+        data.extend_from_slice(value);
+    }
+
+    fn transfer_fee_config() -> Vec<u8> {
+        let mut value = Vec::new();
+        value.extend_from_slice(&AUTHORITY);
+        value.extend_from_slice(&[0; 32]);
+        value.extend_from_slice(&7u64.to_le_bytes());
+        for (epoch, maximum_fee, basis_points) in [(100u64, 5000u64, 25u16), (101, 10000, 50)] {
+            value.extend_from_slice(&epoch.to_le_bytes());
+            value.extend_from_slice(&maximum_fee.to_le_bytes());
+            value.extend_from_slice(&basis_points.to_le_bytes());
+        }
+
+        value
+    }
+
+    fn token_metadata() -> Vec<u8> {
+        let mut value = Vec::new();
+        value.extend_from_slice(&AUTHORITY);
+        value.extend_from_slice(&MINT);
+        for string in ["Proxy Token", "PRX", "https://example.com/prx.json"] {
+            borsh_string(&mut value, string);
+        }
+        value.extend_from_slice(&1u32.to_le_bytes());
+        borsh_string(&mut value, "color");
+        borsh_string(&mut value, "blue");
+
+        value
+    }
+
+    fn borsh_string(value: &mut Vec<u8>, string: &str) {
+        value.extend_from_slice(&(string.len() as u32).to_le_bytes());
+        value.extend_from_slice(string.as_bytes());
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{AccountDataReader, ParseAccountError, ParseResult};
+use serde::Serialize;
+
+/// A parsed Stake program account
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "type", content = "info")]
+pub enum StakeAccountType {
+    /// The account has not been initialized
+    Uninitialized,
+    /// The account is initialized but not delegated
+    Initialized(UiStakeAccount),
+    /// The account is delegated to a vote account
+    Delegated(UiStakeAccount),
+    /// A rewards pool of the Stake program
+    RewardsPool,
+}
+
+/// An initialized stake account
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiStakeAccount {
+    /// The authorities and lockup of the account
+    pub meta: UiMeta,
+    /// The delegation, `null` unless the account is delegated
+    pub stake: Option<UiStake>,
+}
+
+/// The authorities and lockup of a stake account
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiMeta {
+    /// The lamports kept for rent exemption
+    pub rent_exempt_reserve: String,
+    /// The authorities of the account
+    pub authorized: UiAuthorized,
+    /// The lockup of the account
+    pub lockup: UiLockup,
+}
+
+/// The authorities of a stake account
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiAuthorized {
+    /// The authority allowed to delegate and deactivate the stake
+    pub staker: String,
+    /// The authority allowed to withdraw the lamports
+    pub withdrawer: String,
+}
+
+/// The lockup of a stake account, the lamports cannot be withdrawn before it expires
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiLockup {
+    /// The unix timestamp the lockup expires at
+    pub unix_timestamp: i64,
+    /// The epoch the lockup expires at
+    pub epoch: u64,
+    /// The custodian allowed to change the lockup
+    pub custodian: String,
+}
+
+/// The delegation of a stake account
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiStake {
+    /// The delegation to a vote account
+    pub delegation: UiDelegation,
+    /// The credits of the vote account last observed by the stake
+    pub credits_observed: u64,
+}
+
+/// The delegation to a vote account
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiDelegation {
+    /// The vote account the stake is delegated to
+    pub voter: String,
+    /// The delegated lamports
+    pub stake: String,
+    /// The epoch the stake was activated at
+    pub activation_epoch: String,
+    /// The epoch the stake was deactivated at, `u64::MAX` while it is active
+    pub deactivation_epoch: String,
+    /// Deprecated, the rate at which the stake is activated and deactivated
+    pub warmup_cooldown_rate: f64,
+}
+
+/// Parse a Stake program account, the `bincode` layout of `StakeStateV2`
+pub fn parse_stake(data: &[u8]) -> ParseResult<StakeAccountType> {
+    let mut reader = AccountDataReader::new(data);
+
+    match reader.u32()? {
+        0 => Ok(StakeAccountType::Uninitialized),
+        1 => Ok(StakeAccountType::Initialized(UiStakeAccount {
+            meta: meta(&mut reader)?,
+            stake: Option::None,
+        })),
+        2 => Ok(StakeAccountType::Delegated(UiStakeAccount {
+            meta: meta(&mut reader)?,
+            stake: Some(stake(&mut reader)?),
+        })),
+        3 => Ok(StakeAccountType::RewardsPool),
+        _ => Err(ParseAccountError::InvalidData),
+    }
+}
+
+fn meta(reader: &mut AccountDataReader) -> ParseResult<UiMeta> {
+    Ok(UiMeta {
+        rent_exempt_reserve: reader.u64()?.to_string(),
+        authorized: UiAuthorized {
+            staker: reader.pubkey()?,
+            withdrawer: reader.pubkey()?,
+        },
+        lockup: UiLockup {
+            unix_timestamp: reader.i64()?,
+            epoch: reader.u64()?,
+            custodian: reader.pubkey()?,
+        },
+    })
+}
+
+fn stake(reader: &mut AccountDataReader) -> ParseResult<UiStake> {
+    Ok(UiStake {
+        delegation: UiDelegation {
+            voter: reader.pubkey()?,
+            stake: reader.u64()?.to_string(),
+            activation_epoch: reader.u64()?.to_string(),
+            deactivation_epoch: reader.u64()?.to_string(),
+            warmup_cooldown_rate: reader.f64()?,
+        },
+        credits_observed: reader.u64()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parse_account_data, AdditionalData, ParsedAccount, STAKE_PROGRAM_ID, SYSTEM_PROGRAM_ID,
+    };
+    use serde_json::json;
+
+    const ACCOUNT: &str = "YMN9Qj5jPNp7j14VPcML1B6xGgcPWVZUGLFU3Mnyfaf";
+
+    #[test]
+    fn parse_stake_accounts() -> anyhow::Result<()> {
+        let delegated = "AgAAAIDVIgAAAAAABQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHABCl1OgAAAD0AQAAAAAAAP//////////AAAAAAAA0D9A4gEAAAAAAAAAAAA=";
+        assert_eq!(
+            parse_account_data(
+                ACCOUNT,
+                STAKE_PROGRAM_ID,
+                &base64::decode(delegated)?,
+                &AdditionalData::default()
+            ),
+            Ok(ParsedAccount {
+                program: "stake".to_owned(),
+                parsed: json!({
+                    "type": "delegated",
+                    "info": {
+                        "meta": {
+                            "rentExemptReserve": "2282880",
+                            "authorized": {
+                                "staker": "LbUiWL3xVV8hTFYBVdbTNrpDo41NKS6o3LHHuDzjfcY",
+                                "withdrawer": "QWmroo4YnnMqYW3cnxWkFdaTxGD3P7vMSzwMHGbUzwF"
+                            },
+                            "lockup": {
+                                "unixTimestamp": 0,
+                                "epoch": 0,
+                                "custodian": SYSTEM_PROGRAM_ID
+                            }
+                        },
+                        "stake": {
+                            "delegation": {
+                                "voter": "US517G5965aydkZ46HS38QLi7UQiSojurfbQfKCELFx",
+                                "stake": "1000000000000",
+                                "activationEpoch": "500",
+                                "deactivationEpoch": "18446744073709551615",
+                                "warmupCooldownRate": 0.25
+                            },
+                            "creditsObserved": 123456
+                        }
+                    }
+                }),
+                space: 200,
+            })
+        );
+
+        let initialized = "AQAAAIDVIgAAAAAABQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgDxU2UAAAAAWAIAAAAAAAAICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=";
+        let parsed = parse_account_data(
+            ACCOUNT,
+            STAKE_PROGRAM_ID,
+            &base64::decode(initialized)?,
+            &AdditionalData::default(),
+        )
+        .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        assert_eq!(parsed.parsed["type"], "initialized");
+        assert_eq!(parsed.parsed["info"]["stake"], serde_json::Value::Null);
+        assert_eq!(
+            parsed.parsed["info"]["meta"]["lockup"],
+            json!({
+                "unixTimestamp": 1700000000,
+                "epoch": 600,
+                "custodian": "YMN9Qj5jPNp7j14VPcML1B6xGgcPWVZUGLFU3Mnyfaf"
+            })
+        );
+
+        let parsed = parse_account_data(
+            ACCOUNT,
+            STAKE_PROGRAM_ID,
+            &[0; 200],
+            &AdditionalData::default(),
+        )
+        .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        assert_eq!(parsed.parsed, json!({ "type": "uninitialized" }));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,495 @@
+use crate::{AccountDataReader, ParseAccountError, ParseResult};
+use serde::Serialize;
+
+/// The public key of the `Clock` sysvar
+pub const SYSVAR_CLOCK: &str = "SysvarC1ock11111111111111111111111111111111";
+/// The public key of the `Rent` sysvar
+pub const SYSVAR_RENT: &str = "SysvarRent111111111111111111111111111111111";
+/// The public key of the `EpochSchedule` sysvar
+pub const SYSVAR_EPOCH_SCHEDULE: &str = "SysvarEpochSchedu1e111111111111111111111111";
+/// The public key of the `SlotHashes` sysvar
+pub const SYSVAR_SLOT_HASHES: &str = "SysvarS1otHashes111111111111111111111111111";
+/// The public key of the `StakeHistory` sysvar
+pub const SYSVAR_STAKE_HISTORY: &str = "SysvarStakeHistory1111111111111111111111111";
+
+/// A parsed sysvar
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "type", content = "info")]
+pub enum SysvarAccountType {
+    /// The `Clock` sysvar
+    Clock(UiClock),
+    /// The `EpochSchedule` sysvar
+    EpochSchedule(UiEpochSchedule),
+    /// The `Rent` sysvar
+    Rent(UiRent),
+    /// The `SlotHashes` sysvar
+    SlotHashes(Vec<UiSlotHashEntry>),
+    /// The `StakeHistory` sysvar
+    StakeHistory(Vec<UiStakeHistoryEntry>),
+}
+
+/// The `Clock` sysvar
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiClock {
+    /// The current slot
+    pub slot: u64,
+    /// The current epoch
+    pub epoch: u64,
+    /// The unix timestamp of the first slot of the epoch
+    pub epoch_start_timestamp: i64,
+    /// The epoch of the most recent leader schedule
+    pub leader_schedule_epoch: u64,
+    /// The estimated unix timestamp of the current slot
+    pub unix_timestamp: i64,
+}
+
+/// The `EpochSchedule` sysvar
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiEpochSchedule {
+    /// The number of slots of an epoch after the warmup
+    pub slots_per_epoch: u64,
+    /// The number of slots before an epoch its leader schedule is computed
+    pub leader_schedule_slot_offset: u64,
+    /// Whether the first epochs are shorter
+    pub warmup: bool,
+    /// The first epoch with `slotsPerEpoch` slots
+    pub first_normal_epoch: u64,
+    /// The first slot of `firstNormalEpoch`
+    pub first_normal_slot: u64,
+}
+
+/// The `Rent` sysvar
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiRent {
+    /// The rental rate in lamports per byte and year
+    pub lamports_per_byte_year: String,
+    /// The number of years of rent an account must hold to be rent exempt
+    pub exemption_threshold: f64,
+    /// The percentage of the collected rent that is burned
+    pub burn_percent: u8,
+}
+
+impl From<Rent> for UiRent {
+    fn from(rent: Rent) -> Self {
+        UiRent {
+            lamports_per_byte_year: rent.lamports_per_byte_year.to_string(),
+            exemption_threshold: rent.exemption_threshold,
+            burn_percent: rent.burn_percent,
+        }
+    }
+}
+
+impl From<EpochSchedule> for UiEpochSchedule {
+    fn from(epoch_schedule: EpochSchedule) -> Self {
+        UiEpochSchedule {
+            slots_per_epoch: epoch_schedule.slots_per_epoch,
+            leader_schedule_slot_offset: epoch_schedule.leader_schedule_slot_offset,
+            warmup: epoch_schedule.warmup,
+            first_normal_epoch: epoch_schedule.first_normal_epoch,
+            first_normal_slot: epoch_schedule.first_normal_slot,
+        }
+    }
+}
+
+/// An entry of the `SlotHashes` sysvar
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiSlotHashEntry {
+    /// The slot
+    pub slot: u64,
+    /// The bank hash of the slot
+    pub hash: String,
+}
+
+/// An entry of the `StakeHistory` sysvar
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiStakeHistoryEntry {
+    /// The epoch
+    pub epoch: u64,
+    /// The stake of the epoch
+    pub stake_history: UiStakeHistory,
+}
+
+/// The stake of an epoch in lamports
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiStakeHistory {
+    /// The effective stake
+    pub effective: u64,
+    /// The stake being activated
+    pub activating: u64,
+    /// The stake being deactivated
+    pub deactivating: u64,
+}
+
+/// Parse a sysvar, the sysvar is identified by the `pubkey` of the account
+pub fn parse_sysvar(pubkey: &str, data: &[u8]) -> ParseResult<SysvarAccountType> {
+    let mut reader = AccountDataReader::new(data);
+
+    let sysvar = match pubkey {
+        SYSVAR_CLOCK => SysvarAccountType::Clock(UiClock {
+            slot: reader.u64()?,
+            epoch_start_timestamp: reader.i64()?,
+            epoch: reader.u64()?,
+            leader_schedule_epoch: reader.u64()?,
+            unix_timestamp: reader.i64()?,
+        }),
+        SYSVAR_RENT => SysvarAccountType::Rent(Rent::unpack(data)?.into()),
+        SYSVAR_EPOCH_SCHEDULE => {
+            SysvarAccountType::EpochSchedule(EpochSchedule::unpack(data)?.into())
+        }
+        SYSVAR_SLOT_HASHES => {
+            let mut slot_hashes = Vec::new();
+            for _ in 0..reader.bincode_length()? {
+                slot_hashes.push(UiSlotHashEntry {
+                    slot: reader.u64()?,
+                    hash: reader.hash()?,
+                });
+            }
+
+            SysvarAccountType::SlotHashes(slot_hashes)
+        }
+        SYSVAR_STAKE_HISTORY => {
+            let mut stake_history = Vec::new();
+            for _ in 0..reader.bincode_length()? {
+                stake_history.push(UiStakeHistoryEntry {
+                    epoch: reader.u64()?,
+                    stake_history: UiStakeHistory {
+                        effective: reader.u64()?,
+                        activating: reader.u64()?,
+                        deactivating: reader.u64()?,
+                    },
+                });
+            }
+
+            SysvarAccountType::StakeHistory(stake_history)
+        }
+        _ => return Err(ParseAccountError::InvalidData),
+    };
+
+    Ok(sysvar)
+}
+
+/// The `Rent` sysvar, used to compute the balance an account needs to be rent exempt
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rent {
+    /// The rental rate in lamports per byte and year
+    pub lamports_per_byte_year: u64,
+    /// The number of years of rent an account must hold to be rent exempt
+    pub exemption_threshold: f64,
+    /// The percentage of the collected rent that is burned
+    pub burn_percent: u8,
+}
+
+impl Rent {
+    /// The number of bytes stored with each account besides its data, same as Solana
+    pub const ACCOUNT_STORAGE_OVERHEAD: u64 = 128;
+    /// The rental rate of Solana, 1 SOL per MiB and year divided by 100 rounded down
+    pub const DEFAULT_LAMPORTS_PER_BYTE_YEAR: u64 = 3480;
+    /// The exemption threshold of Solana, two years of rent
+    pub const DEFAULT_EXEMPTION_THRESHOLD: f64 = 2.0;
+    /// The burn percentage of Solana
+    pub const DEFAULT_BURN_PERCENT: u8 = 50;
+
+    /// Read the `Rent` from the data of the sysvar account
+    pub fn unpack(data: &[u8]) -> ParseResult<Self> {
+        let mut reader = AccountDataReader::new(data);
+
+        Ok(Rent {
+            lamports_per_byte_year: reader.u64()?,
+            exemption_threshold: reader.f64()?,
+            burn_percent: reader.u8()?,
+        })
+    }
+
+    /// The minimum balance of an account with `data_len` bytes of data to be rent exempt,
+    /// computed like Solana including the truncation of the `f64` product
+    pub fn minimum_balance(&self, data_len: usize) -> u64 {
+        let bytes = Rent::ACCOUNT_STORAGE_OVERHEAD.saturating_add(data_len as u64);
+
+        (bytes.saturating_mul(self.lamports_per_byte_year) as f64 * self.exemption_threshold) as u64
+    }
+}
+
+impl Default for Rent {
+    fn default() -> Self {
+        Rent {
+            lamports_per_byte_year: Rent::DEFAULT_LAMPORTS_PER_BYTE_YEAR,
+            exemption_threshold: Rent::DEFAULT_EXEMPTION_THRESHOLD,
+            burn_percent: Rent::DEFAULT_BURN_PERCENT,
+        }
+    }
+}
+
+/// The `EpochSchedule` sysvar, maps slots to epochs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EpochSchedule {
+    /// The number of slots of an epoch after the warmup
+    pub slots_per_epoch: u64,
+    /// The number of slots before an epoch its leader schedule is computed
+    pub leader_schedule_slot_offset: u64,
+    /// Whether the first epochs are shorter
+    pub warmup: bool,
+    /// The first epoch with `slots_per_epoch` slots
+    pub first_normal_epoch: u64,
+    /// The first slot of `first_normal_epoch`
+    pub first_normal_slot: u64,
+}
+
+impl EpochSchedule {
+    /// The number of slots of the first epoch when the schedule has a warmup, same as Solana
+    pub const MINIMUM_SLOTS_PER_EPOCH: u64 = 32;
+
+    /// Read the `EpochSchedule` from the data of the sysvar account
+    pub fn unpack(data: &[u8]) -> ParseResult<Self> {
+        let mut reader = AccountDataReader::new(data);
+
+        Ok(EpochSchedule {
+            slots_per_epoch: reader.u64()?,
+            leader_schedule_slot_offset: reader.u64()?,
+            warmup: reader.bool()?,
+            first_normal_epoch: reader.u64()?,
+            first_normal_slot: reader.u64()?,
+        })
+    }
+
+    /// The number of slots of `epoch`, the warmup epochs double in length starting
+    /// with [EpochSchedule::MINIMUM_SLOTS_PER_EPOCH] slots
+    pub fn slots_in_epoch(&self, epoch: u64) -> u64 {
+        if epoch < self.first_normal_epoch {
+            2u64.saturating_pow(
+                (epoch as u32)
+                    .saturating_add(EpochSchedule::MINIMUM_SLOTS_PER_EPOCH.trailing_zeros()),
+            )
+        } else {
+            self.slots_per_epoch
+        }
+    }
+
+    /// The epoch of `slot` and the index of the slot in that epoch, same as Solana
+    pub fn epoch_and_slot_index(&self, slot: u64) -> (u64, u64) {
+        if slot < self.first_normal_slot {
+            let minimum_exponent = EpochSchedule::MINIMUM_SLOTS_PER_EPOCH.trailing_zeros();
+
+            let epoch = slot
+                .saturating_add(EpochSchedule::MINIMUM_SLOTS_PER_EPOCH)
+                .saturating_add(1)
+                .next_power_of_two()
+                .trailing_zeros()
+                .saturating_sub(minimum_exponent)
+                .saturating_sub(1);
+
+            let epoch_length = 2u64.saturating_pow(epoch.saturating_add(minimum_exponent));
+
+            (
+                epoch as u64,
+                slot.saturating_sub(
+                    epoch_length.saturating_sub(EpochSchedule::MINIMUM_SLOTS_PER_EPOCH),
+                ),
+            )
+        } else {
+            let normal_slot_index = slot.saturating_sub(self.first_normal_slot);
+            let normal_epoch_index = normal_slot_index
+                .checked_div(self.slots_per_epoch)
+                .unwrap_or(0);
+            let slot_index = normal_slot_index
+                .checked_rem(self.slots_per_epoch)
+                .unwrap_or(0);
+
+            (
+                self.first_normal_epoch.saturating_add(normal_epoch_index),
+                slot_index,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parse_account_data, AdditionalData, EpochSchedule, ParsableAccount, ParseAccountError,
+        Rent, SYSVAR_PROGRAM_ID,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn parse_sysvars() -> anyhow::Result<()> {
+        // Sysvars are identified by their public key
+        let sysvars = [
+            (
+                "SysvarC1ock11111111111111111111111111111111",
+                "gLLmDgAAAAAA8VNlAAAAAEICAAAAAAAAQwIAAAAAAACgd1VlAAAAAA==",
+                json!({
+                    "type": "clock",
+                    "info": {
+                        "slot": 250000000,
+                        "epoch": 578,
+                        "epochStartTimestamp": 1700000000,
+                        "leaderScheduleEpoch": 579,
+                        "unixTimestamp": 1700100000
+                    }
+                }),
+            ),
+            (
+                "SysvarRent111111111111111111111111111111111",
+                "mA0AAAAAAAAAAAAAAAAAQDI=",
+                json!({
+                    "type": "rent",
+                    "info": {
+                        "lamportsPerByteYear": "3480",
+                        "exemptionThreshold": 2.0,
+                        "burnPercent": 50
+                    }
+                }),
+            ),
+            (
+                "SysvarEpochSchedu1e111111111111111111111111",
+                "gJcGAAAAAACAlwYAAAAAAAAAAAAAAAAAAAAAAAAAAAAA",
+                json!({
+                    "type": "epochSchedule",
+                    "info": {
+                        "slotsPerEpoch": 432000,
+                        "leaderScheduleSlotOffset": 432000,
+                        "warmup": false,
+                        "firstNormalEpoch": 0,
+                        "firstNormalSlot": 0
+                    }
+                }),
+            ),
+            (
+                "SysvarS1otHashes111111111111111111111111111",
+                "AgAAAAAAAACAsuYOAAAAAAoKCgoKCgoKCgoKCgoKCgoKCgoKCgoKCgoKCgoKCgoKf7LmDgAAAAALCwsLCwsLCwsLCwsLCwsLCwsLCwsLCwsLCwsLCwsLCw==",
+                json!({
+                    "type": "slotHashes",
+                    "info": [
+                        { "slot": 250000000, "hash": "gBxS1f6uyyGPuW5MzGBukidSb71jdsCb5fZaoSzULE5" },
+                        { "slot": 249999999, "hash": "k7FaK87WHGVXzkaoHb7CdVPgkKDQhZ29VLDeBVbDfYn" }
+                    ]
+                }),
+            ),
+            (
+                "SysvarStakeHistory1111111111111111111111111",
+                "AgAAAAAAAABCAgAAAAAAAJABAAAAAAAAFAAAAAAAAAAKAAAAAAAAAEECAAAAAAAAhgEAAAAAAAAFAAAAAAAAAAAAAAAAAAAA",
+                json!({
+                    "type": "stakeHistory",
+                    "info": [
+                        {
+                            "epoch": 578,
+                            "stakeHistory": { "effective": 400, "activating": 20, "deactivating": 10 }
+                        },
+                        {
+                            "epoch": 577,
+                            "stakeHistory": { "effective": 390, "activating": 5, "deactivating": 0 }
+                        }
+                    ]
+                }),
+            ),
+        ];
+        for (pubkey, fixture, expected) in sysvars {
+            let parsed = parse_account_data(
+                pubkey,
+                SYSVAR_PROGRAM_ID,
+                &base64::decode(fixture)?,
+                &AdditionalData::default(),
+            )
+            .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+            assert_eq!(parsed.program, "sysvar");
+            assert_eq!(parsed.parsed, expected);
+        }
+        // A sysvar without a parser
+        assert_eq!(
+            parse_account_data(
+                "SysvarFees111111111111111111111111111111111",
+                SYSVAR_PROGRAM_ID,
+                &[0; 8],
+                &AdditionalData::default()
+            ),
+            Err(ParseAccountError::AccountNotParsable(
+                ParsableAccount::Sysvar
+            ))
+        );
+
+        Ok(())
+    }
+
+    /// The rent exemption and the epoch of a slot against the values returned by Solana RPC nodes
+    #[test]
+    fn rent_and_epoch_schedule() -> anyhow::Result<()> {
+        // The `Rent` sysvar of every Solana cluster
+        let rent = Rent::unpack(&[0x98, 0x0d, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x40, 50])
+            .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        assert_eq!(rent, Rent::default());
+
+        // The empty account, a mint and a token account
+        assert_eq!(rent.minimum_balance(0), 890_880);
+        assert_eq!(rent.minimum_balance(82), 1_461_600);
+        assert_eq!(rent.minimum_balance(165), 2_039_280);
+
+        // The product is truncated like Solana
+        let rent = Rent {
+            lamports_per_byte_year: 3,
+            exemption_threshold: 0.5,
+            ..Rent::default()
+        };
+        assert_eq!(rent.minimum_balance(1), 193);
+        assert!(Rent::unpack(&[0; 16]).is_err());
+
+        // The schedule of mainnet-beta has no warmup
+        let mainnet = EpochSchedule {
+            slots_per_epoch: 432_000,
+            leader_schedule_slot_offset: 432_000,
+            warmup: false,
+            first_normal_epoch: 0,
+            first_normal_slot: 0,
+        };
+        assert_eq!(mainnet.epoch_and_slot_index(0), (0, 0));
+        assert_eq!(mainnet.epoch_and_slot_index(431_999), (0, 431_999));
+        assert_eq!(mainnet.epoch_and_slot_index(250_000_000), (578, 304_000));
+        assert_eq!(mainnet.slots_in_epoch(578), 432_000);
+
+        // The schedule of devnet and testnet starts with epochs of 32, 64, 128... slots
+        let mut data = Vec::new();
+        data.extend_from_slice(&432_000u64.to_le_bytes());
+        data.extend_from_slice(&432_000u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(&14u64.to_le_bytes());
+        data.extend_from_slice(&524_256u64.to_le_bytes());
+        let warmup =
+            EpochSchedule::unpack(&data).map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        assert!(warmup.warmup);
+
+        assert_eq!(warmup.epoch_and_slot_index(0), (0, 0));
+        assert_eq!(warmup.epoch_and_slot_index(31), (0, 31));
+        assert_eq!(warmup.epoch_and_slot_index(32), (1, 0));
+        assert_eq!(warmup.epoch_and_slot_index(95), (1, 63));
+        assert_eq!(warmup.epoch_and_slot_index(96), (2, 0));
+        assert_eq!(warmup.epoch_and_slot_index(524_255), (13, 262_143));
+        assert_eq!(warmup.epoch_and_slot_index(524_256), (14, 0));
+        assert_eq!(warmup.epoch_and_slot_index(956_256), (15, 0));
+
+        assert_eq!(warmup.slots_in_epoch(0), 32);
+        assert_eq!(warmup.slots_in_epoch(1), 64);
+        assert_eq!(warmup.slots_in_epoch(13), 262_144);
+        assert_eq!(warmup.slots_in_epoch(14), 432_000);
+
+        // Every slot of the warmup falls in its epoch
+        let mut first_slot = 0;
+        for epoch in 0..warmup.first_normal_epoch {
+            let last_slot = first_slot + warmup.slots_in_epoch(epoch) - 1;
+            assert_eq!(warmup.epoch_and_slot_index(first_slot), (epoch, 0));
+            assert_eq!(
+                warmup.epoch_and_slot_index(last_slot),
+                (epoch, last_slot - first_slot)
+            );
+
+            first_slot = last_slot + 1;
+        }
+        assert_eq!(first_slot, warmup.first_normal_slot);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+use crate::{AccountDataReader, ParseAccountError, ParseResult, UiAccountState};
+use serde_json::{json, Value as JsonValue};
+
+/// The length of an ElGamal public key
+const ELGAMAL_PUBKEY_LENGTH: usize = 32;
+/// The length of an ElGamal ciphertext
+const ELGAMAL_CIPHERTEXT_LENGTH: usize = 64;
+/// The length of an authenticated encryption ciphertext
+const AE_CIPHERTEXT_LENGTH: usize = 36;
+/// The `ExtensionType` of the variable length token metadata
+const TOKEN_METADATA: u16 = 19;
+
+/// Parse the TLV data of a Token-2022 mint or account into the extensions in the same
+/// shape as the Solana account-decoder, `{"extension": "<name>", "state": {..}}`.
+/// The extensions are read until the first uninitialized entry, an extension that is
+/// unknown or does not match its layout is returned as `unparseableExtension`.
+pub fn parse_extensions(tlv_data: &[u8]) -> Vec<JsonValue> {
+    let mut extensions = Vec::new();
+    let mut reader = AccountDataReader::new(tlv_data);
+
+    while let Ok(extension_type) = reader.u16() {
+        if extension_type == 0 {
+            break;
+        }
+
+        let value = match reader
+            .u16()
+            .and_then(|length| reader.bytes(length as usize))
+        {
+            Ok(value) => value,
+            Err(_) => break,
+        };
+
+        let extension = parse_extension(extension_type, value)
+            .unwrap_or_else(|_| json!({ "extension": "unparseableExtension" }));
+
+        extensions.push(extension);
+    }
+
+    extensions
+}
+
+fn parse_extension(extension_type: u16, value: &[u8]) -> ParseResult<JsonValue> {
+    let mut reader = AccountDataReader::new(value);
+
+    let (name, state) = match extension_type {
+        1 => (
+            "transferFeeConfig",
+            json!({
+                "transferFeeConfigAuthority": reader.optional_nonzero_pubkey()?,
+                "withdrawWithheldAuthority": reader.optional_nonzero_pubkey()?,
+                "withheldAmount": reader.u64()?,
+                "olderTransferFee": transfer_fee(&mut reader)?,
+                "newerTransferFee": transfer_fee(&mut reader)?,
+            }),
+        ),
+        2 => (
+            "transferFeeAmount",
+            json!({ "withheldAmount": reader.u64()? }),
+        ),
+        3 => (
+            "mintCloseAuthority",
+            json!({ "closeAuthority": reader.optional_nonzero_pubkey()? }),
+        ),
+        4 => (
+            "confidentialTransferMint",
+            json!({
+                "authority": reader.optional_nonzero_pubkey()?,
+                "autoApproveNewAccounts": reader.bool()?,
+                "auditorElgamalPubkey": optional_nonzero_base64(&mut reader, ELGAMAL_PUBKEY_LENGTH)?,
+            }),
+        ),
+        5 => (
+            "confidentialTransferAccount",
+            json!({
+                "approved": reader.bool()?,
+                "elgamalPubkey": reader.base64(ELGAMAL_PUBKEY_LENGTH)?,
+                "pendingBalanceLo": reader.base64(ELGAMAL_CIPHERTEXT_LENGTH)?,
+                "pendingBalanceHi": reader.base64(ELGAMAL_CIPHERTEXT_LENGTH)?,
+                "availableBalance": reader.base64(ELGAMAL_CIPHERTEXT_LENGTH)?,
+                "decryptableAvailableBalance": reader.base64(AE_CIPHERTEXT_LENGTH)?,
+                "allowConfidentialCredits": reader.bool()?,
+                "allowNonConfidentialCredits": reader.bool()?,
+                "pendingBalanceCreditCounter": reader.u64()?,
+                "maximumPendingBalanceCreditCounter": reader.u64()?,
+                "expectedPendingBalanceCreditCounter": reader.u64()?,
+                "actualPendingBalanceCreditCounter": reader.u64()?,
+            }),
+        ),
+        6 => (
+            "defaultAccountState",
+            json!({ "accountState": UiAccountState::from_u8(reader.u8()?)? }),
+        ),
+        7 => ("immutableOwner", JsonValue::Null),
+        8 => (
+            "memoTransfer",
+            json!({ "requireIncomingTransferMemos": reader.bool()? }),
+        ),
+        9 => ("nonTransferable", JsonValue::Null),
+        10 => (
+            "interestBearingConfig",
+            json!({
+                "rateAuthority": reader.optional_nonzero_pubkey()?,
+                "initializationTimestamp": reader.i64()?,
+                "preUpdateAverageRate": reader.i16()?,
+                "lastUpdateTimestamp": reader.i64()?,
+                "currentRate": reader.i16()?,
+            }),
+        ),
+        11 => ("cpiGuard", json!({ "lockCpi": reader.bool()? })),
+        12 => (
+            "permanentDelegate",
+            json!({ "delegate": reader.optional_nonzero_pubkey()? }),
+        ),
+        13 => ("nonTransferableAccount", JsonValue::Null),
+        14 => (
+            "transferHook",
+            json!({
+                "authority": reader.optional_nonzero_pubkey()?,
+                "programId": reader.optional_nonzero_pubkey()?,
+            }),
+        ),
+        15 => (
+            "transferHookAccount",
+            json!({ "transferring": reader.bool()? }),
+        ),
+        16 => (
+            "confidentialTransferFeeConfig",
+            json!({
+                "authority": reader.optional_nonzero_pubkey()?,
+                "withdrawWithheldAuthorityElgamalPubkey": optional_nonzero_base64(&mut reader, ELGAMAL_PUBKEY_LENGTH)?,
+                "harvestToMintEnabled": reader.bool()?,
+                "withheldAmount": reader.base64(ELGAMAL_CIPHERTEXT_LENGTH)?,
+            }),
+        ),
+        17 => (
+            "confidentialTransferFeeAmount",
+            json!({ "withheldAmount": reader.base64(ELGAMAL_CIPHERTEXT_LENGTH)? }),
+        ),
+        18 => (
+            "metadataPointer",
+            json!({
+                "authority": reader.optional_nonzero_pubkey()?,
+                "metadataAddress": reader.optional_nonzero_pubkey()?,
+            }),
+        ),
+        TOKEN_METADATA => {
+            let update_authority = reader.optional_nonzero_pubkey()?;
+            let mint = reader.pubkey()?;
+            let name = reader.string()?;
+            let symbol = reader.string()?;
+            let uri = reader.string()?;
+
+            let mut additional_metadata = Vec::new();
+            for _ in 0..reader.u32()? {
+                additional_metadata.push(json!([reader.string()?, reader.string()?]));
+            }
+
+            (
+                "tokenMetadata",
+                json!({
+                    "updateAuthority": update_authority,
+                    "mint": mint,
+                    "name": name,
+                    "symbol": symbol,
+                    "uri": uri,
+                    "additionalMetadata": additional_metadata,
+                }),
+            )
+        }
+        20 => (
+            "groupPointer",
+            json!({
+                "authority": reader.optional_nonzero_pubkey()?,
+                "groupAddress": reader.optional_nonzero_pubkey()?,
+            }),
+        ),
+        21 => (
+            "tokenGroup",
+            json!({
+                "updateAuthority": reader.optional_nonzero_pubkey()?,
+                "mint": reader.pubkey()?,
+                "size": reader.u64()?,
+                "maxSize": reader.u64()?,
+            }),
+        ),
+        22 => (
+            "groupMemberPointer",
+            json!({
+                "authority": reader.optional_nonzero_pubkey()?,
+                "memberAddress": reader.optional_nonzero_pubkey()?,
+            }),
+        ),
+        23 => (
+            "tokenGroupMember",
+            json!({
+                "mint": reader.pubkey()?,
+                "group": reader.pubkey()?,
+                "memberNumber": reader.u64()?,
+            }),
+        ),
+        24 => (
+            "confidentialMintBurn",
+            json!({
+                "confidentialSupply": reader.base64(ELGAMAL_CIPHERTEXT_LENGTH)?,
+                "decryptableSupply": reader.base64(AE_CIPHERTEXT_LENGTH)?,
+                "supplyElgamalPubkey": reader.base64(ELGAMAL_PUBKEY_LENGTH)?,
+                "pendingBurn": reader.base64(ELGAMAL_CIPHERTEXT_LENGTH)?,
+            }),
+        ),
+        25 => (
+            "scaledUiAmountConfig",
+            json!({
+                "authority": reader.optional_nonzero_pubkey()?,
+                "multiplier": reader.f64()?.to_string(),
+                "newMultiplierEffectiveTimestamp": reader.i64()?,
+                "newMultiplier": reader.f64()?.to_string(),
+            }),
+        ),
+        26 => (
+            "pausableConfig",
+            json!({
+                "authority": reader.optional_nonzero_pubkey()?,
+                "paused": reader.bool()?,
+            }),
+        ),
+        27 => ("pausableAccount", JsonValue::Null),
+        _ => return Err(ParseAccountError::InvalidData),
+    };
+
+    // Every fixed length extension must be exactly the size of its layout,
+    // the token metadata is variable length and the space allocated for it may be larger
+    if extension_type != TOKEN_METADATA && !reader.remaining().is_empty() {
+        return Err(ParseAccountError::InvalidData);
+    }
+
+    if state.is_null() {
+        Ok(json!({ "extension": name }))
+    } else {
+        Ok(json!({ "extension": name, "state": state }))
+    }
+}
+
+fn transfer_fee(reader: &mut AccountDataReader) -> ParseResult<JsonValue> {
+    Ok(json!({
+        "epoch": reader.u64()?,
+        "maximumFee": reader.u64()?,
+        "transferFeeBasisPoints": reader.u16()?,
+    }))
+}
+
+/// An optional ElGamal public key where all zeroes means no public key
+fn optional_nonzero_base64(
+    reader: &mut AccountDataReader,
+    length: usize,
+) -> ParseResult<Option<String>> {
+    let bytes = reader.bytes(length)?;
+
+    if bytes.iter().all(|byte| *byte == 0) {
+        Ok(Option::None)
+    } else {
+        Ok(Some(base64::encode(bytes)))
+    }
+}
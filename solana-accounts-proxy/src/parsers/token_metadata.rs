@@ -0,0 +1,580 @@
+use crate::{AccountDataReader, ParseAccountError, ParseResult};
+use serde::Serialize;
+
+/// The length of a creator of a metadata account, its address, `verified` and `share`
+const CREATOR_LENGTH: usize = 34;
+
+/// A parsed account of the Metaplex Token Metadata program
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "type", content = "info")]
+pub enum TokenMetadataAccountType {
+    /// The account has not been initialized
+    Uninitialized,
+    /// The edition printed from a master edition
+    Edition(UiEdition),
+    /// A master edition of the first version, with the deprecated printing mints
+    MasterEditionV1(UiMasterEdition),
+    /// A master edition
+    MasterEditionV2(UiMasterEdition),
+    /// The metadata of a mint
+    Metadata(UiMetadata),
+}
+
+/// The metadata of a mint
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiMetadata {
+    /// The authority allowed to update the metadata
+    pub update_authority: String,
+    /// The mint described by the metadata
+    pub mint: String,
+    /// The name of the asset
+    pub name: String,
+    /// The symbol of the asset
+    pub symbol: String,
+    /// The URI of the JSON metadata of the asset
+    pub uri: String,
+    /// The royalties paid to the creators in basis points
+    pub seller_fee_basis_points: u16,
+    /// The creators of the asset
+    pub creators: Option<Vec<UiCreator>>,
+    /// Whether the asset has been sold once
+    pub primary_sale_happened: bool,
+    /// Whether the metadata can be updated
+    pub is_mutable: bool,
+    /// The nonce of the edition account
+    pub edition_nonce: Option<u8>,
+    /// The kind of asset
+    pub token_standard: Option<UiTokenStandard>,
+    /// The collection of the asset
+    pub collection: Option<UiCollection>,
+    /// How many times the asset can be used
+    pub uses: Option<UiUses>,
+    /// The size of the collection, only set on the metadata of a collection
+    pub collection_details: Option<UiCollectionDetails>,
+    /// The rule set of a programmable asset
+    pub programmable_config: Option<UiProgrammableConfig>,
+}
+
+/// A creator of an asset
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiCreator {
+    /// The address of the creator
+    pub address: String,
+    /// Whether the creator signed the metadata
+    pub verified: bool,
+    /// The percentage of the royalties paid to the creator
+    pub share: u8,
+}
+
+/// The kind of asset of a metadata account
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum UiTokenStandard {
+    /// A non-fungible token with a master edition
+    NonFungible,
+    /// A fungible token with attributes, without decimals
+    FungibleAsset,
+    /// A fungible token
+    Fungible,
+    /// A non-fungible token printed from a master edition
+    NonFungibleEdition,
+    /// A non-fungible token with transfer rules
+    ProgrammableNonFungible,
+    /// A programmable non-fungible token printed from a master edition
+    ProgrammableNonFungibleEdition,
+}
+
+/// The collection of an asset
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiCollection {
+    /// Whether the collection authority verified the asset
+    pub verified: bool,
+    /// The mint of the collection
+    pub key: String,
+}
+
+/// How many times an asset can be used
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiUses {
+    /// What happens to the asset when it is used
+    pub use_method: UiUseMethod,
+    /// The remaining uses
+    pub remaining: u64,
+    /// The total number of uses
+    pub total: u64,
+}
+
+/// What happens to an asset when it is used
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum UiUseMethod {
+    /// The asset is burnt
+    Burn,
+    /// The asset can be used several times
+    Multiple,
+    /// The asset can be used once
+    Single,
+}
+
+/// The details of a collection
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "version")]
+pub enum UiCollectionDetails {
+    /// The number of assets in the collection
+    V1 {
+        /// The number of verified assets of the collection
+        size: u64,
+    },
+    /// The size of the collection is no longer tracked
+    V2,
+}
+
+/// The configuration of a programmable asset
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "version")]
+pub enum UiProgrammableConfig {
+    /// The rule set checked when the asset is transferred
+    #[serde(rename_all = "camelCase")]
+    V1 {
+        /// The rule set account
+        rule_set: Option<String>,
+    },
+}
+
+/// A master edition
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiMasterEdition {
+    /// The number of editions printed
+    pub supply: u64,
+    /// The maximum number of editions, `null` if unlimited
+    pub max_supply: Option<u64>,
+    /// The deprecated mint of the printing tokens, only set on the first version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub printing_mint: Option<String>,
+    /// The deprecated mint of the one time printing authorization, only set on the first version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub one_time_printing_authorization_mint: Option<String>,
+}
+
+/// An edition printed from a master edition
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiEdition {
+    /// The master edition account the edition was printed from
+    pub parent: String,
+    /// The number of the edition
+    pub edition: u64,
+}
+
+/// Parse an account of the Token Metadata program, the `borsh` layout selected by the `Key` in its first byte
+pub fn parse_token_metadata(data: &[u8]) -> ParseResult<TokenMetadataAccountType> {
+    let mut reader = AccountDataReader::new(data);
+
+    let account = match reader.u8()? {
+        0 => TokenMetadataAccountType::Uninitialized,
+        1 => TokenMetadataAccountType::Edition(UiEdition {
+            parent: reader.pubkey()?,
+            edition: reader.u64()?,
+        }),
+        2 => TokenMetadataAccountType::MasterEditionV1(UiMasterEdition {
+            supply: reader.u64()?,
+            max_supply: reader.option_u64()?,
+            printing_mint: Some(reader.pubkey()?),
+            one_time_printing_authorization_mint: Some(reader.pubkey()?),
+        }),
+        4 => TokenMetadataAccountType::Metadata(parse_metadata(&mut reader)?),
+        6 => TokenMetadataAccountType::MasterEditionV2(UiMasterEdition {
+            supply: reader.u64()?,
+            max_supply: reader.option_u64()?,
+            printing_mint: Option::None,
+            one_time_printing_authorization_mint: Option::None,
+        }),
+        _ => return Err(ParseAccountError::InvalidData),
+    };
+
+    Ok(account)
+}
+
+/// Parse a metadata account after its `Key`. The fields after `isMutable` were added by later
+/// versions of the program, like the program they are `null` when they are missing from an older
+/// account or cannot be read. `tokenStandard`, `collection` and `uses` were added together and are
+/// only kept if all three can be read.
+fn parse_metadata(reader: &mut AccountDataReader) -> ParseResult<UiMetadata> {
+    let update_authority = reader.pubkey()?;
+    let mint = reader.pubkey()?;
+    let name = padded_string(reader)?;
+    let symbol = padded_string(reader)?;
+    let uri = padded_string(reader)?;
+    let seller_fee_basis_points = reader.u16()?;
+    let creators = option(reader, creators)?;
+    let primary_sale_happened = reader.bool()?;
+    let is_mutable = reader.bool()?;
+
+    let edition_nonce = trailing(reader, |reader| option(reader, |reader| reader.u8())).flatten();
+
+    let (token_standard, collection, uses) = trailing(reader, |reader| {
+        Ok((
+            option(reader, token_standard)?,
+            option(reader, collection)?,
+            option(reader, uses)?,
+        ))
+    })
+    .unwrap_or((Option::None, Option::None, Option::None));
+
+    let collection_details =
+        trailing(reader, |reader| option(reader, collection_details)).flatten();
+    let programmable_config =
+        trailing(reader, |reader| option(reader, programmable_config)).flatten();
+
+    Ok(UiMetadata {
+        update_authority,
+        mint,
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points,
+        creators,
+        primary_sale_happened,
+        is_mutable,
+        edition_nonce,
+        token_standard,
+        collection,
+        uses,
+        collection_details,
+        programmable_config,
+    })
+}
+
+/// Read a `borsh` string. The first versions of the program padded the name, symbol
+/// and URI to their maximum length with null bytes, which are removed.
+fn padded_string(reader: &mut AccountDataReader) -> ParseResult<String> {
+    Ok(reader.string()?.trim_end_matches('\0').to_owned())
+}
+
+/// Read a `borsh` `Option`, a `u8` tag followed by the value only when it is present
+fn option<'d, T>(
+    reader: &mut AccountDataReader<'d>,
+    read: impl FnOnce(&mut AccountDataReader<'d>) -> ParseResult<T>,
+) -> ParseResult<Option<T>> {
+    match reader.u8()? {
+        0 => Ok(Option::None),
+        1 => Ok(Some(read(reader)?)),
+        _ => Err(ParseAccountError::InvalidData),
+    }
+}
+
+/// Read fields that older accounts may not have. Once they cannot be read the
+/// position of the fields after them is unknown, so those are not read either.
+fn trailing<'d, T>(
+    reader: &mut AccountDataReader<'d>,
+    read: impl FnOnce(&mut AccountDataReader<'d>) -> ParseResult<T>,
+) -> Option<T> {
+    match read(reader) {
+        Ok(value) => Some(value),
+        Err(_) => {
+            *reader = AccountDataReader::new(&[]);
+
+            Option::None
+        }
+    }
+}
+
+fn creators(reader: &mut AccountDataReader) -> ParseResult<Vec<UiCreator>> {
+    let length = reader.u32()? as usize;
+
+    if length.saturating_mul(CREATOR_LENGTH) > reader.remaining().len() {
+        return Err(ParseAccountError::InvalidData);
+    }
+
+    (0..length)
+        .map(|_| {
+            Ok(UiCreator {
+                address: reader.pubkey()?,
+                verified: reader.bool()?,
+                share: reader.u8()?,
+            })
+        })
+        .collect()
+}
+
+fn token_standard(reader: &mut AccountDataReader) -> ParseResult<UiTokenStandard> {
+    let token_standard = match reader.u8()? {
+        0 => UiTokenStandard::NonFungible,
+        1 => UiTokenStandard::FungibleAsset,
+        2 => UiTokenStandard::Fungible,
+        3 => UiTokenStandard::NonFungibleEdition,
+        4 => UiTokenStandard::ProgrammableNonFungible,
+        5 => UiTokenStandard::ProgrammableNonFungibleEdition,
+        _ => return Err(ParseAccountError::InvalidData),
+    };
+
+    Ok(token_standard)
+}
+
+fn collection(reader: &mut AccountDataReader) -> ParseResult<UiCollection> {
+    Ok(UiCollection {
+        verified: reader.bool()?,
+        key: reader.pubkey()?,
+    })
+}
+
+fn uses(reader: &mut AccountDataReader) -> ParseResult<UiUses> {
+    let use_method = match reader.u8()? {
+        0 => UiUseMethod::Burn,
+        1 => UiUseMethod::Multiple,
+        2 => UiUseMethod::Single,
+        _ => return Err(ParseAccountError::InvalidData),
+    };
+
+    Ok(UiUses {
+        use_method,
+        remaining: reader.u64()?,
+        total: reader.u64()?,
+    })
+}
+
+fn collection_details(reader: &mut AccountDataReader) -> ParseResult<UiCollectionDetails> {
+    match reader.u8()? {
+        0 => Ok(UiCollectionDetails::V1 {
+            size: reader.u64()?,
+        }),
+        1 => {
+            // Padding kept so the account does not shrink
+            reader.skip(8)?;
+
+            Ok(UiCollectionDetails::V2)
+        }
+        _ => Err(ParseAccountError::InvalidData),
+    }
+}
+
+fn programmable_config(reader: &mut AccountDataReader) -> ParseResult<UiProgrammableConfig> {
+    match reader.u8()? {
+        0 => Ok(UiProgrammableConfig::V1 {
+            rule_set: reader.option_pubkey()?,
+        }),
+        _ => Err(ParseAccountError::InvalidData),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parse_account_data, AdditionalData, ParsableAccount, ParseAccountError, ParsedAccount,
+        TOKEN_METADATA_PROGRAM_ID,
+    };
+    use serde_json::json;
+
+    const ACCOUNT: &str = "YMN9Qj5jPNp7j14VPcML1B6xGgcPWVZUGLFU3Mnyfaf";
+    const MINT: [u8; 32] = [1; 32];
+    const OWNER: [u8; 32] = [2; 32];
+    const DELEGATE: [u8; 32] = [3; 32];
+    const AUTHORITY: [u8; 32] = [4; 32];
+
+    #[test]
+    fn parse_token_metadata_accounts() -> anyhow::Result<()> {
+        let [mint_address, owner, delegate, authority] = [MINT, OWNER, DELEGATE, AUTHORITY]
+            .map(|public_key| bs58::encode(public_key).into_string());
+
+        // The current layout, allocated with room for the largest metadata
+        let mut data = metadata_account("Degen Ape #1", 5);
+        data.push(1);
+        data.push(254);
+        data.extend_from_slice(&[1, 4]);
+        data.extend_from_slice(&[1, 1]);
+        data.extend_from_slice(&MINT);
+        data.extend_from_slice(&[1, 1]);
+        data.extend_from_slice(&3u64.to_le_bytes());
+        data.extend_from_slice(&5u64.to_le_bytes());
+        data.extend_from_slice(&[1, 0]);
+        data.extend_from_slice(&42u64.to_le_bytes());
+        data.extend_from_slice(&[1, 0, 1]);
+        data.extend_from_slice(&AUTHORITY);
+        data.resize(679, 0);
+
+        assert_eq!(
+            parse_account_data(
+                ACCOUNT,
+                TOKEN_METADATA_PROGRAM_ID,
+                &data,
+                &AdditionalData::default()
+            ),
+            Ok(ParsedAccount {
+                program: "tokenMetadata".to_owned(),
+                parsed: json!({
+                    "type": "metadata",
+                    "info": {
+                        "updateAuthority": authority,
+                        "mint": mint_address,
+                        "name": "Degen Ape #1",
+                        "symbol": "DAPE",
+                        "uri": "https://example.com/1.json",
+                        "sellerFeeBasisPoints": 500,
+                        "creators": [
+                            { "address": owner, "verified": true, "share": 60 },
+                            { "address": delegate, "verified": false, "share": 40 }
+                        ],
+                        "primarySaleHappened": true,
+                        "isMutable": false,
+                        "editionNonce": 254,
+                        "tokenStandard": "programmableNonFungible",
+                        "collection": { "verified": true, "key": mint_address },
+                        "uses": { "useMethod": "multiple", "remaining": 3, "total": 5 },
+                        "collectionDetails": { "version": "v1", "size": 42 },
+                        "programmableConfig": { "version": "v1", "ruleSet": authority }
+                    }
+                }),
+                space: 679,
+            })
+        );
+
+        // The first layout ends after `isMutable` and pads the strings with null bytes
+        let data = metadata_account("Old\0\0\0\0\0", 0);
+        let parsed = parse_account_data(
+            ACCOUNT,
+            TOKEN_METADATA_PROGRAM_ID,
+            &data,
+            &AdditionalData::default(),
+        )
+        .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        let info = &parsed.parsed["info"];
+        assert_eq!(info["name"], "Old");
+        assert_eq!(info["creators"], json!(null));
+        assert_eq!(info["editionNonce"], json!(null));
+        assert_eq!(info["tokenStandard"], json!(null));
+        assert_eq!(info["programmableConfig"], json!(null));
+
+        // Without `uses`, the fields added with it are dropped like the program does
+        let mut data = metadata_account("Partial", 0);
+        data.extend_from_slice(&[1, 7]);
+        data.extend_from_slice(&[1, 0]);
+        data.extend_from_slice(&[1, 0]);
+        data.extend_from_slice(&MINT);
+        let parsed = parse_account_data(
+            ACCOUNT,
+            TOKEN_METADATA_PROGRAM_ID,
+            &data,
+            &AdditionalData::default(),
+        )
+        .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        let info = &parsed.parsed["info"];
+        assert_eq!(info["editionNonce"], 7);
+        assert_eq!(info["tokenStandard"], json!(null));
+        assert_eq!(info["collection"], json!(null));
+        assert_eq!(info["uses"], json!(null));
+        assert_eq!(info["collectionDetails"], json!(null));
+
+        // Master editions of both versions and an edition
+        let mut data = vec![6];
+        data.extend_from_slice(&10u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(&100u64.to_le_bytes());
+        data.resize(282, 0);
+        assert_eq!(
+            parse_account_data(
+                ACCOUNT,
+                TOKEN_METADATA_PROGRAM_ID,
+                &data,
+                &AdditionalData::default()
+            )
+            .map(|account| account.parsed),
+            Ok(json!({
+                "type": "masterEditionV2",
+                "info": { "supply": 10, "maxSupply": 100 }
+            }))
+        );
+
+        let mut data = vec![2];
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.push(0);
+        data.extend_from_slice(&MINT);
+        data.extend_from_slice(&OWNER);
+        assert_eq!(
+            parse_account_data(
+                ACCOUNT,
+                TOKEN_METADATA_PROGRAM_ID,
+                &data,
+                &AdditionalData::default()
+            )
+            .map(|account| account.parsed),
+            Ok(json!({
+                "type": "masterEditionV1",
+                "info": {
+                    "supply": 1,
+                    "maxSupply": null,
+                    "printingMint": mint_address,
+                    "oneTimePrintingAuthorizationMint": owner
+                }
+            }))
+        );
+
+        let mut data = vec![1];
+        data.extend_from_slice(&MINT);
+        data.extend_from_slice(&7u64.to_le_bytes());
+        data.resize(241, 0);
+        assert_eq!(
+            parse_account_data(
+                ACCOUNT,
+                TOKEN_METADATA_PROGRAM_ID,
+                &data,
+                &AdditionalData::default()
+            )
+            .map(|account| account.parsed),
+            Ok(json!({
+                "type": "edition",
+                "info": { "parent": mint_address, "edition": 7 }
+            }))
+        );
+
+        // Other accounts of the program and truncated metadata are returned as `base64`
+        for data in [vec![7; 32], metadata_account("Truncated", 0)[..80].to_vec()] {
+            assert_eq!(
+                parse_account_data(
+                    ACCOUNT,
+                    TOKEN_METADATA_PROGRAM_ID,
+                    &data,
+                    &AdditionalData::default()
+                ),
+                Err(ParseAccountError::AccountNotParsable(
+                    ParsableAccount::TokenMetadata
+                ))
+            );
+        }
+
+        Ok(())
+    }
+
+    /// A metadata account up to `isMutable`, the layout of the first version of the program.
+    /// The creators are only set if `seller_fee_basis_points` is not zero.
+    fn metadata_account(name: &str, seller_fee_basis_points: u16) -> Vec<u8> {
+        let mut data = vec![4];
+        data.extend_from_slice(&AUTHORITY);
+        data.extend_from_slice(&MINT);
+        for string in [name, "DAPE", "https://example.com/1.json"] {
+            data.extend_from_slice(&(string.len() as u32).to_le_bytes());
+            data.extend_from_slice(string.as_bytes());
+        }
+        data.extend_from_slice(&(seller_fee_basis_points * 100).to_le_bytes());
+
+        if seller_fee_basis_points == 0 {
+            data.push(0);
+        } else {
+            data.push(1);
+            data.extend_from_slice(&2u32.to_le_bytes());
+            for (creator, verified, share) in [(OWNER, 1, 60), (DELEGATE, 0, 40)] {
+                data.extend_from_slice(&creator);
+                data.extend_from_slice(&[verified, share]);
+            }
+        }
+
+        data.push((seller_fee_basis_points != 0) as u8);
+        data.push(0);
+
+        data
+    }
+}
@@ -0,0 +1,367 @@
+use crate::{AccountDataReader, ParseAccountError, ParseResult};
+use serde::Serialize;
+
+/// The number of entries in the circular buffer of prior voters
+const MAX_PRIOR_VOTERS: usize = 32;
+
+/// A parsed Vote program account
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "type", content = "info")]
+pub enum VoteAccountType {
+    /// A vote account
+    Vote(UiVoteState),
+}
+
+/// The state of a vote account
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiVoteState {
+    /// The validator identity
+    pub node_pubkey: String,
+    /// The authority allowed to withdraw the lamports
+    pub authorized_withdrawer: String,
+    /// The percentage of the rewards kept by the validator
+    pub commission: u8,
+    /// The vote tower
+    pub votes: Vec<UiLockout>,
+    /// The last slot rooted by the tower
+    pub root_slot: Option<u64>,
+    /// The authorized voters for the current and future epochs
+    pub authorized_voters: Vec<UiAuthorizedVoters>,
+    /// The previous authorized voters
+    pub prior_voters: Vec<UiPriorVoters>,
+    /// The credits earned in the recent epochs
+    pub epoch_credits: Vec<UiEpochCredits>,
+    /// The most recent timestamp submitted with a vote
+    pub last_timestamp: UiBlockTimestamp,
+}
+
+/// A vote of the tower
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiLockout {
+    /// The voted slot
+    pub slot: u64,
+    /// The number of votes on top of this vote
+    pub confirmation_count: u32,
+}
+
+/// The authorized voter starting at an epoch
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiAuthorizedVoters {
+    /// The first epoch of the authorized voter
+    pub epoch: u64,
+    /// The authorized voter
+    pub authorized_voter: String,
+}
+
+/// A previous authorized voter
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiPriorVoters {
+    /// The previous authorized voter
+    pub authorized_pubkey: String,
+    /// The first epoch of the previous authorized voter
+    pub epoch_of_last_authorized_switch: u64,
+    /// The epoch the authorized voter was replaced at
+    pub target_epoch: u64,
+}
+
+/// The credits earned in an epoch
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiEpochCredits {
+    /// The epoch
+    pub epoch: u64,
+    /// The total credits at the end of the epoch
+    pub credits: String,
+    /// The total credits at the start of the epoch
+    pub previous_credits: String,
+}
+
+/// A timestamp submitted with a vote
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiBlockTimestamp {
+    /// The voted slot
+    pub slot: u64,
+    /// The unix timestamp of the slot
+    pub timestamp: i64,
+}
+
+/// Parse a Vote program account, the `bincode` layout of the `VoteStateVersions`
+/// from `0.23.5`, `1.14.11` and the current one. Older versions are converted to the
+/// current one like the Vote program does.
+pub fn parse_vote(data: &[u8]) -> ParseResult<VoteAccountType> {
+    let mut reader = AccountDataReader::new(data);
+
+    let vote_state = match reader.u32()? {
+        0 => vote_state_0_23_5(&mut reader)?,
+        1 => vote_state(&mut reader, false)?,
+        2 => vote_state(&mut reader, true)?,
+        _ => return Err(ParseAccountError::InvalidData),
+    };
+
+    Ok(VoteAccountType::Vote(vote_state))
+}
+
+/// The `1.14.11` and current layouts, they only differ by the latency stored with each vote
+fn vote_state(reader: &mut AccountDataReader, with_latency: bool) -> ParseResult<UiVoteState> {
+    let node_pubkey = reader.pubkey()?;
+    let authorized_withdrawer = reader.pubkey()?;
+    let commission = reader.u8()?;
+
+    let mut votes = Vec::new();
+    for _ in 0..reader.bincode_length()? {
+        if with_latency {
+            reader.u8()?;
+        }
+        votes.push(lockout(reader)?);
+    }
+
+    let root_slot = reader.option_u64()?;
+
+    let mut authorized_voters = Vec::new();
+    for _ in 0..reader.bincode_length()? {
+        authorized_voters.push(UiAuthorizedVoters {
+            epoch: reader.u64()?,
+            authorized_voter: reader.pubkey()?,
+        });
+    }
+
+    let mut prior_voters = Vec::new();
+    for _ in 0..MAX_PRIOR_VOTERS {
+        let authorized_pubkey = reader.optional_nonzero_pubkey()?;
+        let epoch_of_last_authorized_switch = reader.u64()?;
+        let target_epoch = reader.u64()?;
+
+        // The unused entries of the circular buffer are zeroed
+        if let Some(authorized_pubkey) = authorized_pubkey {
+            prior_voters.push(UiPriorVoters {
+                authorized_pubkey,
+                epoch_of_last_authorized_switch,
+                target_epoch,
+            });
+        }
+    }
+    // The index of the last entry and whether the buffer is empty
+    reader.u64()?;
+    reader.bool()?;
+
+    Ok(UiVoteState {
+        node_pubkey,
+        authorized_withdrawer,
+        commission,
+        votes,
+        root_slot,
+        authorized_voters,
+        prior_voters,
+        epoch_credits: epoch_credits(reader)?,
+        last_timestamp: block_timestamp(reader)?,
+    })
+}
+
+/// The `0.23.5` layout has a single authorized voter and its prior voters are dropped
+/// when it is converted to the current layout
+fn vote_state_0_23_5(reader: &mut AccountDataReader) -> ParseResult<UiVoteState> {
+    let node_pubkey = reader.pubkey()?;
+    let authorized_voter = reader.pubkey()?;
+    let authorized_voter_epoch = reader.u64()?;
+
+    // The prior voters with the slot of the switch, and the index of the last entry
+    reader.skip(MAX_PRIOR_VOTERS * (32 + 8 + 8 + 8))?;
+    reader.u64()?;
+
+    let authorized_withdrawer = reader.pubkey()?;
+    let commission = reader.u8()?;
+
+    let mut votes = Vec::new();
+    for _ in 0..reader.bincode_length()? {
+        votes.push(lockout(reader)?);
+    }
+
+    Ok(UiVoteState {
+        node_pubkey,
+        authorized_withdrawer,
+        commission,
+        votes,
+        root_slot: reader.option_u64()?,
+        authorized_voters: vec![UiAuthorizedVoters {
+            epoch: authorized_voter_epoch,
+            authorized_voter,
+        }],
+        prior_voters: Vec::new(),
+        epoch_credits: epoch_credits(reader)?,
+        last_timestamp: block_timestamp(reader)?,
+    })
+}
+
+fn lockout(reader: &mut AccountDataReader) -> ParseResult<UiLockout> {
+    Ok(UiLockout {
+        slot: reader.u64()?,
+        confirmation_count: reader.u32()?,
+    })
+}
+
+fn epoch_credits(reader: &mut AccountDataReader) -> ParseResult<Vec<UiEpochCredits>> {
+    let mut epoch_credits = Vec::new();
+    for _ in 0..reader.bincode_length()? {
+        epoch_credits.push(UiEpochCredits {
+            epoch: reader.u64()?,
+            credits: reader.u64()?.to_string(),
+            previous_credits: reader.u64()?.to_string(),
+        });
+    }
+
+    Ok(epoch_credits)
+}
+
+fn block_timestamp(reader: &mut AccountDataReader) -> ParseResult<UiBlockTimestamp> {
+    Ok(UiBlockTimestamp {
+        slot: reader.u64()?,
+        timestamp: reader.i64()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_account_data, AdditionalData, ParsedAccount, VOTE_PROGRAM_ID};
+    use serde_json::json;
+
+    const ACCOUNT: &str = "YMN9Qj5jPNp7j14VPcML1B6xGgcPWVZUGLFU3Mnyfaf";
+
+    #[test]
+    fn parse_vote_accounts() -> anyhow::Result<()> {
+        // The current vote state, with the latency of each vote
+        let vote_state = vote_account(true);
+        let parsed = parse_account_data(
+            ACCOUNT,
+            VOTE_PROGRAM_ID,
+            &vote_state,
+            &AdditionalData::default(),
+        )
+        .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        assert_eq!(
+            parsed,
+            ParsedAccount {
+                program: "vote".to_owned(),
+                parsed: json!({
+                    "type": "vote",
+                    "info": {
+                        "nodePubkey": "LbUiWL3xVV8hTFYBVdbTNrpDo41NKS6o3LHHuDzjfcY",
+                        "authorizedWithdrawer": "QWmroo4YnnMqYW3cnxWkFdaTxGD3P7vMSzwMHGbUzwF",
+                        "commission": 10,
+                        "votes": [
+                            { "slot": 100, "confirmationCount": 2 },
+                            { "slot": 101, "confirmationCount": 1 }
+                        ],
+                        "rootSlot": 99,
+                        "authorizedVoters": [{
+                            "epoch": 578,
+                            "authorizedVoter": "US517G5965aydkZ46HS38QLi7UQiSojurfbQfKCELFx"
+                        }],
+                        "priorVoters": [{
+                            "authorizedPubkey": "YMN9Qj5jPNp7j14VPcML1B6xGgcPWVZUGLFU3Mnyfaf",
+                            "epochOfLastAuthorizedSwitch": 570,
+                            "targetEpoch": 575
+                        }],
+                        "epochCredits": [
+                            { "epoch": 578, "credits": "1000", "previousCredits": "900" }
+                        ],
+                        "lastTimestamp": { "slot": 101, "timestamp": 1700000000 }
+                    }
+                }),
+                space: vote_state.len() as u64,
+            }
+        );
+
+        // The `1.14.11` vote state has the same JSON as the current one
+        let parsed_1_14_11 = parse_account_data(
+            ACCOUNT,
+            VOTE_PROGRAM_ID,
+            &vote_account(false),
+            &AdditionalData::default(),
+        )
+        .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        assert_eq!(parsed_1_14_11.parsed, parsed.parsed);
+
+        // The `0.23.5` vote state has a single authorized voter and no prior voters
+        let mut data = 0u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&[5; 32]);
+        data.extend_from_slice(&[7; 32]);
+        data.extend_from_slice(&578u64.to_le_bytes());
+        data.extend_from_slice(&[8; 32]);
+        data.extend_from_slice(&[0; 32 * 56 - 32 + 8]);
+        data.extend_from_slice(&[6; 32]);
+        data.push(10);
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.extend_from_slice(&100u64.to_le_bytes());
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.push(0);
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.extend_from_slice(&[0; 16]);
+        let parsed =
+            parse_account_data(ACCOUNT, VOTE_PROGRAM_ID, &data, &AdditionalData::default())
+                .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        assert_eq!(
+            parsed.parsed["info"]["authorizedVoters"],
+            json!([{
+                "epoch": 578,
+                "authorizedVoter": "US517G5965aydkZ46HS38QLi7UQiSojurfbQfKCELFx"
+            }])
+        );
+        assert_eq!(parsed.parsed["info"]["priorVoters"], json!([]));
+        assert_eq!(parsed.parsed["info"]["rootSlot"], serde_json::Value::Null);
+        assert_eq!(
+            parsed.parsed["info"]["votes"],
+            json!([{ "slot": 100, "confirmationCount": 2 }])
+        );
+
+        Ok(())
+    }
+
+    /// A vote account with the current layout, or the `1.14.11` layout without the latency of the votes
+    fn vote_account(current: bool) -> Vec<u8> {
+        let mut data = if current { 2u32 } else { 1u32 }.to_le_bytes().to_vec();
+        data.extend_from_slice(&[5; 32]);
+        data.extend_from_slice(&[6; 32]);
+        data.push(10);
+
+        data.extend_from_slice(&2u64.to_le_bytes());
+        for (latency, slot, confirmation_count) in [(1u8, 100u64, 2u32), (0, 101, 1)] {
+            if current {
+                data.push(latency);
+            }
+            data.extend_from_slice(&slot.to_le_bytes());
+            data.extend_from_slice(&confirmation_count.to_le_bytes());
+        }
+
+        data.push(1);
+        data.extend_from_slice(&99u64.to_le_bytes());
+
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.extend_from_slice(&578u64.to_le_bytes());
+        data.extend_from_slice(&[7; 32]);
+
+        data.extend_from_slice(&[8; 32]);
+        data.extend_from_slice(&570u64.to_le_bytes());
+        data.extend_from_slice(&575u64.to_le_bytes());
+        data.extend_from_slice(&[0; 31 * 48]);
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.push(0);
+
+        data.extend_from_slice(&1u64.to_le_bytes());
+        for credits in [578u64, 1000, 900] {
+            data.extend_from_slice(&credits.to_le_bytes());
+        }
+
+        data.extend_from_slice(&101u64.to_le_bytes());
+        data.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+
+        // Vote accounts are allocated with room for the largest state
+        data.resize(3762, 0);
+
+        data
+    }
+}
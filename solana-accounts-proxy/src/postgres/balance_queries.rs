@@ -0,0 +1,79 @@
+use crate::{Commitment, GetBalanceRow, ProxyResult};
+use tokio_postgres::Client;
+
+/// Helper struct to create the query for `getBalance` using the builder pattern
+pub struct GetBalanceQuery<'q> {
+    base58_public_key: &'q str,
+    commitment: Commitment,
+    min_context_slot: Option<u64>,
+}
+
+impl<'q> GetBalanceQuery<'q> {
+    /// Instantiate the struct with defaults
+    pub fn new() -> Self {
+        GetBalanceQuery {
+            base58_public_key: "",
+            commitment: Commitment::Finalized,
+            min_context_slot: Option::None,
+        }
+    }
+
+    /// Add a base58 public key
+    pub fn add_public_key(mut self, base58_public_key: &'q str) -> Self {
+        self.base58_public_key = base58_public_key;
+
+        self
+    }
+
+    /// Add the commitment level
+    pub fn add_commitment(mut self, commitment: Commitment) -> Self {
+        self.commitment = commitment;
+
+        self
+    }
+
+    /// Add the minimum context slot
+    pub fn add_min_context_slot(mut self, min_context_slot: Option<u64>) -> Self {
+        self.min_context_slot = min_context_slot;
+
+        self
+    }
+
+    /// Fetch only the lamports of the latest version of the account visible at the commitment
+    /// level together with the context slot in a single round trip.
+    /// The `LEFT JOIN` ensures the context slot is returned even when the account does not exist.
+    pub async fn query(self, pg_client: &Client) -> ProxyResult<GetBalanceRow> {
+        let query = format!(
+            "
+            WITH context AS (SELECT {} AS slot)
+            SELECT
+                context.slot,
+                latest.lamports
+            FROM context
+            LEFT JOIN LATERAL (
+                SELECT accounts.lamports
+                FROM accounts
+                WHERE accounts.pubkey = $1::TEXT
+                AND accounts.slot <= context.slot
+                ORDER BY accounts.slot DESC
+                LIMIT 1
+            ) AS latest ON TRUE;",
+            self.commitment.max_slot_subquery()
+        );
+
+        let row: GetBalanceRow = pg_client
+            .query_one(&query, &[&self.base58_public_key])
+            .await?
+            .into();
+
+        row.context.check_min_context_slot(self.min_context_slot)?;
+
+        Ok(row)
+    }
+}
+
+impl<'q> Default for GetBalanceQuery<'q> {
+    fn default() -> Self {
+        GetBalanceQuery::new()
+    }
+}
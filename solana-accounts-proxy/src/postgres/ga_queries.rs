@@ -1,10 +1,13 @@
-use crate::{Commitment, Context, GetAccountInfoRow, ProxyResult};
+use crate::{Commitment, Context, DataSlice, GetAccountInfoRow, ProxyResult};
+use tokio_postgres::{error::SqlState, types::ToSql, Client};
 
 /// Helper struct to create the query for `getAccountInfo` using the builder pattern
 pub struct GetAccountInfoQuery<'q> {
     base58_public_key: &'q str,
-    commitment: &'q str,
+    commitment: Commitment,
     min_context_slot: Option<u64>,
+    context_slot: Option<u64>,
+    data_slice: Option<DataSlice>,
 }
 
 impl<'q> GetAccountInfoQuery<'q> {
@@ -12,8 +15,10 @@ impl<'q> GetAccountInfoQuery<'q> {
     pub fn new() -> Self {
         GetAccountInfoQuery {
             base58_public_key: "",
-            commitment: "",
+            commitment: Commitment::Finalized,
             min_context_slot: Option::None,
+            context_slot: Option::None,
+            data_slice: Option::None,
         }
     }
 
@@ -25,7 +30,7 @@ impl<'q> GetAccountInfoQuery<'q> {
     }
 
     /// Add the commitment level
-    pub fn add_commitment(&mut self, commitment: &'q str) -> &mut Self {
+    pub fn add_commitment(&mut self, commitment: Commitment) -> &mut Self {
         self.commitment = commitment;
 
         self
@@ -38,56 +43,91 @@ impl<'q> GetAccountInfoQuery<'q> {
         self
     }
 
-    /// Build the SQL query
-    pub async fn query(self) -> ProxyResult<GetAccountInfoRow> {
-        crate::PgConnection::client_exists().await?;
-        let guarded_pg_client = crate::CLIENT.read().await;
-        let pg_client = guarded_pg_client.as_ref().unwrap(); // Cannot fail since `Option::None` has been handled by `PgConnection::client_exists()?;` above
+    /// Add the context slot the account is read at instead of the highest slot
+    /// visible at the commitment level
+    pub fn add_context_slot(&mut self, context_slot: u64) -> &mut Self {
+        self.context_slot = Some(context_slot);
 
-        let pubkey = self.base58_public_key;
+        self
+    }
 
-        if let Some(min_context_slot) = self.min_context_slot {
-            let slot = min_context_slot as i64;
-
-            let row = pg_client
-                .query_one(
-                    "
-                    SELECT 
-                        accounts.slot,
-                        accounts.data,
-                        accounts.executable,
-                        accounts.owner,
-                        accounts.lamports,
-                        accounts.rent_epoch
-                    FROM accounts WHERE pubkey = '$1'
-                    AND slot >= $2;",
-                    &[&pubkey, &slot],
-                )
-                .await?;
-
-            let outcome: GetAccountInfoRow = row.into();
-
-            Ok(outcome)
-        } else {
-            let row = pg_client
-                .query_one(
-                    "
-                    SELECT 
-                        accounts.slot,
-                        accounts.data,
-                        accounts.executable,
-                        accounts.owner,
-                        accounts.lamports,
-                        accounts.rent_epoch
-                    FROM accounts WHERE pubkey = $1::TEXT;",
-                    &[&pubkey],
-                )
-                .await?;
-
-            let outcome: GetAccountInfoRow = row.into();
-
-            Ok(outcome)
-        }
+    /// Add the data slice
+    pub fn add_data_slice(&mut self, data_slice: Option<DataSlice>) -> &mut Self {
+        self.data_slice = data_slice;
+
+        self
+    }
+
+    /// Build the SQL query.
+    /// Selects the latest version of the account written at or before the highest slot
+    /// visible at the commitment level, or the added context slot, and returns that slot
+    /// as the context slot.
+    /// If the account does not exist the value is [Option::None] and the context slot
+    /// is the added context slot or is fetched using [CurrentSlot] on the same connection.
+    pub async fn query(&self, pg_client: &Client) -> ProxyResult<GetAccountInfoRow> {
+        let outcome = match (self.query_account(pg_client).await?, self.context_slot) {
+            (Some(row), _) => row,
+            (None, Some(context_slot)) => GetAccountInfoRow::missing(Context::new(context_slot)),
+            (None, None) => GetAccountInfoRow::missing(
+                CurrentSlot::new()
+                    .add_commitment(self.commitment)
+                    .query(pg_client)
+                    .await?,
+            ),
+        };
+
+        outcome
+            .context
+            .check_min_context_slot(self.min_context_slot)?;
+
+        Ok(outcome)
+    }
+
+    /// Fetch the account
+    async fn query_account(&self, pg_client: &Client) -> ProxyResult<Option<GetAccountInfoRow>> {
+        let pubkey = self.base58_public_key;
+        let mut params: Vec<&(dyn ToSql + Sync)> = vec![&pubkey];
+
+        let data_slice = self.data_slice.map(|data_slice| data_slice.as_sql_range());
+        let data_column = match data_slice.as_ref() {
+            Some((start, length)) => {
+                params.push(start);
+                params.push(length);
+
+                "SUBSTRING(accounts.data FROM $2 FOR $3)"
+            }
+            None => "accounts.data",
+        };
+
+        let context_slot = self.context_slot.map(|context_slot| context_slot as i64);
+        let max_slot = match context_slot.as_ref() {
+            Some(context_slot) => {
+                params.push(context_slot);
+
+                format!("${}::BIGINT", params.len())
+            }
+            None => self.commitment.max_slot_subquery().to_owned(),
+        };
+
+        let query = format!(
+            "
+            SELECT
+                {} AS context_slot,
+                {},
+                accounts.executable,
+                accounts.owner,
+                accounts.lamports,
+                accounts.rent_epoch
+            FROM accounts WHERE accounts.pubkey = $1::TEXT
+            AND accounts.slot <= {}
+            ORDER BY accounts.slot DESC
+            LIMIT 1;",
+            max_slot, data_column, max_slot
+        );
+
+        let row = pg_client.query_opt(&query, &params).await?;
+
+        Ok(row.map(|row| row.into()))
     }
 }
 
@@ -126,24 +166,51 @@ impl CurrentSlot {
     }
 
     /// Run the query in the database and deserialize it to [Self]
-    pub async fn query(self) -> ProxyResult<Context> {
-        let commitment = self.commitment.queryable();
-
-        crate::PgConnection::client_exists().await?;
-        let guarded_pg_client = crate::CLIENT.read().await;
-        let pg_client = guarded_pg_client.as_ref().unwrap(); // Cannot fail since `Option::None` has been handled by `PgConnection::client_exists()?;` above
-
-        let row = pg_client
-            .query_one(
-                "
-            SELECT MAX(slot) FROM slots WHERE status::VARCHAR = $1::TEXT;
-            ",
-                &[&commitment],
-            )
-            .await?;
+    pub async fn query(self, pg_client: &Client) -> ProxyResult<Context> {
+        let query = format!("SELECT {};", self.commitment.max_slot_subquery());
+
+        let row = pg_client.query_one(&query, &[]).await?;
 
         let context: Context = row.into();
 
         Ok(context)
     }
 }
+
+/// Get the block height of a slot from the `block` table written by the Geyser plugin
+#[derive(Debug)]
+pub struct BlockHeight {
+    /// The slot to get the block height of
+    pub slot: u64,
+}
+
+impl BlockHeight {
+    /// Instantiate a new structure
+    pub fn new(slot: u64) -> Self {
+        BlockHeight { slot }
+    }
+
+    /// Run the query in the database. The block height of a skipped slot is the one of the
+    /// latest block before it. Returns [Option::None] if the database has no `block` table
+    /// with a `block_height` column or no block at or before the slot.
+    pub async fn query(self, pg_client: &Client) -> ProxyResult<Option<u64>> {
+        let query = "
+            SELECT block.block_height
+            FROM block
+            WHERE block.slot <= $1::BIGINT
+            AND block.block_height IS NOT NULL
+            ORDER BY block.slot DESC
+            LIMIT 1;";
+
+        match pg_client.query_opt(query, &[&(self.slot as i64)]).await {
+            Ok(row) => Ok(row.map(|row| row.get::<_, i64>(0) as u64)),
+            Err(error)
+                if error.code() == Some(&SqlState::UNDEFINED_TABLE)
+                    || error.code() == Some(&SqlState::UNDEFINED_COLUMN) =>
+            {
+                Ok(Option::None)
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{Commitment, DataSlice, GetMultipleAccountsRow, ProxyResult};
+use tokio_postgres::{types::ToSql, Client};
+
+/// Helper struct to create the query for `getMultipleAccounts` using the builder pattern
+#[derive(Debug)]
+pub struct GetMultipleAccountsQuery<'q> {
+    base58_public_keys: &'q [String],
+    commitment: Commitment,
+    context_slot: Option<u64>,
+    data_slice: Option<DataSlice>,
+}
+
+impl<'q> GetMultipleAccountsQuery<'q> {
+    /// Instantiate the struct with defaults
+    pub fn new() -> Self {
+        GetMultipleAccountsQuery {
+            base58_public_keys: &[],
+            commitment: Commitment::Finalized,
+            context_slot: Option::None,
+            data_slice: Option::None,
+        }
+    }
+
+    /// Add the base58 public keys
+    pub fn add_public_keys(mut self, base58_public_keys: &'q [String]) -> Self {
+        self.base58_public_keys = base58_public_keys;
+
+        self
+    }
+
+    /// Add the commitment level
+    pub fn add_commitment(mut self, commitment: Commitment) -> Self {
+        self.commitment = commitment;
+
+        self
+    }
+
+    /// Add the context slot the accounts are read at instead of the highest slot
+    /// visible at the commitment level
+    pub fn add_context_slot(mut self, context_slot: u64) -> Self {
+        self.context_slot = Some(context_slot);
+
+        self
+    }
+
+    /// Add the data slice
+    pub fn add_data_slice(mut self, data_slice: Option<DataSlice>) -> Self {
+        self.data_slice = data_slice;
+
+        self
+    }
+
+    /// Fetch the latest version of every account visible at the commitment level, or at the
+    /// added context slot, together with the context slot in a single round trip.
+    /// The `LEFT JOIN` ensures the context slot is returned even when none of the accounts exist.
+    pub async fn query(self, pg_client: &Client) -> ProxyResult<GetMultipleAccountsRow> {
+        let mut params: Vec<&(dyn ToSql + Sync)> = vec![&self.base58_public_keys];
+
+        let data_slice = self.data_slice.map(|data_slice| data_slice.as_sql_range());
+        let data_column = match data_slice.as_ref() {
+            Some((start, length)) => {
+                params.push(start);
+                params.push(length);
+
+                "SUBSTRING(accounts.data FROM $2 FOR $3)"
+            }
+            None => "accounts.data",
+        };
+
+        let context_slot = self.context_slot.map(|context_slot| context_slot as i64);
+        let max_slot = match context_slot.as_ref() {
+            Some(context_slot) => {
+                params.push(context_slot);
+
+                format!("${}::BIGINT", params.len())
+            }
+            None => self.commitment.max_slot_subquery().to_owned(),
+        };
+
+        let query = format!(
+            "
+            WITH context AS (SELECT {} AS slot)
+            SELECT
+                context.slot,
+                latest.pubkey,
+                latest.data,
+                latest.executable,
+                latest.owner,
+                latest.lamports,
+                latest.rent_epoch
+            FROM context
+            LEFT JOIN LATERAL (
+                SELECT DISTINCT ON (accounts.pubkey)
+                    accounts.pubkey,
+                    {} AS data,
+                    accounts.executable,
+                    accounts.owner,
+                    accounts.lamports,
+                    accounts.rent_epoch
+                FROM accounts
+                WHERE accounts.pubkey = ANY($1::TEXT[])
+                AND accounts.slot <= context.slot
+                ORDER BY accounts.pubkey, accounts.slot DESC
+            ) AS latest ON TRUE;",
+            max_slot, data_column
+        );
+
+        let rows = pg_client.query(&query, &params).await?;
+
+        Ok(rows.into())
+    }
+}
+
+impl<'q> Default for GetMultipleAccountsQuery<'q> {
+    fn default() -> Self {
+        GetMultipleAccountsQuery::new()
+    }
+}
@@ -1,14 +1,21 @@
-use crate::{Commitment, DataSlice, Filter, ProxyError, ProxyResult};
-use tokio_postgres::{types::ToSql, Row};
+use crate::{
+    Commitment, Context, DataSlice, Encoding, Filter, GetProgramAccountsRow, PgClient, ProxyResult,
+};
+use tokio_postgres::types::ToSql;
+
+/// A parameter of a query, the [ToSql] value is printed by [fmt::Debug](core::fmt::Debug)
+pub type SqlParam = Box<dyn ToSql + Sync + Send>;
 
 /// Helper struct for `getProgramAccounts`
 #[derive(Debug)]
 pub struct GetProgramAccounts<'q> {
     base58_public_key: &'q str,
-    commitment: &'q str,
+    commitment: Commitment,
+    encoding: Encoding,
     min_context_slot: Option<u64>,
     data_slice: Option<DataSlice>,
-    filters: Option<Vec<Filter>>,
+    filters: &'q [Filter],
+    with_context: bool,
 }
 
 impl<'q> GetProgramAccounts<'q> {
@@ -16,10 +23,12 @@ impl<'q> GetProgramAccounts<'q> {
     pub fn new() -> Self {
         GetProgramAccounts {
             base58_public_key: "",
-            commitment: "",
+            commitment: Commitment::Finalized,
+            encoding: Encoding::DEFAULT,
             min_context_slot: Option::default(),
             data_slice: Option::default(),
-            filters: Option::default(),
+            filters: &[],
+            with_context: false,
         }
     }
 
@@ -31,12 +40,19 @@ impl<'q> GetProgramAccounts<'q> {
     }
 
     /// Add the commitment level
-    pub fn add_commitment(mut self, commitment: &'q str) -> Self {
+    pub fn add_commitment(mut self, commitment: Commitment) -> Self {
         self.commitment = commitment;
 
         self
     }
 
+    /// Add the encoding of the accounts
+    pub fn add_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+
+        self
+    }
+
     /// Add the minimum context slot
     pub fn add_min_context_slot(mut self, min_context_slot: Option<u64>) -> Self {
         self.min_context_slot = min_context_slot;
@@ -52,234 +68,309 @@ impl<'q> GetProgramAccounts<'q> {
     }
 
     /// Add the filters for the query
-    pub fn add_filters(mut self, filters: Option<Vec<Filter>>) -> Self {
+    pub fn add_filters(mut self, filters: &'q [Filter]) -> Self {
         self.filters = filters;
 
         self
     }
 
-    /// Executor for the queries
-    pub async fn load_data(&self) -> ProxyResult<Vec<tokio_postgres::Row>> {
-        if self.data_slice.is_some() {
-            self.with_memcmp_and_data_slice().await
-        } else {
-            self.with_memcmp().await
-        }
-    }
+    /// Return the context slot with the accounts
+    pub fn add_with_context(mut self, with_context: bool) -> Self {
+        self.with_context = with_context;
 
-    /// `gPA` accounts with commitment level `Confirmed` and `mint`
-    // substring(data, {1}, {2}), memcmp.offset+1, len(memcmp.bytes)
-    pub async fn with_memcmp(&self) -> ProxyResult<Vec<Row>> {
-        let owner = self.base58_public_key;
-
-        crate::PgConnection::client_exists().await?;
-        let guarded_pg_client = crate::CLIENT.read().await;
-        let pg_client = guarded_pg_client.as_ref().unwrap(); // Cannot fail since `Option::None` has been handled by `PgConnection::client_exists()?;` above
+        self
+    }
 
-        let mut query = "SELECT DISTINCT on(pubkey) pubkey, lamports, owner, executable, rent_epoch, data FROM accounts ".to_owned();
+    /// Run the query. The context slot is only fetched when it is returned to the client,
+    /// the `minContextSlot` has to be checked or the mints of `jsonParsed` token accounts are
+    /// loaded, the accounts are then read at that exact slot using the same connection.
+    /// With `jsonParsed` the rows are read through a cursor so the mints are loaded on that
+    /// connection between two batches, see [GetProgramAccountsRow::query_cursor].
+    pub async fn load_data(&self, pg_client: PgClient) -> ProxyResult<GetProgramAccountsRow> {
+        let json_parsed = matches!(self.encoding, Encoding::JsonParsed);
 
-        let commitment: Commitment = self.commitment.into();
+        let context = if self.with_context || self.min_context_slot.is_some() || json_parsed {
+            let query = format!("SELECT {};", self.commitment.max_slot_subquery());
+            let context: Context = pg_client.query_one(&query, &[]).await?.into();
 
-        match commitment {
-            Commitment::Processed => {
-                query += "WHERE slot = (SELECT MAX(slot) FROM slots WHERE status = 'confirmed' OR status='processed')";
-            }
-            Commitment::Confirmed => {
-                query += "WHERE slot = (SELECT MAX(slot) FROM slots WHERE status = 'confirmed')";
-            }
-            Commitment::Finalized => {
-                query += "WHERE slot = (SELECT MAX(slot) FROM slots WHERE status = 'finalized')";
-            }
-        }
+            context.check_min_context_slot(self.min_context_slot)?;
 
-        query += "AND owner = $1::TEXT";
-
-        let mut sizes: Vec<i32> = vec![];
-        let mut bytes = vec![];
-        for filter in self.filters.as_ref().unwrap() {
-            let filter = filter.clone();
-            match filter {
-                Filter::Memcmp(memcmp_data) => {
-                    let decoded_bytes = memcmp_data.decode()?;
-
-                    sizes.push(memcmp_data.offset as i32 + 1);
-                    sizes.push(decoded_bytes.len() as i32);
-                    bytes.push(decoded_bytes);
-                }
-                _ => (),
-            }
-        }
+            Some(context)
+        } else {
+            Option::None
+        };
 
-        let mut data_size = Option::<i32>::None;
+        let (query, params) = self.build(context.as_ref().map(|context| context.slot))?;
 
-        for filter in self.filters.as_ref().unwrap() {
-            let filter = filter.clone();
-            match filter {
-                Filter::DataSize(client_data_size) => {
-                    data_size.replace(client_data_size as i32);
-                }
-                _ => (),
-            }
-        }
+        let params = params
+            .iter()
+            .map(|param| param.as_ref() as &(dyn ToSql + Sync))
+            .collect::<Vec<&(dyn ToSql + Sync)>>();
 
-        let mut params: Vec<&(dyn ToSql + Sync)> = vec![&owner];
-        let mut cnt1 = 0;
-        let mut cnt2 = 0;
-        for filter in self.filters.as_ref().unwrap() {
-            let filter = filter.clone();
-            match filter {
-                Filter::DataSize(_data_size) => {}
-                Filter::Memcmp(_memcmp_data) => {
-                    let len = params.len();
-                    query += &format!(
-                        " AND substring(data,${},${}) = ${}",
-                        len + 1,
-                        len + 2,
-                        len + 3
-                    );
-
-                    params.push(&sizes[cnt1]);
-                    cnt1 += 1;
-                    params.push(&sizes[cnt1]);
-                    cnt1 += 1;
-                    params.push(&bytes[cnt2]);
-                    cnt2 += 1;
-                }
+        let rows = match context.as_ref() {
+            Some(context) if json_parsed => {
+                GetProgramAccountsRow::query_cursor(pg_client, &query, &params, context.slot)
+                    .await?
             }
-        }
-
-        if let Some(data_size_exists) = data_size {
-            query += &format!(" AND length(data) = ${};", params.len() + 1);
+            _ => GetProgramAccountsRow::query(pg_client, &query, &params).await?,
+        };
 
-            params.push(&data_size_exists);
+        Ok(rows.add_context(context.filter(|_| self.with_context)))
+    }
 
-            let rows = pg_client.query(&query, &params).await?;
+    /// Build the SQL query and its parameters.
+    /// The latest version of every account that was ever owned by the program is selected
+    /// at or before the `context_slot`, or the highest slot visible at the commitment level
+    /// if it is not known, and the owner and the filters are applied to that version
+    /// so an account that changed owner or data since is not returned.
+    pub fn build(&self, context_slot: Option<u64>) -> ProxyResult<(String, Vec<SqlParam>)> {
+        let mut params: Vec<SqlParam> = vec![Box::new(self.base58_public_key.to_owned())];
 
-            Ok(rows)
-        } else {
-            query += ";";
-            let rows = pg_client.query(&query, &params).await?;
+        let max_slot = match context_slot {
+            Some(context_slot) => {
+                params.push(Box::new(context_slot as i64));
 
-            Ok(rows)
-        }
-    }
-
-    /// `gPA` accounts with `Commitment` level, `Filters` and `dataSlice`
-    // substring(data, {1}, {2}), memcmp.offset+1, len(memcmp.bytes)
-    pub async fn with_memcmp_and_data_slice(&self) -> ProxyResult<Vec<tokio_postgres::Row>> {
-        let owner = self.base58_public_key;
-
-        let data_slice = match self.data_slice {
-            Some(data_slice) => data_slice,
-            None => {
-                //FIXME Remove this error possibly by combining both the `with_memcmp` and `with_memcmp_and_data_slice` methods but you have to deal with lifetimes
-                return Err(ProxyError::Client(
-                    "The `dataSlice` field is required to perform this query".to_owned(),
-                ));
+                format!("${}::BIGINT", params.len())
             }
+            None => self.commitment.max_slot_subquery().to_owned(),
         };
-        let data_slice_offset = data_slice.offset as i32 + 1;
-        let data_slice_length = data_slice.length as i32;
-
-        crate::PgConnection::client_exists().await?;
-        let guarded_pg_client = crate::CLIENT.read().await;
-        let pg_client = guarded_pg_client.as_ref().unwrap(); // Cannot fail since `Option::None` has been handled by `PgConnection::client_exists()?;` above
-
-        let mut query = "
-        SELECT DISTINCT on(accounts.pubkey) 
-            pubkey, lamports, owner, executable, rent_epoch, data, SUBSTRING(data, $1, $2) 
-        FROM accounts "
-            .to_owned();
-        let commitment: Commitment = self.commitment.into();
-
-        match commitment {
-            Commitment::Processed => {
-                query += "WHERE slot = (SELECT MAX(slot) FROM slots WHERE status = 'confirmed' OR status='processed')";
-            }
-            Commitment::Confirmed => {
-                query += "WHERE slot = (SELECT MAX(slot) FROM slots WHERE status = 'confirmed')";
-            }
-            Commitment::Finalized => {
-                query += "WHERE slot = (SELECT MAX(slot) FROM slots WHERE status = 'finalized')";
-            }
-        }
 
-        query += "AND owner = $3::TEXT ";
-
-        let mut sizes: Vec<i32> = vec![];
-        let mut bytes = vec![];
-        for filter in self.filters.as_ref().unwrap() {
-            let filter = filter.clone();
-            match filter {
-                Filter::Memcmp(memcmp_data) => {
-                    let decoded_bytes = memcmp_data.decode()?;
-
-                    sizes.push(memcmp_data.offset as i32 + 1);
-                    sizes.push(decoded_bytes.len() as i32);
-                    bytes.push(decoded_bytes);
-                }
-                _ => (),
+        let data_column = match self.data_slice {
+            Some(data_slice) => {
+                let (start, length) = data_slice.as_sql_range();
+                params.push(Box::new(start));
+                params.push(Box::new(length));
+
+                format!(
+                    "SUBSTRING(latest.data FROM ${} FOR ${})",
+                    params.len() - 1,
+                    params.len()
+                )
             }
-        }
+            None => "latest.data".to_owned(),
+        };
 
-        let mut data_size = Option::<i32>::None;
+        let mut conditions = vec!["latest.owner = $1::TEXT".to_owned()];
+
+        push_filter_conditions(self.filters, &mut params, &mut conditions)?;
+
+        let query = format!(
+            "
+            SELECT
+                latest.pubkey,
+                latest.lamports,
+                latest.owner,
+                latest.executable,
+                latest.rent_epoch,
+                {}
+            FROM (
+                SELECT DISTINCT ON (accounts.pubkey)
+                    accounts.pubkey,
+                    accounts.lamports,
+                    accounts.owner,
+                    accounts.executable,
+                    accounts.rent_epoch,
+                    accounts.data
+                FROM accounts
+                WHERE accounts.pubkey IN (SELECT owned.pubkey FROM accounts AS owned WHERE owned.owner = $1::TEXT)
+                AND accounts.slot <= {}
+                ORDER BY accounts.pubkey, accounts.slot DESC
+            ) AS latest
+            WHERE {};",
+            data_column,
+            max_slot,
+            conditions.join(" AND ")
+        );
+
+        Ok((query, params))
+    }
+}
 
-        for filter in self.filters.as_ref().unwrap() {
-            let filter = filter.clone();
-            match filter {
-                Filter::DataSize(client_data_size) => {
-                    data_size.replace(client_data_size as i32);
-                }
-                _ => (),
-            }
-        }
+impl<'q> Default for GetProgramAccounts<'q> {
+    fn default() -> Self {
+        GetProgramAccounts::new()
+    }
+}
 
-        let mut params: Vec<&(dyn ToSql + Sync)> =
-            vec![&data_slice_offset, &data_slice_length, &owner];
-        let mut cnt1 = 0;
-        let mut cnt2 = 0;
-        for filter in self.filters.as_ref().unwrap() {
-            let filter = filter.clone();
-            match filter {
-                Filter::DataSize(_data_size) => {}
-                Filter::Memcmp(_memcmp_data) => {
-                    let len = params.len();
-                    query += &format!(
-                        " AND substring(data,${},${}) = ${}",
-                        len + 1,
-                        len + 2,
-                        len + 3
-                    );
-
-                    params.push(&sizes[cnt1]);
-                    cnt1 += 1;
-                    params.push(&sizes[cnt1]);
-                    cnt1 += 1;
-                    params.push(&bytes[cnt2]);
-                    cnt2 += 1;
-                }
+/// Add the SQL conditions of the `filters` on the `latest.data` column and their parameters
+pub(crate) fn push_filter_conditions(
+    filters: &[Filter],
+    params: &mut Vec<SqlParam>,
+    conditions: &mut Vec<String>,
+) -> ProxyResult<()> {
+    for filter in filters {
+        match filter {
+            Filter::DataSize(data_size) => {
+                params.push(Box::new(i64::try_from(*data_size).unwrap_or(i64::MAX)));
+
+                conditions.push(format!("LENGTH(latest.data) = ${}::BIGINT", params.len()));
+            }
+            Filter::Memcmp(memcmp_data) => {
+                let bytes = memcmp_data.decode()?;
+                let start = i32::try_from(memcmp_data.offset.saturating_add(1)).unwrap_or(i32::MAX);
+                let length = i32::try_from(bytes.len()).unwrap_or(i32::MAX);
+
+                params.push(Box::new(start));
+                params.push(Box::new(length));
+                params.push(Box::new(bytes));
+
+                conditions.push(format!(
+                    "SUBSTRING(latest.data FROM ${} FOR ${}) = ${}",
+                    params.len() - 2,
+                    params.len() - 1,
+                    params.len()
+                ));
+            }
+            Filter::TokenAccountState => {
+                conditions.push(format!(
+                    "((LENGTH(latest.data) = {length} AND GET_BYTE(latest.data, {state}) <> 0) \
+                    OR (LENGTH(latest.data) > {length} AND LENGTH(latest.data) <> {multisig} \
+                    AND GET_BYTE(latest.data, {length}) = {account_type}))",
+                    length = crate::TOKEN_ACCOUNT_LENGTH,
+                    multisig = crate::MULTISIG_LENGTH,
+                    state = crate::TOKEN_ACCOUNT_STATE_OFFSET,
+                    account_type = crate::ACCOUNT_TYPE_ACCOUNT,
+                ));
             }
         }
+    }
 
-        if let Some(data_size_exists) = data_size {
-            query += &format!(" AND length(data) = ${};", params.len() + 1);
-
-            params.push(&data_size_exists);
-
-            let rows = pg_client.query(&query, &params).await?;
+    Ok(())
+}
 
-            Ok(rows)
-        } else {
-            query += ";";
-            let rows = pg_client.query(&query, &params).await?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MemCmpBytes, MemCmpData};
+
+    const PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+
+    /// The SQL and the parameters generated for every combination of
+    /// filters, `dataSlice`, commitment and context slot
+    #[test]
+    fn build_gpa_sql() -> anyhow::Result<()> {
+        // No filters, the owner is the only condition
+        let (query, params) = build(GetProgramAccounts::new().add_public_key(PROGRAM), None)?;
+        assert!(query.contains("latest.rent_epoch, latest.data FROM ("));
+        assert!(query.contains("AND accounts.slot <= (SELECT MAX(slot) FROM slots WHERE LOWER(status::VARCHAR) = 'finalized')"));
+        assert!(query.ends_with("WHERE latest.owner = $1::TEXT;"));
+        assert_eq!(params, vec![format!("{:?}", PROGRAM)]);
+
+        // The commitment changes the highest visible slot
+        let (query, _) = build(
+            GetProgramAccounts::new()
+                .add_public_key(PROGRAM)
+                .add_commitment(Commitment::Processed),
+            None,
+        )?;
+        assert!(query.contains("AND accounts.slot <= (SELECT MAX(slot) FROM slots) ORDER BY"));
+
+        // A `dataSize` filter
+        let filters = vec![Filter::DataSize(165)];
+        let (query, params) = build(
+            GetProgramAccounts::new()
+                .add_public_key(PROGRAM)
+                .add_filters(&filters),
+            None,
+        )?;
+        assert!(
+            query.ends_with("WHERE latest.owner = $1::TEXT AND LENGTH(latest.data) = $2::BIGINT;")
+        );
+        assert_eq!(params[1..], ["165"]);
+
+        // A `memcmp` filter without a `dataSize`
+        let filters = vec![memcmp(32, "1Ldp")];
+        let (query, params) = build(
+            GetProgramAccounts::new()
+                .add_public_key(PROGRAM)
+                .add_filters(&filters),
+            None,
+        )?;
+        assert!(query.ends_with(
+            "WHERE latest.owner = $1::TEXT AND SUBSTRING(latest.data FROM $2 FOR $3) = $4;"
+        ));
+        assert_eq!(params[1..], ["33", "4", "[0, 1, 2, 3]"]);
+
+        // A `dataSlice` with every kind of filter and a known context slot
+        let filters = vec![memcmp(0, "1"), Filter::DataSize(165), memcmp(64, "2")];
+        let (query, params) = build(
+            GetProgramAccounts::new()
+                .add_public_key(PROGRAM)
+                .add_commitment(Commitment::Confirmed)
+                .add_min_context_slot(Some(90))
+                .add_data_slice(Some(DataSlice {
+                    offset: 8,
+                    length: 32,
+                }))
+                .add_filters(&filters)
+                .add_with_context(true),
+            Some(100),
+        )?;
+        assert!(query.contains("latest.rent_epoch, SUBSTRING(latest.data FROM $3 FOR $4) FROM ("));
+        assert!(query.contains("AND accounts.slot <= $2::BIGINT ORDER BY"));
+        assert!(query.ends_with(
+            "WHERE latest.owner = $1::TEXT \
+            AND SUBSTRING(latest.data FROM $5 FOR $6) = $7 \
+            AND LENGTH(latest.data) = $8::BIGINT \
+            AND SUBSTRING(latest.data FROM $9 FOR $10) = $11;"
+        ));
+        assert_eq!(
+            params[1..],
+            ["100", "9", "32", "1", "1", "[0]", "165", "65", "1", "[1]"]
+        );
+
+        // The token accounts of `getTokenAccountsByOwner` with Token-2022, the initialized
+        // accounts without extensions or with the `AccountType` of an account that is not a multisig
+        let filters = vec![memcmp(32, "1Ldp"), Filter::TokenAccountState];
+        let (query, params) = build(
+            GetProgramAccounts::new()
+                .add_public_key(PROGRAM)
+                .add_filters(&filters),
+            None,
+        )?;
+        assert!(query.ends_with(
+            "WHERE latest.owner = $1::TEXT \
+            AND SUBSTRING(latest.data FROM $2 FOR $3) = $4 \
+            AND ((LENGTH(latest.data) = 165 AND GET_BYTE(latest.data, 108) <> 0) \
+            OR (LENGTH(latest.data) > 165 AND LENGTH(latest.data) <> 355 \
+            AND GET_BYTE(latest.data, 165) = 2));"
+        ));
+        assert_eq!(params[1..], ["33", "4", "[0, 1, 2, 3]"]);
+
+        // An invalid `memcmp` is rejected before the query is sent
+        let filters = vec![memcmp(0, "0OIl")];
+        assert!(GetProgramAccounts::new()
+            .add_public_key(PROGRAM)
+            .add_filters(&filters)
+            .build(None)
+            .is_err());
+
+        Ok(())
+    }
 
-            Ok(rows)
-        }
+    fn memcmp(offset: usize, bytes: &str) -> Filter {
+        Filter::Memcmp(MemCmpData {
+            offset,
+            bytes: MemCmpBytes::Encoded(bytes.to_owned()),
+            encoding: Option::None,
+        })
     }
-}
 
-impl<'q> Default for GetProgramAccounts<'q> {
-    fn default() -> Self {
-        GetProgramAccounts::new()
+    /// Build the query with the whitespace collapsed and the parameters printed with [fmt::Debug](core::fmt::Debug)
+    fn build(
+        gpa: GetProgramAccounts,
+        context_slot: Option<u64>,
+    ) -> anyhow::Result<(String, Vec<String>)> {
+        let (query, params) = gpa
+            .build(context_slot)
+            .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+
+        let query = query.split_whitespace().collect::<Vec<&str>>().join(" ");
+        let params = params
+            .iter()
+            .map(|param: &SqlParam| format!("{:?}", param))
+            .collect::<Vec<String>>();
+
+        Ok((query, params))
     }
 }
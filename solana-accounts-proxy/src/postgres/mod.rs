@@ -1,6 +1,12 @@
 mod pg_connection;
 pub use pg_connection::*;
 
+mod pg_tls;
+pub use pg_tls::*;
+
+mod pg_router;
+pub use pg_router::*;
+
 mod ga_queries;
 pub use ga_queries::*;
 
@@ -10,8 +16,14 @@ pub use pg_row_types::*;
 mod gpa_queries;
 pub use gpa_queries::*;
 
-/// Print the length of the `Row`s Vec and the total size in MiB of the Vec
-pub fn row_data_size_info(rows_len: usize) {
-    let row_len = rows_len as f32;
-    tracing::debug!("NUM OF ACCOUNTS - {:?}", &row_len);
-}
+mod gma_queries;
+pub use gma_queries::*;
+
+mod balance_queries;
+pub use balance_queries::*;
+
+mod token_queries;
+pub use token_queries::*;
+
+mod supply_queries;
+pub use supply_queries::*;
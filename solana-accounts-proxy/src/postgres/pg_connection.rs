@@ -1,21 +1,41 @@
-use crate::{config::PostgresConfig, ProxyError, ProxyResult};
-use jsonrpsee::core::Error as JsonrpseeError;
+use crate::{config::PostgresConfig, PgTls, ProxyError, ProxyResult};
+use bb8::{ErrorSink, Pool, PooledConnection, RunError};
+use bb8_postgres::PostgresConnectionManager;
+use jsonrpsee::{
+    core::Error as JsonrpseeError,
+    types::error::{CallError, ErrorObject, INTERNAL_ERROR_CODE},
+};
 use std::time::Duration;
-use tokio_postgres::{error::Severity, Client, Config, NoTls};
+use tokio_postgres::{error::Severity, Config};
+use tokio_postgres_rustls::MakeRustlsConnect;
+
+/// The pool of connections to the Postgres database
+pub type PgPool = Pool<PostgresConnectionManager<MakeRustlsConnect>>;
+
+/// A connection checked out of the [PgPool], it is returned to the pool when dropped
+pub type PgClient = PooledConnection<'static, PostgresConnectionManager<MakeRustlsConnect>>;
 
 /// A Postgres database connection
 pub struct PgConnection;
 
 impl PgConnection {
-    /// Connect to a database using the configuration [ProxyConfig]
-    pub async fn connect(user_config: &PostgresConfig) -> anyhow::Result<Client> {
+    /// The default maximum number of connections opened by the pool
+    pub const POOL_MAX_SIZE: u32 = 10;
+    /// The default minimum number of idle connections kept open by the pool
+    pub const POOL_MIN_IDLE: u32 = 1;
+    /// The default number of seconds to wait for a connection from the pool
+    pub const POOL_CONNECTION_TIMEOUT: u64 = 30;
+
+    /// Create the `tokio_postgres` configuration from the [PostgresConfig]
+    pub fn config(user_config: &PostgresConfig) -> Config {
         use secrecy::ExposeSecret;
 
         let mut config = Config::new();
         config
             .user(user_config.user.expose_secret())
             .dbname(&user_config.dbname)
-            .host(user_config.host.as_str());
+            .host(user_config.host.as_str())
+            .ssl_mode(PgTls::ssl_mode(user_config.ssl_mode()));
 
         if let Some(password) = &user_config.password {
             config.password(password.expose_secret());
@@ -37,17 +57,41 @@ impl PgConnection {
             config.connect_timeout(Duration::from_secs(secs));
         }
 
-        let (client, db_conn) = config.connect(NoTls).await?;
+        config
+    }
+
+    /// Create a pool of connections to a database using the configuration [PostgresConfig].
+    /// No connection is opened here, the pool opens `pool_min_idle` connections in the background
+    /// and a failed connection attempt is retried with an exponential backoff until `pool_connection_timeout`.
+    /// Connections are health checked when they are checked out and broken connections are replaced,
+    /// so a restart or failover of the database does not require a restart of the proxy.
+    /// Returns an error if the TLS configuration is invalid.
+    pub fn connect(user_config: &PostgresConfig) -> anyhow::Result<PgPool> {
+        let manager = PostgresConnectionManager::new(
+            PgConnection::config(user_config),
+            PgTls::connector(user_config)?,
+        );
 
-        tokio::spawn(async {
-            if let Err(error) = db_conn.await {
-                tracing::error!("connection error: {}", error);
+        let pool = Pool::builder()
+            .max_size(user_config.pool_max_size())
+            .min_idle(Some(user_config.pool_min_idle()))
+            .connection_timeout(Duration::from_secs(user_config.pool_connection_timeout()))
+            .test_on_check_out(true)
+            .retry_connection(true)
+            .error_sink(Box::new(PgErrorSink))
+            .build_unchecked(manager);
 
-                std::process::exit(1)
-            }
-        });
+        Ok(pool)
+    }
 
-        Ok(client)
+    /// Check out a connection from the database picked by the router in [crate::PG_ROUTER]
+    pub async fn client() -> ProxyResult<PgClient> {
+        match crate::PG_ROUTER.get() {
+            Some(router) => router.client().await,
+            None => Err(ProxyError::Internal(
+                "The pool of connections to the database does not exist.".to_owned(),
+            )),
+        }
     }
 
     /// Log all the errors encountered ny the Postgres connection
@@ -80,7 +124,11 @@ impl PgConnection {
             None => PgConnection::unresolved_error(error),
         }
 
-        JsonrpseeError::Custom(crate::INTERNAL_SERVER_ERROR.to_owned())
+        JsonrpseeError::Call(CallError::Custom(ErrorObject::owned(
+            INTERNAL_ERROR_CODE,
+            crate::INTERNAL_SERVER_ERROR,
+            Option::<()>::None,
+        )))
     }
 
     /// Errors cannot be converted to [Severity]
@@ -91,15 +139,29 @@ impl PgConnection {
             error.to_string()
         );
     }
+}
+
+/// Logs the errors of the connections opened in the background by the [PgPool]
+#[derive(Debug, Clone, Copy)]
+pub struct PgErrorSink;
+
+impl ErrorSink<tokio_postgres::Error> for PgErrorSink {
+    fn sink(&self, error: tokio_postgres::Error) {
+        tracing::error!("connection error: {}", error);
+    }
+
+    fn boxed_clone(&self) -> Box<dyn ErrorSink<tokio_postgres::Error>> {
+        Box::new(*self)
+    }
+}
 
-    /// Handles a HTTP response when the static variable [crate::CLIENT] is [Option::None]
-    pub async fn client_exists() -> ProxyResult<()> {
-        if crate::CLIENT.read().await.is_none() {
-            Err(ProxyError::Client(
-                "Internal server error. The connection to the database does not exist.".to_owned(),
-            ))
-        } else {
-            Ok(())
+impl From<RunError<tokio_postgres::Error>> for ProxyError {
+    fn from(error: RunError<tokio_postgres::Error>) -> Self {
+        match error {
+            RunError::User(pg_error) => ProxyError::Pg(pg_error),
+            RunError::TimedOut => ProxyError::Internal(
+                "Timed out waiting for a connection to the database.".to_owned(),
+            ),
         }
     }
 }
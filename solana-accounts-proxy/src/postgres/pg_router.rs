@@ -0,0 +1,475 @@
+use crate::{
+    Commitment, PgClient, PgConnection, PgPool, PgRole, PostgresConfig, ProxyError, ProxyResult,
+    RoutingConfig,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// A database the proxy can send requests to
+pub struct PgBackend {
+    address: String,
+    role: PgRole,
+    weight: u32,
+    pool: PgPool,
+    healthy: AtomicBool,
+    finalized_slot: AtomicU64,
+}
+
+impl PgBackend {
+    /// The `host:port` of the database
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// The role of the database
+    pub fn role(&self) -> PgRole {
+        self.role
+    }
+
+    /// Whether the database responded to the last health check and,
+    /// for a replica, was not too far behind the freshest database
+    pub fn is_healthy(&self) -> bool {
+        self.healthy.load(Ordering::Relaxed)
+    }
+
+    /// The latest finalized slot seen by the last successful health check
+    pub fn finalized_slot(&self) -> u64 {
+        self.finalized_slot.load(Ordering::Relaxed)
+    }
+
+    /// The pool of connections to the database
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
+
+/// The freshest finalized slot of the databases and when it was first seen
+#[derive(Debug, Clone, Copy)]
+struct SlotProgress {
+    slot: u64,
+    advanced_at: Instant,
+}
+
+/// Routes the requests across the configured databases.
+/// The requests are spread across the healthy replicas according to their `weight`.
+/// The primary only serves requests when no replica is healthy, and when there is no
+/// primary every replica is used regardless of its health so the proxy keeps answering.
+pub struct PgRouter {
+    backends: Vec<PgBackend>,
+    max_replica_lag_slots: u64,
+    health_check_interval: Duration,
+    health_check_slot_distance: u64,
+    slot_progress: Mutex<Option<SlotProgress>>,
+    next: AtomicUsize,
+}
+
+impl PgRouter {
+    /// The target duration of a slot of the Solana cluster in milliseconds
+    pub const MS_PER_SLOT: u64 = 400;
+
+    /// Create a pool of connections for every database, see [PgConnection::connect].
+    /// Every database is considered healthy until the first health check.
+    pub fn new(
+        user_configs: &[PostgresConfig],
+        routing_config: &RoutingConfig,
+    ) -> anyhow::Result<Self> {
+        let mut backends = Vec::with_capacity(user_configs.len());
+
+        for user_config in user_configs {
+            backends.push(PgBackend {
+                address: user_config.address(),
+                role: user_config.role(),
+                weight: user_config.weight(),
+                pool: PgConnection::connect(user_config)?,
+                healthy: AtomicBool::new(true),
+                finalized_slot: AtomicU64::new(0),
+            });
+        }
+
+        Ok(PgRouter {
+            backends,
+            max_replica_lag_slots: routing_config.max_replica_lag_slots(),
+            health_check_interval: Duration::from_secs(routing_config.health_check_interval()),
+            health_check_slot_distance: routing_config.health_check_slot_distance(),
+            slot_progress: Mutex::new(Option::None),
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// All the configured databases
+    pub fn backends(&self) -> &[PgBackend] {
+        &self.backends
+    }
+
+    /// Pick the database for the next request using a weighted round robin
+    pub fn backend(&self) -> ProxyResult<&PgBackend> {
+        let is_candidate: fn(&PgBackend) -> bool =
+            if self.backends.iter().any(PgRouter::is_healthy_replica) {
+                PgRouter::is_healthy_replica
+            } else if self
+                .backends
+                .iter()
+                .any(|backend| backend.role == PgRole::Primary)
+            {
+                |backend| backend.role == PgRole::Primary
+            } else {
+                |_| true
+            };
+
+        let candidates = self.backends.iter().filter(|backend| is_candidate(backend));
+
+        let total_weight = candidates
+            .clone()
+            .map(|backend| backend.weight as usize)
+            .sum::<usize>();
+
+        if total_weight == 0 {
+            return candidates.clone().next().ok_or_else(|| {
+                ProxyError::Internal("No database has been configured.".to_owned())
+            });
+        }
+
+        let mut position = self.next.fetch_add(1, Ordering::Relaxed) % total_weight;
+
+        for backend in candidates {
+            if position < backend.weight as usize {
+                return Ok(backend);
+            }
+
+            position -= backend.weight as usize;
+        }
+
+        unreachable!("The position is always lower than the total weight of the candidates")
+    }
+
+    /// Check out a connection from the database picked by [PgRouter::backend]
+    pub async fn client(&self) -> ProxyResult<PgClient> {
+        Ok(self.backend()?.pool.get_owned().await?)
+    }
+
+    /// Query the latest finalized slot of every database concurrently and update which databases are healthy
+    pub async fn check_health(&'static self) {
+        let finalized_slots = self
+            .backends
+            .iter()
+            .map(|backend| tokio::spawn(self.query_finalized_slot(backend)))
+            .collect::<Vec<_>>();
+
+        let mut outcome = Vec::with_capacity(finalized_slots.len());
+        for finalized_slot in finalized_slots {
+            outcome.push(finalized_slot.await.unwrap_or_default());
+        }
+
+        self.update_health(&outcome);
+    }
+
+    /// Update the health of every database from the latest finalized slot of each database,
+    /// in the same order as [PgRouter::backends]. [Option::None] means the database could not be queried.
+    /// A database that cannot be queried is unhealthy and a replica is also unhealthy
+    /// when it is more than `max_replica_lag_slots` behind the freshest database.
+    pub fn update_health(&self, finalized_slots: &[Option<u64>]) {
+        self.update_health_at(finalized_slots, Instant::now())
+    }
+
+    /// [PgRouter::update_health] with the freshest slot seen at `now`
+    pub fn update_health_at(&self, finalized_slots: &[Option<u64>], now: Instant) {
+        let freshest_slot = finalized_slots.iter().flatten().max().copied();
+
+        if let Some(freshest_slot) = freshest_slot {
+            let mut slot_progress = self
+                .slot_progress
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+            let advanced = match slot_progress.as_ref() {
+                Some(progress) => freshest_slot > progress.slot,
+                None => true,
+            };
+
+            if advanced {
+                slot_progress.replace(SlotProgress {
+                    slot: freshest_slot,
+                    advanced_at: now,
+                });
+            }
+        }
+
+        for (backend, finalized_slot) in self.backends.iter().zip(finalized_slots) {
+            let healthy = match (finalized_slot, freshest_slot) {
+                (Some(finalized_slot), Some(freshest_slot)) => {
+                    backend
+                        .finalized_slot
+                        .store(*finalized_slot, Ordering::Relaxed);
+
+                    let slots_behind = freshest_slot.saturating_sub(*finalized_slot);
+
+                    backend.role == PgRole::Primary || slots_behind <= self.max_replica_lag_slots
+                }
+                _ => false,
+            };
+
+            let was_healthy = backend.healthy.swap(healthy, Ordering::Relaxed);
+
+            match (was_healthy, healthy) {
+                (true, false) => tracing::warn!(
+                    "Database `{}` is unhealthy at finalized slot `{:?}` while the freshest database is at `{:?}`",
+                    backend.address,
+                    finalized_slot,
+                    freshest_slot
+                ),
+                (false, true) => tracing::info!(
+                    "Database `{}` is healthy again at finalized slot `{:?}`",
+                    backend.address,
+                    finalized_slot
+                ),
+                _ => (),
+            }
+        }
+    }
+
+    /// The health of the proxy reported by `getHealth`, see [PgRouter::health_at]
+    pub fn health(&self) -> ProxyResult<()> {
+        self.health_at(Instant::now())
+    }
+
+    /// The proxy is unhealthy when no database answered the last health check or when the
+    /// freshest finalized slot has not advanced for longer than `health_check_slot_distance` slots.
+    /// The number of slots behind is estimated from the time since the slot last advanced.
+    pub fn health_at(&self, now: Instant) -> ProxyResult<()> {
+        let slot_progress = *self
+            .slot_progress
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let slot_progress = match slot_progress {
+            Some(slot_progress) if self.backends.iter().any(PgBackend::is_healthy) => slot_progress,
+            _ => {
+                return Err(ProxyError::NodeUnhealthy {
+                    num_slots_behind: Option::None,
+                })
+            }
+        };
+
+        let elapsed = now.saturating_duration_since(slot_progress.advanced_at);
+        let num_slots_behind = (elapsed.as_millis() / PgRouter::MS_PER_SLOT as u128) as u64;
+
+        if num_slots_behind > self.health_check_slot_distance {
+            return Err(ProxyError::NodeUnhealthy {
+                num_slots_behind: Some(num_slots_behind),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Run [PgRouter::check_health] every `health_check_interval` seconds in the background
+    pub fn spawn_health_check(&'static self) {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(self.health_check_interval).await;
+
+                self.check_health().await;
+            }
+        });
+    }
+
+    /// A database that does not answer within the health check interval is considered unreachable
+    async fn query_finalized_slot(&self, backend: &PgBackend) -> Option<u64> {
+        let query = format!("SELECT {};", Commitment::Finalized.max_slot_subquery());
+
+        let outcome = tokio::time::timeout(self.health_check_interval, async {
+            let pg_client = backend.pool.get().await?;
+            let row = pg_client.query_one(&query, &[]).await?;
+
+            Ok::<Option<i64>, ProxyError>(row.get(0))
+        })
+        .await;
+
+        match outcome {
+            Ok(Ok(finalized_slot)) => finalized_slot.map(|finalized_slot| finalized_slot as u64),
+            Ok(Err(error)) => {
+                tracing::error!(
+                    "Health check of the database `{}` failed: `{:?}`",
+                    backend.address,
+                    error
+                );
+
+                Option::None
+            }
+            Err(_) => {
+                tracing::error!(
+                    "Health check of the database `{}` timed out",
+                    backend.address
+                );
+
+                Option::None
+            }
+        }
+    }
+
+    fn is_healthy_replica(backend: &PgBackend) -> bool {
+        backend.role == PgRole::Replica && backend.weight > 0 && backend.is_healthy()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProxyConfig;
+    use std::collections::HashMap;
+
+    const REPLICATED_CONFIG: &str = r#"
+    [socket]
+    ip = "127.0.0.1"
+    port = 4000
+
+    [[postgres]]
+    user = "postgres"
+    dbname = "geyser"
+    host = "primary.invalid"
+    pool_min_idle = 0
+
+    [[postgres]]
+    user = "postgres"
+    dbname = "geyser"
+    host = "replica-a.invalid"
+    role = "replica"
+    weight = 3
+    pool_min_idle = 0
+
+    [[postgres]]
+    user = "postgres"
+    dbname = "geyser"
+    host = "replica-b.invalid"
+    role = "replica"
+    pool_min_idle = 0
+
+    [routing]
+    max_replica_lag_slots = 100
+    "#;
+
+    /// The weighted routing across the replicas and the ejection of the lagging replicas.
+    /// The pools never connect so no database is needed.
+    #[tokio::test]
+    async fn route_across_replicas() -> anyhow::Result<()> {
+        let config = toml::from_str::<ProxyConfig>(REPLICATED_CONFIG)?;
+        assert_eq!(config.postgres_configs().len(), 3);
+        assert_eq!(config.postgres_configs()[0].role(), PgRole::Primary);
+        assert_eq!(config.postgres_configs()[1].role(), PgRole::Replica);
+        assert_eq!(config.routing_config().max_replica_lag_slots(), 100);
+
+        let router = PgRouter::new(config.postgres_configs(), config.routing_config())?;
+
+        // Both replicas are healthy, the primary does not serve requests
+        router.update_health(&[Some(1000), Some(1000), Some(950)]);
+        let routed = route(&router, 400)?;
+        assert_eq!(routed.get("replica-a.invalid:5432"), Some(&300));
+        assert_eq!(routed.get("replica-b.invalid:5432"), Some(&100));
+        assert_eq!(routed.get("primary.invalid:5432"), None);
+
+        // `replica-b` is 101 slots behind the freshest database and is ejected
+        router.update_health(&[Some(1101), Some(1101), Some(1000)]);
+        let routed = route(&router, 400)?;
+        assert_eq!(routed.get("replica-a.invalid:5432"), Some(&400));
+        assert!(!router.backends()[2].is_healthy());
+
+        // `replica-a` is unreachable and `replica-b` caught up
+        router.update_health(&[Some(1200), None, Some(1200)]);
+        let routed = route(&router, 400)?;
+        assert_eq!(routed.get("replica-b.invalid:5432"), Some(&400));
+
+        // No replica is healthy, the primary serves every request
+        router.update_health(&[Some(1300), None, Some(1000)]);
+        let routed = route(&router, 400)?;
+        assert_eq!(routed.get("primary.invalid:5432"), Some(&400));
+
+        Ok(())
+    }
+
+    /// `getHealth` reports how many slots the proxy is behind from the time since the
+    /// freshest finalized slot last advanced
+    #[tokio::test]
+    async fn slot_health() -> anyhow::Result<()> {
+        let config = toml::from_str::<ProxyConfig>(&REPLICATED_CONFIG.replace(
+            "max_replica_lag_slots = 100",
+            "max_replica_lag_slots = 100\nhealth_check_slot_distance = 50",
+        ))?;
+        assert_eq!(config.routing_config().health_check_slot_distance(), 50);
+
+        let router = PgRouter::new(config.postgres_configs(), config.routing_config())?;
+
+        // No health check has completed yet
+        assert_eq!(slots_behind(&router, Instant::now()), Err(None));
+
+        let start = Instant::now();
+        router.update_health_at(&[Some(1000), Some(1000), Some(1000)], start);
+        assert_eq!(
+            slots_behind(&router, start + Duration::from_secs(20)),
+            Ok(())
+        );
+
+        // 50 slots of 400ms, the slot distance is not exceeded yet
+        assert_eq!(
+            slots_behind(&router, start + Duration::from_millis(20_399)),
+            Ok(())
+        );
+        assert_eq!(
+            slots_behind(&router, start + Duration::from_secs(30)),
+            Err(Some(75))
+        );
+
+        // The same slot again does not reset the time it advanced at
+        router.update_health_at(
+            &[Some(1000), Some(990), None],
+            start + Duration::from_secs(30),
+        );
+        assert_eq!(
+            slots_behind(&router, start + Duration::from_secs(30)),
+            Err(Some(75))
+        );
+
+        // The freshest slot advanced
+        router.update_health_at(&[Some(1001), None, None], start + Duration::from_secs(31));
+        assert_eq!(
+            slots_behind(&router, start + Duration::from_secs(32)),
+            Ok(())
+        );
+
+        // No database answered the health check
+        router.update_health_at(&[None, None, None], start + Duration::from_secs(33));
+        assert_eq!(
+            slots_behind(&router, start + Duration::from_secs(33)),
+            Err(None)
+        );
+
+        Ok(())
+    }
+
+    /// The number of slots behind of an unhealthy proxy, `None` if it is not known
+    fn slots_behind(router: &PgRouter, now: Instant) -> Result<(), Option<u64>> {
+        match router.health_at(now) {
+            Ok(()) => Ok(()),
+            Err(ProxyError::NodeUnhealthy { num_slots_behind }) => Err(num_slots_behind),
+            Err(error) => panic!("Unexpected error `{:?}`", error),
+        }
+    }
+
+    fn route(router: &PgRouter, requests: usize) -> anyhow::Result<HashMap<String, usize>> {
+        let mut routed = HashMap::<String, usize>::new();
+
+        for _ in 0..requests {
+            let backend = router
+                .backend()
+                .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+
+            *routed.entry(backend.address().to_owned()).or_default() += 1;
+        }
+
+        Ok(routed)
+    }
+}
@@ -1,20 +1,31 @@
-use crate::{Account, AccountInfo, Context, Encoding};
+use crate::{Account, AccountEncoder, AccountInfo, Context, PgClient, ProxyResult};
+use core::fmt;
+use futures_util::{stream::Fuse, StreamExt};
 use jsonrpsee::core::RpcResult;
 use serde_json::Value as SerdeJsonValue;
-use tokio_postgres::Row;
-
-#[cfg(debug_assertions)]
-use tokio::time::Instant;
+use std::{collections::HashMap, pin::Pin};
+use tokio_postgres::{types::ToSql, Client, Row, RowStream};
 
 /// Enables easier serialization from a postgres `Row` from the `getAccountInfo` query
 #[derive(Debug)]
 pub struct GetAccountInfoRow {
     pub(crate) context: Context,
-    pub(crate) value: Account,
+    pub(crate) value: Option<Account>,
+}
+
+impl GetAccountInfoRow {
+    /// The result for a public key that has no account visible at the context slot
+    pub fn missing(context: Context) -> Self {
+        GetAccountInfoRow {
+            context,
+            value: Option::None,
+        }
+    }
 }
 
 impl From<Row> for GetAccountInfoRow {
     fn from(row: Row) -> Self {
+        // The context slot of the commitment level, not the slot the account was written at
         let slot: i64 = row.get(0);
         let slot = slot as u64;
         let data: Vec<u8> = row.get(1);
@@ -24,87 +35,332 @@ impl From<Row> for GetAccountInfoRow {
         let rent_epoch: i64 = row.get(5);
 
         GetAccountInfoRow {
-            context: Context {
-                slot,
-                api_version: Option::None,
-            },
-            value: Account {
+            context: Context::new(slot),
+            value: Some(Account {
                 data,
                 executable,
                 owner,
                 lamports,
                 rent_epoch,
-            },
+            }),
         }
     }
 }
 
-/// Enables easier serialization from a postgres `Row` from the `getAccountInfo` query
+/// Enables easier serialization from the postgres `Row`s of the `getMultipleAccounts` query
 #[derive(Debug)]
-pub struct GetProgramAccountsRow;
+pub struct GetMultipleAccountsRow {
+    pub(crate) context: Context,
+    pub(crate) value: HashMap<String, Account>,
+}
 
-impl GetProgramAccountsRow {
-    /// Convert a postgres Row into [AccountInfo] then to JSON format in one method.
-    pub fn from_row(rows: Vec<Row>, encoding: Encoding) -> RpcResult<Vec<SerdeJsonValue>> {
-        tracing::debug!("NUMBER OF ROWS TO PARSE: {:?}", &rows.len());
-        tracing::debug!("PARSING ROWS AND CONVERTING TO JSON");
-
-        #[cfg(debug_assertions)]
-        let timer = Instant::now();
-
-        // FIXME remove re-allocations in each iter
-
-        use rayon::prelude::*;
-        let account_info_list = rows
-            .par_iter()
-            .map(|row| {
-                let pubkey: String = row.get(0);
-                let lamports: i64 = row.get(1);
-                let owner: String = row.get(2);
+impl GetMultipleAccountsRow {
+    /// Encode the [Account] of each public key in the order they were requested,
+    /// using `null` for the accounts that do not exist
+    pub fn in_order(
+        &self,
+        base58_public_keys: &[String],
+        encoder: &AccountEncoder,
+    ) -> RpcResult<Vec<SerdeJsonValue>> {
+        base58_public_keys
+            .iter()
+            .map(
+                |base58_public_key| match self.value.get(base58_public_key) {
+                    Some(account) => encoder.account(base58_public_key, account),
+                    None => Ok(SerdeJsonValue::Null),
+                },
+            )
+            .collect()
+    }
+}
+
+impl From<Vec<Row>> for GetMultipleAccountsRow {
+    fn from(rows: Vec<Row>) -> Self {
+        let mut slot = 0u64;
+        let mut value = HashMap::new();
+
+        for row in rows {
+            let context_slot: Option<i64> = row.get(0);
+            slot = context_slot.unwrap_or_default() as u64;
+
+            // The columns of the account are `NULL` when none of the public keys exist
+            let pubkey: Option<String> = row.get(1);
+            if let Some(pubkey) = pubkey {
+                let data: Vec<u8> = row.get(2);
                 let executable: bool = row.get(3);
-                let rent_epoch: i64 = row.get(4);
-                let data: Vec<u8> = row.get(5);
-
-                let account = Account {
-                    data,
-                    executable,
-                    owner,
-                    lamports,
-                    rent_epoch,
-                };
+                let owner: String = row.get(4);
+                let lamports: i64 = row.get(5);
+                let rent_epoch: i64 = row.get(6);
+
+                value.insert(
+                    pubkey,
+                    Account {
+                        data,
+                        executable,
+                        owner,
+                        lamports,
+                        rent_epoch,
+                    },
+                );
+            }
+        }
+
+        GetMultipleAccountsRow {
+            context: Context::new(slot),
+            value,
+        }
+    }
+}
+
+/// Enables easier serialization from the postgres `Row` of the `getBalance` query
+#[derive(Debug)]
+pub struct GetBalanceRow {
+    pub(crate) context: Context,
+    pub(crate) value: u64,
+}
+
+impl From<Row> for GetBalanceRow {
+    fn from(row: Row) -> Self {
+        let slot: Option<i64> = row.get(0);
+        // `NULL` when the account does not exist, Solana returns a balance of `0`
+        let lamports: Option<i64> = row.get(1);
+
+        GetBalanceRow {
+            context: Context::new(slot.unwrap_or_default() as u64),
+            value: lamports.unwrap_or_default() as u64,
+        }
+    }
+}
+
+/// Enables easier serialization from a postgres `Row` of the `getTokenLargestAccounts` query
+#[derive(Debug)]
+pub struct TokenAmountRow {
+    pub(crate) address: String,
+    pub(crate) amount: u64,
+}
+
+impl From<Row> for TokenAmountRow {
+    fn from(row: Row) -> Self {
+        let address: String = row.get(0);
+        // The little endian amount of the token account
+        let amount: Vec<u8> = row.get(1);
+        let amount = amount
+            .try_into()
+            .map(u64::from_le_bytes)
+            .unwrap_or_default();
+
+        TokenAmountRow { address, amount }
+    }
+}
+
+/// Enables easier serialization from the postgres `Row` of the `getSupply` query
+#[derive(Debug)]
+pub struct SupplyRow {
+    pub(crate) total: u64,
+    pub(crate) non_circulating: u64,
+}
+
+impl From<Row> for SupplyRow {
+    fn from(row: Row) -> Self {
+        let total: i64 = row.get(0);
+        let non_circulating: i64 = row.get(1);
+
+        SupplyRow {
+            total: total as u64,
+            non_circulating: non_circulating as u64,
+        }
+    }
+}
+
+/// Enables easier serialization from a postgres `Row` of the `getLargestAccounts` query
+#[derive(Debug)]
+pub struct LamportsRow {
+    pub(crate) address: String,
+    pub(crate) lamports: u64,
+}
+
+impl From<Row> for LamportsRow {
+    fn from(row: Row) -> Self {
+        let address: String = row.get(0);
+        let lamports: i64 = row.get(1);
+
+        LamportsRow {
+            address,
+            lamports: lamports as u64,
+        }
+    }
+}
+
+/// Streams the `Row`s of the `getProgramAccounts` query as they are received from the database
+/// so the accounts are never buffered in memory all at once
+pub struct GetProgramAccountsRow {
+    pub(crate) context: Option<Context>,
+    // The connection is only returned to the pool once every row has been received
+    pg_client: Option<PgClient>,
+    rows: GpaRows,
+}
+
+/// How the rows of the `getProgramAccounts` query are received
+enum GpaRows {
+    /// Every row as soon as the database sends it.
+    /// Fused so reading past the last row keeps returning no rows.
+    Stream(Pin<Box<Fuse<RowStream>>>),
+    /// [AccountEncoder::BATCH_SIZE] rows at a time from a cursor so the connection is free
+    /// to run other queries in between, see [GetProgramAccountsRow::query_cursor]
+    Cursor {
+        context_slot: u64,
+        rows: std::vec::IntoIter<Row>,
+        done: bool,
+    },
+}
+
+impl GetProgramAccountsRow {
+    /// The name of the cursor of [GetProgramAccountsRow::query_cursor]
+    const CURSOR: &'static str = "gpa_accounts";
 
-                let account_info = AccountInfo { pubkey, account };
-                let to_json = account_info.as_json_value(encoding);
+    /// Run the query, only waits for the database to start returning rows
+    pub async fn query(
+        pg_client: PgClient,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> ProxyResult<Self> {
+        let rows = pg_client.query_raw(query, params.iter().copied()).await?;
 
-                to_json
-            })
-            .collect::<RpcResult<Vec<SerdeJsonValue>>>();
+        Ok(GetProgramAccountsRow {
+            context: Option::None,
+            pg_client: Some(pg_client),
+            rows: GpaRows::Stream(Box::pin(rows.fuse())),
+        })
+    }
+
+    /// Run the query read at `context_slot` through a cursor in a read only transaction.
+    /// The rows are fetched [AccountEncoder::BATCH_SIZE] at a time so the connection can load the
+    /// mints of a batch at the same slot, see [GetProgramAccountsRow::client].
+    pub async fn query_cursor(
+        pg_client: PgClient,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        context_slot: u64,
+    ) -> ProxyResult<Self> {
+        let mut rows = GetProgramAccountsRow {
+            context: Option::None,
+            pg_client: Option::None,
+            rows: GpaRows::Cursor {
+                context_slot,
+                rows: Vec::new().into_iter(),
+                done: false,
+            },
+        };
 
-        #[cfg(debug_assertions)]
-        let outcome = Instant::now().duration_since(timer);
+        pg_client.batch_execute("BEGIN READ ONLY;").await?;
+        // The transaction is rolled back on drop if the cursor cannot be declared
+        let pg_client = rows.pg_client.insert(pg_client);
 
-        #[cfg(debug_assertions)]
-        tracing::debug!(
-            "FINISHED PARSING ROWS AND CONVERTING TO JSON IN {}s",
-            outcome.as_secs()
+        let query = format!(
+            "DECLARE {} NO SCROLL CURSOR FOR {}",
+            GetProgramAccountsRow::CURSOR,
+            query.trim().trim_end_matches(';')
         );
+        pg_client.execute(&query, params).await?;
 
-        #[cfg(debug_assertions)]
-        let mut mb = 0usize;
+        Ok(rows)
+    }
+
+    /// Add the context slot the accounts were read at
+    pub fn add_context(mut self, context: Option<Context>) -> Self {
+        self.context = context;
 
-        let account_info_list = account_info_list?;
+        self
+    }
 
-        #[cfg(debug_assertions)]
-        for chunk in &account_info_list {
-            mb += chunk.to_string().as_bytes().len();
+    /// The connection and the context slot of a query read through a cursor, the connection
+    /// is free to run other queries once [AccountEncoder::BATCH_SIZE] rows have been received.
+    /// [Option::None] when the rows are streamed.
+    pub fn client(&self) -> Option<(&Client, u64)> {
+        match (&self.rows, self.pg_client.as_ref()) {
+            (GpaRows::Cursor { context_slot, .. }, Some(pg_client)) => {
+                Some((&**pg_client, *context_slot))
+            }
+            _ => Option::None,
         }
+    }
+
+    /// Receive the next account, returns [Option::None] once every row has been received
+    pub async fn next_account(&mut self) -> ProxyResult<Option<AccountInfo>> {
+        let row = match &mut self.rows {
+            GpaRows::Stream(rows) => match rows.next().await {
+                Some(row) => row?,
+                None => return Ok(Option::None),
+            },
+            GpaRows::Cursor { rows, done, .. } => loop {
+                if let Some(row) = rows.next() {
+                    break row;
+                }
+
+                let pg_client = match (*done, self.pg_client.as_ref()) {
+                    (false, Some(pg_client)) => pg_client,
+                    _ => return Ok(Option::None),
+                };
 
-        #[cfg(debug_assertions)]
-        let to_mb = mb as f32 / 1024.0 / 1024.0;
+                let query = format!(
+                    "FETCH {} FROM {};",
+                    AccountEncoder::BATCH_SIZE,
+                    GetProgramAccountsRow::CURSOR
+                );
+                let fetched = pg_client.query(&query, &[]).await?;
 
-        #[cfg(debug_assertions)]
-        tracing::debug!("TOTAL LENGTH OF DATA IN MB - {}", to_mb);
+                if fetched.len() < AccountEncoder::BATCH_SIZE {
+                    pg_client.batch_execute("COMMIT;").await?;
+                    *done = true;
+                }
+
+                *rows = fetched.into_iter();
+            },
+        };
+
+        let pubkey: String = row.get(0);
+        let lamports: i64 = row.get(1);
+        let owner: String = row.get(2);
+        let executable: bool = row.get(3);
+        let rent_epoch: i64 = row.get(4);
+        let data: Vec<u8> = row.get(5);
+
+        let account = Account {
+            data,
+            executable,
+            owner,
+            lamports,
+            rent_epoch,
+        };
+
+        Ok(Some(AccountInfo { pubkey, account }))
+    }
+}
+
+impl Drop for GetProgramAccountsRow {
+    fn drop(&mut self) {
+        // A cursor that was not read to the end leaves its transaction open,
+        // it is rolled back before the connection is returned to the pool
+        if let (GpaRows::Cursor { done: false, .. }, Some(pg_client)) =
+            (&self.rows, self.pg_client.take())
+        {
+            if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+                runtime.spawn(async move {
+                    if let Err(error) = pg_client.batch_execute("ROLLBACK;").await {
+                        tracing::error!(
+                            "Failed to close the `getProgramAccounts` cursor: {}",
+                            error
+                        );
+                    }
+                });
+            }
+        }
+    }
+}
 
-        Ok(account_info_list)
+impl fmt::Debug for GetProgramAccountsRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GetProgramAccountsRow")
+            .finish_non_exhaustive()
     }
 }
@@ -0,0 +1,428 @@
+use crate::{PgSslMode, PostgresConfig};
+use anyhow::{anyhow, bail};
+use rustls::{
+    client::{ServerCertVerified, ServerCertVerifier, WebPkiVerifier},
+    Certificate, CertificateError, ClientConfig, Error as TlsError, PrivateKey, RootCertStore,
+    ServerName,
+};
+use rustls_pemfile::Item;
+use std::{fs::File, io::BufReader, path::Path, sync::Arc, time::SystemTime};
+use tokio_postgres::config::SslMode;
+use tokio_postgres_rustls::MakeRustlsConnect;
+
+/// Creates the TLS connector of the Postgres connection from the `ssl_*` fields of [PostgresConfig]
+pub struct PgTls;
+
+impl PgTls {
+    /// The SSL mode passed to `tokio_postgres`. `tokio_postgres` only knows whether TLS is
+    /// disabled, preferred or required, the certificate checks of `verify-ca` and `verify-full`
+    /// are done by the connector returned by [PgTls::connector].
+    pub fn ssl_mode(ssl_mode: PgSslMode) -> SslMode {
+        match ssl_mode {
+            PgSslMode::Disable => SslMode::Disable,
+            PgSslMode::Prefer => SslMode::Prefer,
+            PgSslMode::Require | PgSslMode::VerifyCa | PgSslMode::VerifyFull => SslMode::Require,
+        }
+    }
+
+    /// Build the TLS connector with the same certificate checks as `libpq`:
+    /// - `disable` and `prefer` do not verify the server certificate
+    /// - `require` only verifies the server certificate against `ssl_root_cert` if it is set
+    /// - `verify-ca` verifies the server certificate is signed by a CA in `ssl_root_cert`
+    /// - `verify-full` also verifies the server certificate is valid for the `host`
+    pub fn connector(user_config: &PostgresConfig) -> anyhow::Result<MakeRustlsConnect> {
+        let ssl_mode = user_config.ssl_mode();
+
+        let root_certs = match user_config.ssl_root_cert.as_ref() {
+            Some(path) => Some(PgTls::load_root_certs(path)?),
+            None => Option::None,
+        };
+
+        let verifier: Arc<dyn ServerCertVerifier> = match (ssl_mode, root_certs) {
+            (PgSslMode::Disable | PgSslMode::Prefer, _) | (PgSslMode::Require, None) => {
+                Arc::new(NoVerification)
+            }
+            (PgSslMode::Require | PgSslMode::VerifyCa, Some(root_certs)) => {
+                Arc::new(VerifyCa(WebPkiVerifier::new(root_certs, None)))
+            }
+            (PgSslMode::VerifyFull, Some(root_certs)) => {
+                Arc::new(WebPkiVerifier::new(root_certs, None))
+            }
+            (PgSslMode::VerifyCa | PgSslMode::VerifyFull, None) => bail!(
+                "The `ssl_root_cert` field is required for the `{}` SSL mode",
+                ssl_mode.to_str()
+            ),
+        };
+
+        let builder = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(verifier);
+
+        let config = match (
+            user_config.ssl_cert.as_ref(),
+            user_config.ssl_key.as_ref(),
+        ) {
+            (Some(ssl_cert), Some(ssl_key)) => builder.with_client_auth_cert(
+                PgTls::load_certs(ssl_cert)?,
+                PgTls::load_private_key(ssl_key)?,
+            )?,
+            (None, None) => builder.with_no_client_auth(),
+            _ => bail!("Both the `ssl_cert` and `ssl_key` fields are required to authenticate with a client certificate"),
+        };
+
+        Ok(MakeRustlsConnect::new(config))
+    }
+
+    /// Load the PEM bundle of the certificate authorities trusted to sign the server certificate
+    pub fn load_root_certs(path: &Path) -> anyhow::Result<RootCertStore> {
+        let mut root_certs = RootCertStore::empty();
+
+        for cert in PgTls::load_certs(path)? {
+            root_certs.add(&cert)?;
+        }
+
+        Ok(root_certs)
+    }
+
+    /// Load a PEM certificate chain
+    pub fn load_certs(path: &Path) -> anyhow::Result<Vec<Certificate>> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let certs = rustls_pemfile::certs(&mut reader)?
+            .into_iter()
+            .map(Certificate)
+            .collect::<Vec<Certificate>>();
+
+        if certs.is_empty() {
+            bail!("No PEM certificate was found in `{}`", path.display());
+        }
+
+        Ok(certs)
+    }
+
+    /// Load the first PKCS#8, PKCS#1 (RSA) or SEC1 (EC) private key in a PEM file
+    pub fn load_private_key(path: &Path) -> anyhow::Result<PrivateKey> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        while let Some(item) = rustls_pemfile::read_one(&mut reader)? {
+            match item {
+                Item::PKCS8Key(key) | Item::RSAKey(key) | Item::ECKey(key) => {
+                    return Ok(PrivateKey(key))
+                }
+                _ => continue,
+            }
+        }
+
+        Err(anyhow!(
+            "No PEM private key was found in `{}`",
+            path.display()
+        ))
+    }
+}
+
+/// Accepts any server certificate, the connection is encrypted but the server is not authenticated
+struct NoVerification;
+
+impl ServerCertVerifier for NoVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, TlsError> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+/// Verifies the server certificate is signed by a trusted CA without checking the host name
+struct VerifyCa(WebPkiVerifier);
+
+impl ServerCertVerifier for VerifyCa {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        server_name: &ServerName,
+        scts: &mut dyn Iterator<Item = &[u8]>,
+        ocsp_response: &[u8],
+        now: SystemTime,
+    ) -> Result<ServerCertVerified, TlsError> {
+        match self.0.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            scts,
+            ocsp_response,
+            now,
+        ) {
+            Err(TlsError::InvalidCertificate(CertificateError::NotValidForName)) => {
+                Ok(ServerCertVerified::assertion())
+            }
+            outcome => outcome,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PgConnection, PgTls, PostgresConfig};
+    use rcgen::{BasicConstraints, Certificate, CertificateParams, DnType, IsCa};
+    use rustls::{server::AllowAnyAuthenticatedClient, RootCertStore, ServerConfig};
+    use std::{path::PathBuf, sync::Arc, time::Duration};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+    use tokio_rustls::TlsAcceptor;
+
+    /// The request code a Postgres client sends to ask the server to upgrade the connection to TLS
+    const SSL_REQUEST_CODE: u32 = 80877103;
+
+    /// How the mock Postgres server saw the connection
+    #[derive(Debug, PartialEq, Eq)]
+    enum TlsOutcome {
+        /// The client sent the startup message without asking for TLS
+        Plaintext,
+        /// The TLS handshake completed
+        Established,
+        /// The TLS handshake was aborted by the client or the server
+        Rejected,
+    }
+
+    /// PEM files of the certificates used by the mock Postgres server and the client
+    struct TlsFixtures {
+        dir: PathBuf,
+        ca: Certificate,
+        server: Certificate,
+        wrong_host_server: Certificate,
+    }
+
+    impl TlsFixtures {
+        fn new() -> anyhow::Result<Self> {
+            let dir = std::env::temp_dir()
+                .join(format!("solana-accounts-proxy-tls-{}", std::process::id()));
+            std::fs::create_dir_all(&dir)?;
+
+            let ca = TlsFixtures::ca("Proxy Testsuite CA")?;
+            let other_ca = TlsFixtures::ca("Proxy Testsuite Other CA")?;
+            let server = TlsFixtures::leaf("localhost")?;
+            let wrong_host_server = TlsFixtures::leaf("postgres.invalid")?;
+            let client = TlsFixtures::leaf("proxy")?;
+
+            std::fs::write(dir.join("ca.pem"), ca.serialize_pem()?)?;
+            std::fs::write(dir.join("other-ca.pem"), other_ca.serialize_pem()?)?;
+            std::fs::write(
+                dir.join("client.pem"),
+                client.serialize_pem_with_signer(&ca)?,
+            )?;
+            std::fs::write(dir.join("client.key"), client.serialize_private_key_pem())?;
+
+            Ok(TlsFixtures {
+                dir,
+                ca,
+                server,
+                wrong_host_server,
+            })
+        }
+
+        fn ca(common_name: &str) -> anyhow::Result<Certificate> {
+            let mut params = CertificateParams::new(Vec::new());
+            params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            params
+                .distinguished_name
+                .push(DnType::CommonName, common_name);
+
+            Ok(Certificate::from_params(params)?)
+        }
+
+        fn leaf(host: &str) -> anyhow::Result<Certificate> {
+            let mut params = CertificateParams::new(vec![host.to_owned()]);
+            params.distinguished_name.push(DnType::CommonName, host);
+
+            Ok(Certificate::from_params(params)?)
+        }
+
+        fn path(&self, file_name: &str) -> String {
+            self.dir.join(file_name).display().to_string()
+        }
+
+        /// The TLS configuration of the mock server, signed by the testsuite CA
+        fn server_config(
+            &self,
+            server: &Certificate,
+            require_client_cert: bool,
+        ) -> anyhow::Result<Arc<ServerConfig>> {
+            let cert_chain = vec![rustls::Certificate(
+                server.serialize_der_with_signer(&self.ca)?,
+            )];
+            let private_key = rustls::PrivateKey(server.serialize_private_key_der());
+
+            let builder = ServerConfig::builder().with_safe_defaults();
+
+            let config = if require_client_cert {
+                let mut client_roots = RootCertStore::empty();
+                client_roots.add(&rustls::Certificate(self.ca.serialize_der()?))?;
+
+                builder
+                    .with_client_cert_verifier(
+                        AllowAnyAuthenticatedClient::new(client_roots).boxed(),
+                    )
+                    .with_single_cert(cert_chain, private_key)?
+            } else {
+                builder
+                    .with_no_client_auth()
+                    .with_single_cert(cert_chain, private_key)?
+            };
+
+            Ok(Arc::new(config))
+        }
+    }
+
+    /// Run the proxy TLS connector against a mock Postgres server for every `ssl_mode`
+    #[tokio::test]
+    async fn ssl_modes() -> anyhow::Result<()> {
+        let fixtures = TlsFixtures::new()?;
+        let ca = fixtures.path("ca.pem");
+        let other_ca = fixtures.path("other-ca.pem");
+        let client_cert = fixtures.path("client.pem");
+        let client_key = fixtures.path("client.key");
+
+        let cases = [
+            ("disable", "", &fixtures.server, TlsOutcome::Plaintext),
+            ("prefer", "", &fixtures.server, TlsOutcome::Established),
+            ("require", "", &fixtures.server, TlsOutcome::Established),
+            (
+                "require",
+                &format!("ssl_root_cert = '{}'", other_ca),
+                &fixtures.server,
+                TlsOutcome::Rejected,
+            ),
+            (
+                "verify-ca",
+                &format!("ssl_root_cert = '{}'", ca),
+                &fixtures.wrong_host_server,
+                TlsOutcome::Established,
+            ),
+            (
+                "verify-ca",
+                &format!("ssl_root_cert = '{}'", other_ca),
+                &fixtures.server,
+                TlsOutcome::Rejected,
+            ),
+            (
+                "verify-full",
+                &format!("ssl_root_cert = '{}'", ca),
+                &fixtures.server,
+                TlsOutcome::Established,
+            ),
+            (
+                "verify-full",
+                &format!("ssl_root_cert = '{}'", ca),
+                &fixtures.wrong_host_server,
+                TlsOutcome::Rejected,
+            ),
+        ];
+
+        for (ssl_mode, extra_fields, server, expected) in cases {
+            let outcome = tls_handshake(&fixtures, server, false, ssl_mode, extra_fields).await?;
+
+            assert_eq!(outcome, expected, "ssl_mode {} {}", ssl_mode, extra_fields);
+        }
+
+        let with_client_cert = format!(
+            "ssl_root_cert = '{}'\nssl_cert = '{}'\nssl_key = '{}'",
+            ca, client_cert, client_key
+        );
+        let outcome = tls_handshake(
+            &fixtures,
+            &fixtures.server,
+            true,
+            "verify-full",
+            &with_client_cert,
+        )
+        .await?;
+        assert_eq!(outcome, TlsOutcome::Established);
+
+        let without_client_cert = format!("ssl_root_cert = '{}'", ca);
+        let outcome = tls_handshake(
+            &fixtures,
+            &fixtures.server,
+            true,
+            "verify-full",
+            &without_client_cert,
+        )
+        .await?;
+        assert_eq!(outcome, TlsOutcome::Rejected);
+
+        assert!(PgTls::connector(&postgres_config("verify-ca", 5432, "")?).is_err());
+        assert!(PgTls::connector(&postgres_config(
+            "require",
+            5432,
+            &format!("ssl_cert = '{}'", client_cert)
+        )?)
+        .is_err());
+
+        Ok(())
+    }
+
+    fn postgres_config(
+        ssl_mode: &str,
+        port: u16,
+        extra_fields: &str,
+    ) -> anyhow::Result<PostgresConfig> {
+        let config = format!(
+            "user = 'postgres'\ndbname = 'postgres'\nhost = 'localhost'\nport = {}\nconnect_timeout = 5\nssl_mode = '{}'\n{}",
+            port, ssl_mode, extra_fields
+        );
+
+        Ok(toml::from_str(&config)?)
+    }
+
+    /// Connect the proxy to a mock Postgres server that only answers the `SSLRequest`
+    /// and the TLS handshake, then report how the server saw the connection
+    async fn tls_handshake(
+        fixtures: &TlsFixtures,
+        server: &Certificate,
+        require_client_cert: bool,
+        ssl_mode: &str,
+        extra_fields: &str,
+    ) -> anyhow::Result<TlsOutcome> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let port = listener.local_addr()?.port();
+        let acceptor = TlsAcceptor::from(fixtures.server_config(server, require_client_cert)?);
+
+        let server_thread = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await?;
+
+            let mut request = [0u8; 8];
+            stream.read_exact(&mut request).await?;
+
+            let code = u32::from_be_bytes([request[4], request[5], request[6], request[7]]);
+            if code != SSL_REQUEST_CODE {
+                return Ok::<TlsOutcome, anyhow::Error>(TlsOutcome::Plaintext);
+            }
+
+            stream.write_all(b"S").await?;
+
+            match acceptor.accept(stream).await {
+                Ok(_) => Ok(TlsOutcome::Established),
+                Err(_) => Ok(TlsOutcome::Rejected),
+            }
+        });
+
+        let user_config = postgres_config(ssl_mode, port, extra_fields)?;
+        let connector = PgTls::connector(&user_config)?;
+
+        // The mock server closes the connection after the handshake so the connection itself always fails
+        let _ = tokio::time::timeout(
+            Duration::from_secs(5),
+            PgConnection::config(&user_config).connect(connector),
+        )
+        .await;
+
+        tokio::time::timeout(Duration::from_secs(5), server_thread).await??
+    }
+}
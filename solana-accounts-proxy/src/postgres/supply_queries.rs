@@ -0,0 +1,256 @@
+use crate::{LamportsRow, LargestAccountsFilter, ProxyResult, SqlParam, SupplyRow};
+use tokio_postgres::{types::ToSql, Client};
+
+/// The lamports of the latest version of every account written at or before the context slot `$1`
+const LATEST_LAMPORTS: &str = "
+            SELECT DISTINCT ON (accounts.pubkey)
+                accounts.pubkey,
+                accounts.lamports
+            FROM accounts
+            WHERE accounts.slot <= $1::BIGINT
+            ORDER BY accounts.pubkey, accounts.slot DESC";
+
+/// Helper struct to create the query for `getSupply` using the builder pattern
+#[derive(Debug)]
+pub struct GetSupplyQuery<'q> {
+    context_slot: u64,
+    non_circulating_accounts: &'q [String],
+}
+
+impl<'q> GetSupplyQuery<'q> {
+    /// Instantiate the struct with defaults
+    pub fn new() -> Self {
+        GetSupplyQuery {
+            context_slot: 0,
+            non_circulating_accounts: &[],
+        }
+    }
+
+    /// Add the context slot the accounts are read at
+    pub fn add_context_slot(mut self, context_slot: u64) -> Self {
+        self.context_slot = context_slot;
+
+        self
+    }
+
+    /// Add the base58 public keys of the non circulating accounts
+    pub fn add_non_circulating_accounts(mut self, non_circulating_accounts: &'q [String]) -> Self {
+        self.non_circulating_accounts = non_circulating_accounts;
+
+        self
+    }
+
+    /// Run the query, the lamports of every account and of the non circulating accounts
+    pub async fn query(&self, pg_client: &Client) -> ProxyResult<SupplyRow> {
+        let (query, params) = self.build();
+
+        let params = params
+            .iter()
+            .map(|param| param.as_ref() as &(dyn ToSql + Sync))
+            .collect::<Vec<&(dyn ToSql + Sync)>>();
+
+        Ok(pg_client.query_one(&query, &params).await?.into())
+    }
+
+    /// Build the SQL query and its parameters.
+    /// Both sums are computed over the latest version of every account in a single scan,
+    /// the `SUM` of a `BIGINT` is a `NUMERIC` that is cast back since the supply fits a `BIGINT`.
+    pub fn build(&self) -> (String, Vec<SqlParam>) {
+        let params: Vec<SqlParam> = vec![
+            Box::new(self.context_slot as i64),
+            Box::new(self.non_circulating_accounts.to_vec()),
+        ];
+
+        let query = format!(
+            "
+            SELECT
+                COALESCE(SUM(latest.lamports), 0)::BIGINT AS total,
+                COALESCE(SUM(latest.lamports) FILTER (WHERE latest.pubkey = ANY($2::TEXT[])), 0)::BIGINT AS non_circulating
+            FROM ({}
+            ) AS latest;",
+            LATEST_LAMPORTS
+        );
+
+        (query, params)
+    }
+}
+
+impl<'q> Default for GetSupplyQuery<'q> {
+    fn default() -> Self {
+        GetSupplyQuery::new()
+    }
+}
+
+/// Helper struct to create the query for `getLargestAccounts` using the builder pattern
+#[derive(Debug)]
+pub struct GetLargestAccountsQuery<'q> {
+    context_slot: u64,
+    non_circulating_accounts: &'q [String],
+    filter: Option<LargestAccountsFilter>,
+}
+
+impl<'q> GetLargestAccountsQuery<'q> {
+    /// The maximum number of accounts returned, same as Solana
+    pub const MAX_ACCOUNTS: i64 = 20;
+
+    /// Instantiate the struct with defaults
+    pub fn new() -> Self {
+        GetLargestAccountsQuery {
+            context_slot: 0,
+            non_circulating_accounts: &[],
+            filter: Option::None,
+        }
+    }
+
+    /// Add the context slot the accounts are read at
+    pub fn add_context_slot(mut self, context_slot: u64) -> Self {
+        self.context_slot = context_slot;
+
+        self
+    }
+
+    /// Add the base58 public keys of the non circulating accounts
+    pub fn add_non_circulating_accounts(mut self, non_circulating_accounts: &'q [String]) -> Self {
+        self.non_circulating_accounts = non_circulating_accounts;
+
+        self
+    }
+
+    /// Add the filter on the non circulating accounts
+    pub fn add_filter(mut self, filter: Option<LargestAccountsFilter>) -> Self {
+        self.filter = filter;
+
+        self
+    }
+
+    /// Run the query, the accounts with the most lamports first
+    pub async fn query(&self, pg_client: &Client) -> ProxyResult<Vec<LamportsRow>> {
+        let (query, params) = self.build();
+
+        let params = params
+            .iter()
+            .map(|param| param.as_ref() as &(dyn ToSql + Sync))
+            .collect::<Vec<&(dyn ToSql + Sync)>>();
+
+        let rows = pg_client.query(&query, &params).await?;
+
+        Ok(rows.into_iter().map(LamportsRow::from).collect())
+    }
+
+    /// Build the SQL query and its parameters.
+    /// Closed accounts keep a version without lamports so they are left out.
+    /// Equal balances are ordered by public key like Solana.
+    pub fn build(&self) -> (String, Vec<SqlParam>) {
+        let mut params: Vec<SqlParam> = vec![Box::new(self.context_slot as i64)];
+
+        let mut conditions = vec!["latest.lamports > 0".to_owned()];
+
+        if let Some(filter) = self.filter {
+            params.push(Box::new(self.non_circulating_accounts.to_vec()));
+
+            conditions.push(match filter {
+                LargestAccountsFilter::Circulating => {
+                    format!("latest.pubkey <> ALL(${}::TEXT[])", params.len())
+                }
+                LargestAccountsFilter::NonCirculating => {
+                    format!("latest.pubkey = ANY(${}::TEXT[])", params.len())
+                }
+            });
+        }
+
+        params.push(Box::new(GetLargestAccountsQuery::MAX_ACCOUNTS));
+
+        let query = format!(
+            "
+            SELECT
+                latest.pubkey,
+                latest.lamports
+            FROM ({}
+            ) AS latest
+            WHERE {}
+            ORDER BY latest.lamports DESC, latest.pubkey DESC
+            LIMIT ${};",
+            LATEST_LAMPORTS,
+            conditions.join(" AND "),
+            params.len()
+        );
+
+        (query, params)
+    }
+}
+
+impl<'q> Default for GetLargestAccountsQuery<'q> {
+    fn default() -> Self {
+        GetLargestAccountsQuery::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The SQL of `getSupply` and `getLargestAccounts`
+    #[test]
+    fn build_supply_sql() {
+        let non_circulating_accounts =
+            vec!["Stake11111111111111111111111111111111111111".to_owned()];
+
+        let (query, params) = GetSupplyQuery::new()
+            .add_context_slot(100)
+            .add_non_circulating_accounts(&non_circulating_accounts)
+            .build();
+        let query = query.split_whitespace().collect::<Vec<&str>>().join(" ");
+        let params = debug_params(&params);
+
+        assert!(query.contains("COALESCE(SUM(latest.lamports), 0)::BIGINT AS total"));
+        assert!(query.contains(
+            "COALESCE(SUM(latest.lamports) FILTER (WHERE latest.pubkey = ANY($2::TEXT[])), 0)::BIGINT AS non_circulating"
+        ));
+        assert!(query.contains(
+            "WHERE accounts.slot <= $1::BIGINT ORDER BY accounts.pubkey, accounts.slot DESC ) AS latest;"
+        ));
+        assert_eq!(params, ["100", &format!("{:?}", non_circulating_accounts)]);
+
+        // Without a filter the non circulating accounts are not sent
+        let (query, params) = GetLargestAccountsQuery::new()
+            .add_context_slot(100)
+            .add_non_circulating_accounts(&non_circulating_accounts)
+            .build();
+        let query = query.split_whitespace().collect::<Vec<&str>>().join(" ");
+        assert!(query.ends_with(
+            "WHERE latest.lamports > 0 ORDER BY latest.lamports DESC, latest.pubkey DESC LIMIT $2;"
+        ));
+        assert_eq!(debug_params(&params), ["100", "20"]);
+
+        for (filter, condition) in [
+            (
+                LargestAccountsFilter::Circulating,
+                "WHERE latest.lamports > 0 AND latest.pubkey <> ALL($2::TEXT[]) ORDER BY",
+            ),
+            (
+                LargestAccountsFilter::NonCirculating,
+                "WHERE latest.lamports > 0 AND latest.pubkey = ANY($2::TEXT[]) ORDER BY",
+            ),
+        ] {
+            let (query, params) = GetLargestAccountsQuery::new()
+                .add_context_slot(100)
+                .add_non_circulating_accounts(&non_circulating_accounts)
+                .add_filter(Some(filter))
+                .build();
+            let query = query.split_whitespace().collect::<Vec<&str>>().join(" ");
+            assert!(query.contains(condition));
+            assert!(query.ends_with("LIMIT $3;"));
+            assert_eq!(
+                debug_params(&params)[1..],
+                [format!("{:?}", non_circulating_accounts), "20".to_owned()]
+            );
+        }
+    }
+
+    fn debug_params(params: &[SqlParam]) -> Vec<String> {
+        params
+            .iter()
+            .map(|param| format!("{:?}", param))
+            .collect::<Vec<String>>()
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{Filter, ProxyResult, SqlParam, TokenAmountRow};
+use tokio_postgres::{types::ToSql, Client};
+
+/// Helper struct to create the query for `getTokenLargestAccounts` using the builder pattern
+#[derive(Debug)]
+pub struct GetTokenLargestAccountsQuery<'q> {
+    program_id: &'q str,
+    mint: &'q [u8],
+    context_slot: u64,
+}
+
+impl<'q> GetTokenLargestAccountsQuery<'q> {
+    /// The maximum number of token accounts returned, same as Solana
+    pub const MAX_ACCOUNTS: i64 = 20;
+
+    /// Instantiate the struct with defaults
+    pub fn new() -> Self {
+        GetTokenLargestAccountsQuery {
+            program_id: "",
+            mint: &[],
+            context_slot: 0,
+        }
+    }
+
+    /// Add the token program that owns the mint
+    pub fn add_program_id(mut self, program_id: &'q str) -> Self {
+        self.program_id = program_id;
+
+        self
+    }
+
+    /// Add the bytes of the public key of the mint
+    pub fn add_mint(mut self, mint: &'q [u8]) -> Self {
+        self.mint = mint;
+
+        self
+    }
+
+    /// Add the context slot the mint was read at, the token accounts are read at the same slot
+    pub fn add_context_slot(mut self, context_slot: u64) -> Self {
+        self.context_slot = context_slot;
+
+        self
+    }
+
+    /// Run the query, the token accounts with the largest amounts first
+    pub async fn query(&self, pg_client: &Client) -> ProxyResult<Vec<TokenAmountRow>> {
+        let (query, params) = self.build()?;
+
+        let params = params
+            .iter()
+            .map(|param| param.as_ref() as &(dyn ToSql + Sync))
+            .collect::<Vec<&(dyn ToSql + Sync)>>();
+
+        let rows = pg_client.query(&query, &params).await?;
+
+        Ok(rows.into_iter().map(TokenAmountRow::from).collect())
+    }
+
+    /// Build the SQL query and its parameters.
+    /// The latest version of the token accounts of the mint is selected like `getProgramAccounts`
+    /// and ordered by the little endian `u64` amount of the account, decoded as a `NUMERIC`
+    /// since a `BIGINT` cannot hold every amount. Equal amounts are ordered by public key like Solana.
+    pub fn build(&self) -> ProxyResult<(String, Vec<SqlParam>)> {
+        let mut params: Vec<SqlParam> = vec![
+            Box::new(self.program_id.to_owned()),
+            Box::new(self.mint.to_vec()),
+            Box::new(self.context_slot as i64),
+        ];
+
+        let filters = [
+            Filter::memcmp_bytes(crate::TOKEN_ACCOUNT_MINT_OFFSET, self.mint.to_vec()),
+            Filter::token_accounts(self.program_id),
+        ];
+
+        let mut conditions = vec!["latest.owner = $1::TEXT".to_owned()];
+        crate::push_filter_conditions(&filters, &mut params, &mut conditions)?;
+
+        params.push(Box::new(GetTokenLargestAccountsQuery::MAX_ACCOUNTS));
+
+        let amount = (0..8)
+            .map(|byte| {
+                format!(
+                    "GET_BYTE(latest.data, {})::NUMERIC * {}",
+                    crate::TOKEN_ACCOUNT_AMOUNT_OFFSET + byte,
+                    1u64 << (8 * byte)
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(" + ");
+
+        let query = format!(
+            "
+            SELECT
+                latest.pubkey,
+                SUBSTRING(latest.data FROM {} FOR 8) AS amount
+            FROM (
+                SELECT DISTINCT ON (accounts.pubkey)
+                    accounts.pubkey,
+                    accounts.owner,
+                    accounts.data
+                FROM accounts
+                WHERE accounts.pubkey IN (
+                    SELECT owned.pubkey FROM accounts AS owned
+                    WHERE owned.owner = $1::TEXT
+                    AND SUBSTRING(owned.data FROM {} FOR 32) = $2
+                )
+                AND accounts.slot <= $3::BIGINT
+                ORDER BY accounts.pubkey, accounts.slot DESC
+            ) AS latest
+            WHERE {}
+            ORDER BY ({}) DESC, latest.pubkey DESC
+            LIMIT ${};",
+            crate::TOKEN_ACCOUNT_AMOUNT_OFFSET + 1,
+            crate::TOKEN_ACCOUNT_MINT_OFFSET + 1,
+            conditions.join(" AND "),
+            amount,
+            params.len()
+        );
+
+        Ok((query, params))
+    }
+}
+
+impl<'q> Default for GetTokenLargestAccountsQuery<'q> {
+    fn default() -> Self {
+        GetTokenLargestAccountsQuery::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+
+    /// The SQL of `getTokenLargestAccounts` for both token programs
+    #[test]
+    fn build_token_largest_accounts_sql() -> anyhow::Result<()> {
+        let mint = [9u8; 32];
+
+        let (query, params) = GetTokenLargestAccountsQuery::new()
+            .add_program_id(PROGRAM)
+            .add_mint(&mint)
+            .add_context_slot(100)
+            .build()
+            .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        let query = query.split_whitespace().collect::<Vec<&str>>().join(" ");
+        let params = params
+            .iter()
+            .map(|param: &SqlParam| format!("{:?}", param))
+            .collect::<Vec<String>>();
+
+        // The token accounts are narrowed to the mint before their latest version is selected
+        assert!(query.contains(
+            "WHERE owned.owner = $1::TEXT AND SUBSTRING(owned.data FROM 1 FOR 32) = $2 )"
+        ));
+        assert!(query.contains("AND accounts.slot <= $3::BIGINT ORDER BY"));
+        assert!(query.contains(
+            "WHERE latest.owner = $1::TEXT \
+            AND SUBSTRING(latest.data FROM $4 FOR $5) = $6 \
+            AND LENGTH(latest.data) = $7::BIGINT"
+        ));
+        // The amount is the little endian `u64` at offset 64
+        assert!(query.contains("SUBSTRING(latest.data FROM 65 FOR 8) AS amount"));
+        assert!(query.contains(
+            "ORDER BY (GET_BYTE(latest.data, 64)::NUMERIC * 1 + GET_BYTE(latest.data, 65)::NUMERIC * 256"
+        ));
+        assert!(query.contains(
+            "GET_BYTE(latest.data, 71)::NUMERIC * 72057594037927936) DESC, latest.pubkey DESC"
+        ));
+        assert!(query.ends_with("LIMIT $8;"));
+        assert_eq!(
+            params[2..],
+            [
+                "100",
+                "1",
+                "32",
+                &format!("{:?}", mint.to_vec()),
+                "165",
+                "20"
+            ]
+        );
+
+        // Token-2022 accounts can have extensions
+        let (query, _) = GetTokenLargestAccountsQuery::new()
+            .add_program_id("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb")
+            .add_mint(&mint)
+            .build()
+            .map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        assert!(query.contains("GET_BYTE(latest.data, 165) = 2))"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+use crate::{
+    Account, AccountInfo, AdditionalData, Encoding, GetMultipleAccountsQuery,
+    GetProgramAccountsRow, ProxyResult, SplTokenAdditionalData,
+};
+use jsonrpsee::core::RpcResult;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use tokio_postgres::Client;
+
+/// Encodes the accounts of a response. With the `jsonParsed` encoding the mints of the
+/// token accounts are loaded from the database at the context slot of the response before
+/// the accounts are parsed, each mint is only loaded once per request.
+#[derive(Debug)]
+pub struct AccountEncoder {
+    encoding: Encoding,
+    mint_decimals: HashMap<String, Option<u8>>,
+}
+
+impl AccountEncoder {
+    /// The number of accounts of a `getProgramAccounts` query read before their mints are loaded
+    pub const BATCH_SIZE: usize = 256;
+
+    /// Encode the accounts with `encoding`
+    pub fn new(encoding: Encoding) -> Self {
+        AccountEncoder {
+            encoding,
+            mint_decimals: HashMap::new(),
+        }
+    }
+
+    /// The encoding of the accounts
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Load the mints of the token accounts that have not been loaded yet with a single query
+    /// at the `context_slot` the accounts were read at, on the connection they were read with.
+    /// Does nothing unless the encoding is `jsonParsed`.
+    pub async fn load_additional_data<'a>(
+        &mut self,
+        accounts: impl IntoIterator<Item = &'a Account>,
+        context_slot: u64,
+        pg_client: &Client,
+    ) -> ProxyResult<()> {
+        if !matches!(self.encoding, Encoding::JsonParsed) {
+            return Ok(());
+        }
+
+        let mut mints = accounts
+            .into_iter()
+            .filter_map(|account| crate::token_account_mint(&account.owner, &account.data))
+            .filter(|mint| !self.mint_decimals.contains_key(mint))
+            .collect::<Vec<String>>();
+
+        if mints.is_empty() {
+            return Ok(());
+        }
+
+        mints.sort_unstable();
+        mints.dedup();
+
+        let rows = GetMultipleAccountsQuery::new()
+            .add_public_keys(&mints)
+            .add_context_slot(context_slot)
+            .query(pg_client)
+            .await?;
+
+        for mint in mints {
+            let decimals = rows
+                .value
+                .get(&mint)
+                .and_then(|account| crate::mint_decimals(&account.owner, &account.data));
+
+            self.mint_decimals.insert(mint, decimals);
+        }
+
+        Ok(())
+    }
+
+    /// The accounts needed to parse `account` that have been loaded by [AccountEncoder::load_additional_data]
+    pub fn additional_data(&self, account: &Account) -> AdditionalData {
+        if !matches!(self.encoding, Encoding::JsonParsed) {
+            return AdditionalData::default();
+        }
+
+        let spl_token = crate::token_account_mint(&account.owner, &account.data)
+            .and_then(|mint| self.mint_decimals.get(&mint).copied().flatten())
+            .map(|decimals| SplTokenAdditionalData { decimals });
+
+        AdditionalData { spl_token }
+    }
+
+    /// Encode an [Account], see [Account::to_json_value]
+    pub fn account(&self, pubkey: &str, account: &Account) -> RpcResult<JsonValue> {
+        account.to_json_value(pubkey, self.encoding, &self.additional_data(account))
+    }
+
+    /// Encode an [AccountInfo], see [AccountInfo::as_json_value]
+    pub fn account_info(&self, account_info: &AccountInfo) -> RpcResult<JsonValue> {
+        account_info.as_json_value(self.encoding, &self.additional_data(&account_info.account))
+    }
+
+    /// Read and encode the next [AccountEncoder::BATCH_SIZE] accounts of a `getProgramAccounts` query
+    /// so the mints of a batch are loaded with a single query on the connection of the query and
+    /// at its context slot, see [GetProgramAccountsRow::client]. No accounts means all have been read.
+    pub async fn next_accounts(
+        &mut self,
+        rows: &mut GetProgramAccountsRow,
+    ) -> RpcResult<Vec<JsonValue>> {
+        let mut account_infos = Vec::new();
+        while account_infos.len() < AccountEncoder::BATCH_SIZE {
+            match rows.next_account().await? {
+                Some(account_info) => account_infos.push(account_info),
+                None => break,
+            }
+        }
+
+        if let Some((pg_client, context_slot)) = rows.client() {
+            self.load_additional_data(
+                account_infos
+                    .iter()
+                    .map(|account_info| &account_info.account),
+                context_slot,
+                pg_client,
+            )
+            .await?;
+        }
+
+        account_infos
+            .iter()
+            .map(|account_info| self.account_info(account_info))
+            .collect()
+    }
+}
@@ -1,3 +1,4 @@
+use crate::{AdditionalData, ProxyError};
 use core::fmt;
 use jsonrpsee::core::RpcResult;
 use serde::{Deserialize, Serialize};
@@ -18,13 +19,18 @@ pub struct AccountInfo {
 impl AccountInfo {
     /// Convert the `AccountInfo` into a JSON value to pass to the
     /// RPC response
-    pub fn as_json_value(&self, encoding: crate::Encoding) -> RpcResult<SerdeJsonValue> {
+    pub fn as_json_value(
+        &self,
+        encoding: crate::Encoding,
+        additional_data: &AdditionalData,
+    ) -> RpcResult<SerdeJsonValue> {
         let timer = Instant::now();
 
         let mut map = Map::new();
         map.insert("pubkey".to_owned(), self.pubkey.as_str().into());
 
-        self.account.as_json_value(encoding, &mut map)?;
+        self.account
+            .as_json_value(&self.pubkey, encoding, additional_data, &mut map)?;
 
         let outcome = Instant::now().duration_since(timer);
 
@@ -58,49 +64,72 @@ pub struct Account {
 impl Account {
     /// (optional) dataSlice: <object> -
     /// limit the returned account data using the provided offset: <usize> and length: <usize> fields;
-    /// only available for "base58", "base64" or "base64+zstd" encodings.
+    /// only available for "binary", "base58", "base64" or "base64+zstd" encodings.
+    /// Like Solana the data is `data[offset..offset + length]` cut at the end of the data,
+    /// so a zero `length` or an `offset` past the end returns no data.
     pub fn as_data_slice(&mut self, offset: usize, length: usize) -> &mut Self {
-        if offset == 0 && length == 0 {
-            return self;
-        }
-
-        if length == 0 {
-            let partial_data = self.data[offset..].to_vec();
-
-            self.data = partial_data;
-
-            self
-        } else {
-            let partial_data = self.data[offset..=length].to_vec(); //TODO test for accuracy if the range is inclusive or not
+        let end = offset.saturating_add(length).min(self.data.len());
+        let start = offset.min(end);
 
-            self.data = partial_data;
+        self.data.truncate(end);
+        self.data.drain(..start);
 
-            self
-        }
+        self
     }
 
-    /// Convert to JSON format
+    /// Convert to JSON format and insert it into `map` under the `account` key
     pub fn as_json_value(
         &self,
+        pubkey: &str,
         encoding: crate::Encoding,
+        additional_data: &AdditionalData,
         map: &mut Map<String, SerdeJsonValue>,
     ) -> RpcResult<()> {
-        let mut json_result = Map::new();
-        json_result.insert(
-            "data".into(),
-            SerdeJsonValue::Array(vec![
-                encoding.encode(&self.data)?.into(),
-                encoding.to_str().into(),
-            ]),
+        map.insert(
+            "account".into(),
+            self.to_json_value(pubkey, encoding, additional_data)?,
         );
+
+        Ok(())
+    }
+
+    /// Convert to a JSON object. With the `jsonParsed` encoding the data is parsed by
+    /// [crate::parse_account_data] and falls back to `base64` if it cannot be parsed,
+    /// the `pubkey` of the account identifies sysvars and config accounts.
+    pub fn to_json_value(
+        &self,
+        pubkey: &str,
+        encoding: crate::Encoding,
+        additional_data: &AdditionalData,
+    ) -> RpcResult<SerdeJsonValue> {
+        let parsed = match encoding {
+            crate::Encoding::JsonParsed => {
+                match crate::parse_account_data(pubkey, &self.owner, &self.data, additional_data) {
+                    Ok(parsed) => Some(parsed),
+                    Err(error) => {
+                        tracing::trace!("ACCOUNT NOT PARSED: {:?}", error);
+
+                        Option::None
+                    }
+                }
+            }
+            _ => Option::None,
+        };
+
+        let data = match parsed {
+            Some(parsed) => serde_json::to_value(parsed)
+                .map_err(|error| ProxyError::Internal(error.to_string()))?,
+            None => encoding.encode_json(&self.data)?,
+        };
+
+        let mut json_result = Map::new();
+        json_result.insert("data".into(), data);
         json_result.insert("executable".into(), self.executable.into());
         json_result.insert("lamports".into(), self.lamports.into());
         json_result.insert("owner".into(), self.owner.clone().into());
         json_result.insert("rentEpoch".into(), self.rent_epoch.into());
 
-        map.insert("account".into(), json_result.into());
-
-        Ok(())
+        Ok(json_result.into())
     }
 }
 
@@ -115,3 +144,134 @@ impl fmt::Debug for Account {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataSlice, Encoding, MAX_BASE58_BYTES};
+    use proptest::prelude::*;
+    use serde_json::json;
+
+    const ACCOUNT: &str = "YMN9Qj5jPNp7j14VPcML1B6xGgcPWVZUGLFU3Mnyfaf";
+
+    /// The encodings of the account data against the responses of a Solana RPC node
+    #[test]
+    fn encode_like_solana() -> anyhow::Result<()> {
+        // `binary` returns a bare `base58` string, the other encodings a `[data, encoding]` array
+        let data = vec![1, 2, 3];
+        for (encoding, expected) in [
+            (Encoding::Binary, json!("Ldp")),
+            (Encoding::Base58, json!(["Ldp", "base58"])),
+            (Encoding::Base64, json!(["AQID", "base64"])),
+            (Encoding::JsonParsed, json!(["AQID", "base64"])),
+        ] {
+            assert_eq!(encode(&data, encoding)?["data"], expected);
+        }
+        assert_eq!(encode(&[], Encoding::Binary)?["data"], json!(""));
+
+        // `base58` and `binary` are limited to 128 bytes of data
+        let data = vec![7; MAX_BASE58_BYTES];
+        assert!(encode(&data, Encoding::Binary)?["data"].is_string());
+        assert!(encode(&data, Encoding::Base58)?["data"].is_array());
+
+        let data = vec![7; MAX_BASE58_BYTES + 1];
+        for encoding in [Encoding::Binary, Encoding::Base58] {
+            let error = format!("{:?}", encode(&data, encoding).unwrap_err());
+            assert!(error.contains(
+                "Encoded binary (base 58) data should be less than 128 bytes, please use Base64 encoding."
+            ));
+        }
+        assert_eq!(ProxyError::Base58DataTooLarge.code(), -32600);
+        assert!(encode(&data, Encoding::Base64).is_ok());
+
+        Ok(())
+    }
+
+    /// The `dataSlice` of a Solana RPC node, `data[offset..min(offset + length, len)]`
+    /// with no data for an offset past the end
+    #[test]
+    fn slice_like_solana() {
+        for (offset, length, expected) in [
+            (0, 0, vec![]),
+            (0, 3, vec![0, 1, 2]),
+            (2, 0, vec![]),
+            (2, 2, vec![2, 3]),
+            (3, 10, vec![3, 4]),
+            (5, 1, vec![]),
+            (6, 1, vec![]),
+            (usize::MAX, usize::MAX, vec![]),
+            (1, usize::MAX, vec![1, 2, 3, 4]),
+        ] {
+            assert_eq!(
+                slice(vec![0, 1, 2, 3, 4], offset, length),
+                expected,
+                "offset {} length {}",
+                offset,
+                length
+            );
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(4096))]
+
+        /// The slice of the data in memory and the slice returned by the database
+        /// are the slice of a Solana RPC node
+        #[test]
+        fn slice_properties(
+            data in prop::collection::vec(any::<u8>(), 0..256),
+            offset in prop_oneof![0..300usize, Just(usize::MAX), any::<usize>()],
+            length in prop_oneof![0..300usize, Just(usize::MAX), any::<usize>()],
+        ) {
+            let expected = reference_slice(&data, offset, length);
+
+            prop_assert_eq!(slice(data.clone(), offset, length), expected.clone());
+
+            // Postgres rejects a negative length and the start is 1-based
+            let (start, sql_length) = DataSlice { offset, length }.as_sql_range();
+            prop_assert!(start >= 1 && sql_length >= 0);
+            prop_assert_eq!(sql_substring(&data, start, sql_length), expected);
+        }
+    }
+
+    /// `SUBSTRING(data FROM start FOR length)` of a Postgres `BYTEA`,
+    /// an end past the data or past the largest `INTEGER` reads the rest of the data
+    fn sql_substring(data: &[u8], start: i32, length: i32) -> Vec<u8> {
+        let start = start as i64 - 1;
+        let end = (start + length as i64).min(data.len() as i64);
+
+        if start >= end {
+            return Vec::new();
+        }
+
+        data[start as usize..end as usize].to_vec()
+    }
+
+    /// The slice of a Solana RPC node, written as plainly as possible
+    fn reference_slice(data: &[u8], offset: usize, length: usize) -> Vec<u8> {
+        data.iter().skip(offset).take(length).copied().collect()
+    }
+
+    fn slice(data: Vec<u8>, offset: usize, length: usize) -> Vec<u8> {
+        let mut account = account(data);
+        account.as_data_slice(offset, length);
+
+        account.data
+    }
+
+    fn encode(data: &[u8], encoding: Encoding) -> anyhow::Result<SerdeJsonValue> {
+        account(data.to_vec())
+            .to_json_value(ACCOUNT, encoding, &AdditionalData::default())
+            .map_err(|error| anyhow::anyhow!("{:?}", error))
+    }
+
+    fn account(data: Vec<u8>) -> Account {
+        Account {
+            data,
+            executable: false,
+            lamports: 1,
+            owner: "11111111111111111111111111111111".to_owned(),
+            rent_epoch: 0,
+        }
+    }
+}
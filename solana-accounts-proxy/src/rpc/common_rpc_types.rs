@@ -1,3 +1,4 @@
+use crate::{ProxyError, ProxyResult, UiTokenAmount};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value as JsonValue};
 
@@ -12,6 +13,19 @@ pub struct Context {
 }
 
 impl Context {
+    /// The context of a response read at `slot`, the `apiVersion` is the version of the Solana node
+    pub fn new(slot: u64) -> Self {
+        Context {
+            slot,
+            api_version: Some(
+                crate::USER_CONFIG
+                    .rpc_config()
+                    .solana_core_version()
+                    .to_owned(),
+            ),
+        }
+    }
+
     /// Converts the [Context] into [serde_json::Value] and then inserts it to the
     /// `result` map
     pub fn as_json_value(&self) -> Map<String, JsonValue> {
@@ -37,16 +51,26 @@ impl Context {
 
         map.insert("context".into(), slot.into());
     }
+
+    /// Ensure the data at this context slot is not older than the `minContextSlot` the client requested
+    pub fn check_min_context_slot(&self, min_context_slot: Option<u64>) -> ProxyResult<()> {
+        match min_context_slot {
+            Some(min_context_slot) if self.slot < min_context_slot => {
+                Err(ProxyError::MinContextSlotNotReached {
+                    context_slot: self.slot,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 impl From<tokio_postgres::Row> for Context {
     fn from(row: tokio_postgres::Row) -> Self {
-        let max: i64 = row.get(0);
+        // `MAX(slot)` is `NULL` when no slot has been recorded yet
+        let max: Option<i64> = row.get(0);
 
-        Context {
-            slot: max as u64,
-            api_version: Option::None, //TODO Add the API version here
-        }
+        Context::new(max.unwrap_or_default() as u64)
     }
 }
 
@@ -96,3 +120,67 @@ where
         map
     }
 }
+
+/// A token account of `getTokenLargestAccounts` with its amount
+#[derive(Debug, Serialize)]
+pub struct TokenAccountBalance {
+    /// The public key of the token account
+    pub address: String,
+    /// The amount of the token account
+    #[serde(flatten)]
+    pub amount: UiTokenAmount,
+}
+
+/// The result of `getVersion`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RpcVersionInfo {
+    /// The version of the Solana node writing to the databases
+    pub solana_core: String,
+    /// The feature set of the Solana node, if it is configured
+    pub feature_set: Option<u32>,
+    /// The version of the proxy
+    pub solana_accounts_proxy: String,
+}
+
+/// The value of `getSupply`, in lamports
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSupply {
+    /// The lamports of every account
+    pub total: u64,
+    /// The lamports of the accounts that are not non circulating
+    pub circulating: u64,
+    /// The lamports of the non circulating accounts
+    pub non_circulating: u64,
+    /// The non circulating accounts, empty with `excludeNonCirculatingAccountsList`
+    pub non_circulating_accounts: Vec<String>,
+}
+
+/// An account of `getLargestAccounts`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RpcAccountBalance {
+    /// The public key of the account
+    pub address: String,
+    /// The lamports of the account
+    pub lamports: u64,
+}
+
+/// The result of `getEpochInfo`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcEpochInfo {
+    /// The current epoch
+    pub epoch: u64,
+    /// The index of the current slot in the epoch
+    pub slot_index: u64,
+    /// The number of slots of the epoch
+    pub slots_in_epoch: u64,
+    /// The current slot
+    pub absolute_slot: u64,
+    /// The block height of the current slot, `0` when the database does not record it
+    pub block_height: u64,
+    /// The number of transactions processed, the databases do not record it so it is left out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_count: Option<u64>,
+}
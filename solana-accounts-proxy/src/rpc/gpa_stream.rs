@@ -0,0 +1,235 @@
+use crate::{AccountEncoder, Context, GetProgramAccountsRequest};
+use futures_util::{Stream, StreamExt};
+use hyper::{body::Sender, Body, Response};
+use jsonrpsee::{
+    core::{server::helpers::MethodResponse, RpcResult},
+    types::Id,
+};
+use serde_json::Value as JsonValue;
+
+/// Writes the response of a `getProgramAccounts` request to a chunked HTTP body
+/// while the accounts are received from the database.
+/// At most `buffer_size` bytes of encoded accounts are held in memory before they are
+/// written to the client, the database is not read faster than the client reads the response.
+pub struct GpaStream {
+    sender: Sender,
+    buffer: Vec<u8>,
+    buffer_size: usize,
+}
+
+impl GpaStream {
+    /// Run the query and stream the response. An error before the first account is encoded
+    /// is returned as a JSON-RPC error, an error after the response has started aborts the
+    /// body so the client never receives a truncated response that looks complete.
+    pub async fn respond(
+        id: Id<'static>,
+        request: GetProgramAccountsRequest,
+        buffer_size: usize,
+    ) -> Response<Body> {
+        let outcome = match crate::PgConnection::client().await {
+            Ok(pg_client) => request.query(pg_client).await,
+            Err(error) => Err(error.into()),
+        };
+
+        let mut rows = match outcome {
+            Ok(outcome) => outcome,
+            Err(error) => return crate::json_response(MethodResponse::error(id, error).result),
+        };
+
+        let mut encoder = AccountEncoder::new(request.encoding());
+
+        let first_batch = match encoder.next_accounts(&mut rows).await {
+            Ok(first_batch) => first_batch,
+            Err(error) => return crate::json_response(MethodResponse::error(id, error).result),
+        };
+
+        let context = rows.context.take();
+
+        // The next batches of accounts until every row has been received
+        let batches =
+            futures_util::stream::unfold((rows, encoder), |(mut rows, mut encoder)| async move {
+                match encoder.next_accounts(&mut rows).await {
+                    Ok(batch) if batch.is_empty() => Option::None,
+                    outcome => Some((outcome, (rows, encoder))),
+                }
+            });
+
+        crate::json_response(GpaStream::spawn(
+            id,
+            context,
+            first_batch,
+            batches,
+            buffer_size,
+        ))
+    }
+
+    /// Write the response to a body in the background, the body is aborted on the first error
+    fn spawn(
+        id: Id<'static>,
+        context: Option<Context>,
+        first_batch: Vec<JsonValue>,
+        batches: impl Stream<Item = RpcResult<Vec<JsonValue>>> + Send + 'static,
+        buffer_size: usize,
+    ) -> Body {
+        let (sender, body) = Body::channel();
+
+        tokio::spawn(async move {
+            let mut stream = GpaStream {
+                sender,
+                buffer: Vec::with_capacity(buffer_size),
+                buffer_size,
+            };
+
+            let batches = Box::pin(batches);
+
+            if let Err(error) = stream.write(id, context, first_batch, batches).await {
+                tracing::error!("Aborted the `getProgramAccounts` response: {}", error);
+
+                stream.sender.abort();
+            }
+        });
+
+        body
+    }
+
+    async fn write(
+        &mut self,
+        id: Id<'static>,
+        context: Option<Context>,
+        first_batch: Vec<JsonValue>,
+        mut batches: impl Stream<Item = RpcResult<Vec<JsonValue>>> + Unpin,
+    ) -> anyhow::Result<()> {
+        self.buffer
+            .extend_from_slice(br#"{"jsonrpc":"2.0","result":"#);
+
+        if let Some(context) = context.as_ref() {
+            self.buffer.extend_from_slice(br#"{"context":"#);
+            serde_json::to_writer(&mut self.buffer, &context.as_json_value())?;
+            self.buffer.extend_from_slice(br#","value":"#);
+        }
+
+        self.buffer.push(b'[');
+
+        let mut num_of_accounts = 0usize;
+        let mut batch = first_batch;
+
+        while !batch.is_empty() {
+            for account_info in batch {
+                if num_of_accounts > 0 {
+                    self.buffer.push(b',');
+                }
+
+                serde_json::to_writer(&mut self.buffer, &account_info)?;
+
+                num_of_accounts += 1;
+
+                if self.buffer.len() >= self.buffer_size {
+                    self.flush().await?;
+                }
+            }
+
+            batch = batches
+                .next()
+                .await
+                .transpose()
+                .map_err(|error| anyhow::anyhow!("{:?}", error))?
+                .unwrap_or_default();
+        }
+
+        self.buffer.push(b']');
+
+        if context.is_some() {
+            self.buffer.push(b'}');
+        }
+
+        self.buffer.extend_from_slice(br#","id":"#);
+        serde_json::to_writer(&mut self.buffer, &id)?;
+        self.buffer.push(b'}');
+
+        self.flush().await?;
+
+        Ok(())
+    }
+
+    /// Waits until the client has read the previous chunk
+    async fn flush(&mut self) -> anyhow::Result<()> {
+        let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(self.buffer_size));
+
+        self.sender.send_data(chunk.into()).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProxyError;
+    use hyper::body::HttpBody;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn chunks_and_abort() -> anyhow::Result<()> {
+        let accounts = (0..40)
+            .map(|index| json!({ "pubkey": index.to_string(), "account": null }))
+            .collect::<Vec<JsonValue>>();
+
+        let batches = accounts[10..]
+            .chunks(10)
+            .map(|batch| Ok(batch.to_vec()))
+            .collect::<Vec<RpcResult<Vec<JsonValue>>>>();
+
+        // A large response is written in several chunks of about `buffer_size` bytes
+        let mut body = GpaStream::spawn(
+            Id::Number(1),
+            Option::None,
+            accounts[..10].to_vec(),
+            futures_util::stream::iter(batches),
+            64,
+        );
+
+        let mut chunks = Vec::new();
+        while let Some(chunk) = body.data().await {
+            chunks.push(chunk?);
+        }
+        assert!(chunks.len() > 10);
+        assert!(chunks[..chunks.len() - 1]
+            .iter()
+            .all(|chunk| chunk.len() >= 64));
+
+        let response: JsonValue = serde_json::from_slice(&chunks.concat())?;
+        assert_eq!(
+            response,
+            json!({ "jsonrpc": "2.0", "result": accounts, "id": 1 })
+        );
+
+        // An error after the response has started aborts the body
+        let batches: Vec<RpcResult<Vec<JsonValue>>> = vec![
+            Ok(accounts[10..20].to_vec()),
+            Err(ProxyError::Internal("failed".to_owned()).into()),
+            Ok(accounts[20..].to_vec()),
+        ];
+
+        let mut body = GpaStream::spawn(
+            Id::Number(2),
+            Option::None,
+            accounts[..10].to_vec(),
+            futures_util::stream::iter(batches),
+            64,
+        );
+
+        let mut received = Vec::new();
+        let aborted = loop {
+            match body.data().await {
+                Some(Ok(chunk)) => received.extend_from_slice(&chunk),
+                Some(Err(_)) => break true,
+                None => break false,
+            }
+        };
+        assert!(aborted);
+        assert!(!received.is_empty());
+        assert!(serde_json::from_slice::<JsonValue>(&received).is_err());
+
+        Ok(())
+    }
+}
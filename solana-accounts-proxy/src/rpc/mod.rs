@@ -4,8 +4,17 @@ pub use rpc_traits::*;
 mod rpc_impl;
 pub use rpc_impl::*;
 
+mod gpa_stream;
+pub use gpa_stream::*;
+
 mod common_rpc_types;
 pub use common_rpc_types::*;
 
 mod account_info;
 pub use account_info::*;
+
+mod account_encoder;
+pub use account_encoder::*;
+
+mod supply_cache;
+pub use supply_cache::*;
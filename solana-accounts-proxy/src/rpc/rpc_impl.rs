@@ -1,10 +1,15 @@
 use crate::{
-    Commitment, Context, CurrentSlot, DataSlice, Encoding, Filter, GetAccountInfoQuery,
-    GetProgramAccounts, GetProgramAccountsRow, Parameters, PubKey, RpcProxyServer, WithContext,
+    AccountEncoder, BlockHeight, Commitment, Context, CurrentSlot, DataSlice, Encoding,
+    EpochSchedule, Filter, GetAccountInfoQuery, GetBalanceQuery, GetLargestAccountsQuery,
+    GetMultipleAccountsQuery, GetProgramAccounts, GetProgramAccountsRow, GetSupplyQuery,
+    GetTokenLargestAccountsQuery, Mint, Parameters, PgClient, ProxyError, PubKey, Rent,
+    RpcAccountBalance, RpcEpochInfo, RpcProxyServer, RpcSupply, RpcVersionInfo, TokenAccount,
+    TokenAccountBalance, TokenAccountsFilter, UiEpochSchedule, UiTokenAmount, WithContext,
 };
 use async_trait::async_trait;
-use jsonrpsee::{core::Error as JsonrpseeError, core::RpcResult};
-use serde_json::{Map, Value as JsonValue};
+use jsonrpsee::core::RpcResult;
+use serde_json::Value as JsonValue;
+use tokio_postgres::Client;
 
 // Structure that will implement the `MyRpcServer` trait.
 // It can have fields, if required, as long as it's still `Send + Sync + 'static`.
@@ -27,25 +32,154 @@ impl RpcProxyServer for RpcProxyImpl {
         base58_public_key: String,
         parameters: Option<Parameters>,
     ) -> RpcResult<serde_json::Value> {
-        let _public_key = PubKey::parse(&base58_public_key)?;
+        let request = GetProgramAccountsRequest::parse(base58_public_key, parameters)?;
 
-        Ok(get_program_accounts(&base58_public_key, parameters)
-            .await?
-            .into())
+        get_program_accounts(&request, crate::PgConnection::client().await?).await
     }
 
     async fn get_multiple_accounts(
         &self,
         base58_public_keys: Vec<String>,
-        _parameters: Option<Parameters>,
-    ) -> RpcResult<String> {
+        parameters: Option<Parameters>,
+    ) -> RpcResult<JsonValue> {
+        let max_multiple_accounts = crate::USER_CONFIG.rpc_config().max_multiple_accounts();
+
+        if base58_public_keys.len() > max_multiple_accounts {
+            return Err(ProxyError::InvalidParams(format!(
+                "Too many inputs provided; max {}",
+                max_multiple_accounts
+            ))
+            .into());
+        }
+
         let public_keys: RpcResult<Vec<PubKey>> = base58_public_keys
             .iter()
             .map(|base58_public_key| PubKey::parse(base58_public_key))
             .collect();
         let _public_keys = public_keys?;
 
-        Ok("getMultipleAccounts".into())
+        get_multiple_accounts(&base58_public_keys, parameters.as_ref()).await
+    }
+
+    async fn get_balance(
+        &self,
+        base58_public_key: String,
+        parameters: Option<Parameters>,
+    ) -> RpcResult<JsonValue> {
+        PubKey::parse(&base58_public_key)?;
+
+        get_balance(&base58_public_key, parameters.as_ref()).await
+    }
+
+    async fn get_token_accounts_by_owner(
+        &self,
+        base58_public_key: String,
+        token_accounts_filter: TokenAccountsFilter,
+        parameters: Option<Parameters>,
+    ) -> RpcResult<JsonValue> {
+        let pg_client = crate::PgConnection::client().await?;
+
+        let request = GetProgramAccountsRequest::token_accounts(
+            &pg_client,
+            TokenAccountsBy::Owner,
+            &base58_public_key,
+            token_accounts_filter,
+            parameters,
+        )
+        .await?;
+
+        get_program_accounts(&request, pg_client).await
+    }
+
+    async fn get_token_accounts_by_delegate(
+        &self,
+        base58_public_key: String,
+        token_accounts_filter: TokenAccountsFilter,
+        parameters: Option<Parameters>,
+    ) -> RpcResult<JsonValue> {
+        let pg_client = crate::PgConnection::client().await?;
+
+        let request = GetProgramAccountsRequest::token_accounts(
+            &pg_client,
+            TokenAccountsBy::Delegate,
+            &base58_public_key,
+            token_accounts_filter,
+            parameters,
+        )
+        .await?;
+
+        get_program_accounts(&request, pg_client).await
+    }
+
+    async fn get_token_account_balance(
+        &self,
+        base58_public_key: String,
+        parameters: Option<Parameters>,
+    ) -> RpcResult<JsonValue> {
+        PubKey::parse(&base58_public_key)?;
+
+        get_token_account_balance(&base58_public_key, parameters.as_ref()).await
+    }
+
+    async fn get_token_supply(
+        &self,
+        base58_mint: String,
+        parameters: Option<Parameters>,
+    ) -> RpcResult<JsonValue> {
+        PubKey::parse(&base58_mint)?;
+
+        get_token_supply(&base58_mint, parameters.as_ref()).await
+    }
+
+    async fn get_token_largest_accounts(
+        &self,
+        base58_mint: String,
+        parameters: Option<Parameters>,
+    ) -> RpcResult<JsonValue> {
+        let mint = PubKey::parse(&base58_mint)?;
+
+        get_token_largest_accounts(&base58_mint, &mint, parameters.as_ref()).await
+    }
+
+    async fn get_slot(&self, parameters: Option<Parameters>) -> RpcResult<u64> {
+        get_slot(parameters.as_ref()).await
+    }
+
+    async fn get_health(&self) -> RpcResult<String> {
+        get_health()
+    }
+
+    async fn get_version(&self) -> RpcResult<RpcVersionInfo> {
+        Ok(get_version())
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(
+        &self,
+        data_len: usize,
+        parameters: Option<Parameters>,
+    ) -> RpcResult<u64> {
+        get_minimum_balance_for_rent_exemption(data_len, parameters.as_ref()).await
+    }
+
+    async fn get_epoch_info(&self, parameters: Option<Parameters>) -> RpcResult<RpcEpochInfo> {
+        get_epoch_info(parameters.as_ref()).await
+    }
+
+    async fn get_supply(&self, parameters: Option<Parameters>) -> RpcResult<JsonValue> {
+        get_supply(parameters.as_ref()).await
+    }
+
+    async fn get_largest_accounts(&self, parameters: Option<Parameters>) -> RpcResult<JsonValue> {
+        get_largest_accounts(parameters.as_ref()).await
+    }
+
+    async fn get_epoch_schedule(&self) -> RpcResult<UiEpochSchedule> {
+        let pg_client = crate::PgConnection::client().await?;
+
+        let (_, epoch_schedule) =
+            load_epoch_schedule(&pg_client, Commitment::Finalized, None).await?;
+
+        Ok(epoch_schedule.into())
     }
 }
 
@@ -57,88 +191,689 @@ pub async fn get_account_info(
     let commitment = Commitment::get_commitment(parameters);
     let encoding = Encoding::get_encoding(parameters);
 
+    let mut min_context_slot: Option<u64> = Option::None;
+    let mut data_slice: Option<DataSlice> = Option::None;
+
+    if let Some(has_parameters) = parameters {
+        min_context_slot = has_parameters.min_context_slot;
+
+        data_slice = has_parameters.data_slice;
+    }
+
+    encoding.check_data_slice(data_slice.as_ref())?;
+
     let mut ga_query = GetAccountInfoQuery::new();
     ga_query
         .add_public_key(base58_public_key)
-        .add_commitment(commitment);
+        .add_commitment(commitment)
+        .add_min_context_slot(min_context_slot)
+        .add_data_slice(data_slice);
 
-    let row = ga_query.query().await?;
+    let pg_client = crate::PgConnection::client().await?;
 
-    let mut query_result = Map::new();
+    let row = ga_query.query(&pg_client).await?;
 
-    row.context.insert_json_value(&mut query_result);
+    let mut encoder = AccountEncoder::new(encoding);
 
-    row.value.as_json_value(encoding, &mut query_result)?;
+    let account = match row.value.as_ref() {
+        Some(account) => {
+            encoder
+                .load_additional_data([account], row.context.slot, &pg_client)
+                .await?;
 
-    Ok(Some(query_result.into()))
+            encoder.account(base58_public_key, account)?
+        }
+        None => JsonValue::Null,
+    };
+
+    let with_context = WithContext::<JsonValue>::new(row.context).as_json_value(account);
+
+    Ok(Some(with_context.into()))
 }
 
-/// Handler the for `getProgramAccounts`
-pub async fn get_program_accounts(
-    base58_public_key: &str,
-    parameters: Option<Parameters>,
-) -> RpcResult<Option<JsonValue>> {
-    tracing::debug!("PARSED PUBLIC KEY: {:?}", base58_public_key);
-    tracing::debug!("PARSED PARAMETERS: {:?}", &parameters);
+/// The handler for `getMultipleAccounts` method
+pub async fn get_multiple_accounts(
+    base58_public_keys: &[String],
+    parameters: Option<&Parameters>,
+) -> RpcResult<JsonValue> {
+    let encoding = Encoding::get_encoding(parameters);
 
     let mut commitment = Commitment::Finalized;
     let mut min_context_slot: Option<u64> = Option::None;
-    let encoding = Encoding::get_encoding(parameters.as_ref());
-
-    let mut current_slot: Option<Context> = Option::None;
-    let mut filters: Option<Vec<Filter>> = Option::None;
     let mut data_slice: Option<DataSlice> = Option::None;
 
     if let Some(has_parameters) = parameters {
-        if let Some(has_with_context) = has_parameters.with_context.as_ref() {
-            if *has_with_context {
-                current_slot = Some(
-                    CurrentSlot::new()
-                        .add_commitment(commitment)
-                        .query()
-                        .await?,
-                );
+        if let Some(req_commitment) = has_parameters.commitment {
+            commitment = req_commitment;
+        }
+
+        min_context_slot = has_parameters.min_context_slot;
+
+        data_slice = has_parameters.data_slice;
+    }
+
+    encoding.check_data_slice(data_slice.as_ref())?;
+
+    let pg_client = crate::PgConnection::client().await?;
+
+    let rows = GetMultipleAccountsQuery::new()
+        .add_public_keys(base58_public_keys)
+        .add_commitment(commitment)
+        .add_data_slice(data_slice)
+        .query(&pg_client)
+        .await?;
+
+    rows.context.check_min_context_slot(min_context_slot)?;
+
+    let mut encoder = AccountEncoder::new(encoding);
+    encoder
+        .load_additional_data(rows.value.values(), rows.context.slot, &pg_client)
+        .await?;
+
+    let accounts = rows.in_order(base58_public_keys, &encoder)?;
+
+    let with_context =
+        WithContext::<Vec<JsonValue>>::new(rows.context).as_json_value(accounts.into());
+
+    Ok(with_context.into())
+}
+
+/// The handler for `getBalance` method, only the lamports of the account are read
+pub async fn get_balance(
+    base58_public_key: &str,
+    parameters: Option<&Parameters>,
+) -> RpcResult<JsonValue> {
+    let commitment = Commitment::get_commitment(parameters);
+    let min_context_slot = parameters.and_then(|parameters| parameters.min_context_slot);
+
+    let pg_client = crate::PgConnection::client().await?;
+
+    let row = GetBalanceQuery::new()
+        .add_public_key(base58_public_key)
+        .add_commitment(commitment)
+        .add_min_context_slot(min_context_slot)
+        .query(&pg_client)
+        .await?;
+
+    let with_context = WithContext::<u64>::new(row.context).as_json_value(row.value.into());
+
+    Ok(with_context.into())
+}
+
+/// The handler for `getTokenAccountBalance` method, the amount of the token account
+/// with the decimals of its mint read at the same slot
+pub async fn get_token_account_balance(
+    base58_public_key: &str,
+    parameters: Option<&Parameters>,
+) -> RpcResult<JsonValue> {
+    let commitment = Commitment::get_commitment(parameters);
+    let min_context_slot = parameters.and_then(|parameters| parameters.min_context_slot);
+
+    let mut ga_query = GetAccountInfoQuery::new();
+    ga_query
+        .add_public_key(base58_public_key)
+        .add_commitment(commitment)
+        .add_min_context_slot(min_context_slot);
+
+    let pg_client = crate::PgConnection::client().await?;
+
+    let row = ga_query.query(&pg_client).await?;
+
+    let account = row.value.ok_or_else(|| {
+        ProxyError::InvalidParams("Invalid param: could not find account".to_owned())
+    })?;
+
+    let token_account = match crate::is_token_program(&account.owner) {
+        true => TokenAccount::unpack(&account.data).ok(),
+        false => Option::None,
+    };
+    let (token_account, _) = token_account.ok_or_else(|| {
+        ProxyError::InvalidParams("Invalid param: not a Token account".to_owned())
+    })?;
+
+    let token_mint = load_mint(
+        &pg_client,
+        &token_account.mint,
+        commitment,
+        Some(row.context.slot),
+        MINT_NOT_FOUND,
+    )
+    .await?;
+
+    let amount = UiTokenAmount::new(token_account.amount, token_mint.mint.decimals);
+
+    let with_context =
+        WithContext::<JsonValue>::new(row.context).as_json_value(serde_json::to_value(amount)?);
+
+    Ok(with_context.into())
+}
+
+/// The handler for `getTokenSupply` method
+pub async fn get_token_supply(
+    base58_mint: &str,
+    parameters: Option<&Parameters>,
+) -> RpcResult<JsonValue> {
+    let commitment = Commitment::get_commitment(parameters);
+    let min_context_slot = parameters.and_then(|parameters| parameters.min_context_slot);
+
+    let pg_client = crate::PgConnection::client().await?;
+
+    let token_mint = load_mint(
+        &pg_client,
+        base58_mint,
+        commitment,
+        Option::None,
+        ACCOUNT_NOT_FOUND,
+    )
+    .await?;
+    token_mint
+        .context
+        .check_min_context_slot(min_context_slot)?;
+
+    let supply = UiTokenAmount::new(token_mint.mint.supply, token_mint.mint.decimals);
+
+    let with_context = WithContext::<JsonValue>::new(token_mint.context)
+        .as_json_value(serde_json::to_value(supply)?);
+
+    Ok(with_context.into())
+}
+
+/// The handler for `getTokenLargestAccounts` method, the token accounts are read
+/// at the context slot of the mint
+pub async fn get_token_largest_accounts(
+    base58_mint: &str,
+    mint: &PubKey,
+    parameters: Option<&Parameters>,
+) -> RpcResult<JsonValue> {
+    let commitment = Commitment::get_commitment(parameters);
+    let min_context_slot = parameters.and_then(|parameters| parameters.min_context_slot);
+
+    let pg_client = crate::PgConnection::client().await?;
+
+    let token_mint = load_mint(
+        &pg_client,
+        base58_mint,
+        commitment,
+        Option::None,
+        MINT_NOT_FOUND,
+    )
+    .await?;
+    token_mint
+        .context
+        .check_min_context_slot(min_context_slot)?;
+
+    let rows = GetTokenLargestAccountsQuery::new()
+        .add_program_id(&token_mint.program_id)
+        .add_mint(&mint.0)
+        .add_context_slot(token_mint.context.slot)
+        .query(&pg_client)
+        .await?;
+
+    let balances = rows
+        .into_iter()
+        .map(|row| TokenAccountBalance {
+            address: row.address,
+            amount: UiTokenAmount::new(row.amount, token_mint.mint.decimals),
+        })
+        .collect::<Vec<TokenAccountBalance>>();
+
+    let with_context = WithContext::<JsonValue>::new(token_mint.context)
+        .as_json_value(serde_json::to_value(balances)?);
+
+    Ok(with_context.into())
+}
+
+/// The error of a mint that does not exist
+const MINT_NOT_FOUND: &str = "Invalid param: could not find mint";
+/// The error of a mint that does not exist for `getTokenSupply`, like Solana
+const ACCOUNT_NOT_FOUND: &str = "Invalid param: could not find account";
+
+/// A mint owned by the SPL Token or the Token-2022 program
+struct TokenMint {
+    context: Context,
+    program_id: String,
+    mint: Mint,
+}
+
+/// Read a mint at the commitment level, or at `context_slot`, with the same errors as a Solana RPC node
+async fn load_mint(
+    pg_client: &Client,
+    base58_mint: &str,
+    commitment: Commitment,
+    context_slot: Option<u64>,
+    not_found: &str,
+) -> RpcResult<TokenMint> {
+    let mut ga_query = GetAccountInfoQuery::new();
+    ga_query
+        .add_public_key(base58_mint)
+        .add_commitment(commitment);
+
+    if let Some(context_slot) = context_slot {
+        ga_query.add_context_slot(context_slot);
+    }
+
+    let row = ga_query.query(pg_client).await?;
+
+    let account = row
+        .value
+        .ok_or_else(|| ProxyError::InvalidParams(not_found.to_owned()))?;
+
+    if !crate::is_token_program(&account.owner) {
+        return Err(ProxyError::InvalidParams("Invalid param: not a Token mint".to_owned()).into());
+    }
+
+    let (mint, _) = Mint::unpack(&account.data).map_err(|_| {
+        ProxyError::InvalidParams("Invalid param: Token mint could not be unpacked".to_owned())
+    })?;
+
+    Ok(TokenMint {
+        context: row.context,
+        program_id: account.owner,
+        mint,
+    })
+}
+
+/// The handler for `getSlot` method, the highest slot of the `slots` table at the commitment level
+pub async fn get_slot(parameters: Option<&Parameters>) -> RpcResult<u64> {
+    let commitment = Commitment::get_commitment(parameters);
+    let min_context_slot = parameters.and_then(|parameters| parameters.min_context_slot);
+
+    let pg_client = crate::PgConnection::client().await?;
+
+    let context = CurrentSlot::new()
+        .add_commitment(commitment)
+        .query(&pg_client)
+        .await?;
+    context.check_min_context_slot(min_context_slot)?;
+
+    Ok(context.slot)
+}
+
+/// The handler for `getHealth` method, see [crate::PgRouter::health]
+pub fn get_health() -> RpcResult<String> {
+    let pg_router = crate::PG_ROUTER.get().ok_or_else(|| {
+        ProxyError::Internal("The pool of connections to the database does not exist.".to_owned())
+    })?;
+
+    pg_router.health()?;
+
+    Ok("ok".to_owned())
+}
+
+/// The handler for `getVersion` method
+pub fn get_version() -> RpcVersionInfo {
+    let rpc_config = crate::USER_CONFIG.rpc_config();
+
+    RpcVersionInfo {
+        solana_core: rpc_config.solana_core_version().to_owned(),
+        feature_set: rpc_config.feature_set(),
+        solana_accounts_proxy: env!("CARGO_PKG_VERSION").to_owned(),
+    }
+}
+
+/// The handler for `getSupply` method. The sums are computed at the finalized slot whatever the
+/// commitment level, and cached in [crate::SUPPLY_CACHE] which serves the last sums until they
+/// are refreshed.
+pub async fn get_supply(parameters: Option<&Parameters>) -> RpcResult<JsonValue> {
+    let exclude_non_circulating_accounts_list = parameters
+        .and_then(|parameters| parameters.exclude_non_circulating_accounts_list)
+        .unwrap_or_default();
+
+    let pg_client = crate::PgConnection::client().await?;
+
+    let finalized = CurrentSlot::new()
+        .add_commitment(Commitment::Finalized)
+        .query(&pg_client)
+        .await?;
+
+    let (slot, mut supply) = crate::SUPPLY_CACHE
+        .supply
+        .get((), finalized.slot, move |slot| async move {
+            let non_circulating_accounts =
+                crate::USER_CONFIG.rpc_config().non_circulating_accounts();
+
+            let row = GetSupplyQuery::new()
+                .add_context_slot(slot)
+                .add_non_circulating_accounts(non_circulating_accounts)
+                .query(&pg_client)
+                .await?;
+
+            Ok(RpcSupply {
+                total: row.total,
+                circulating: row.total.saturating_sub(row.non_circulating),
+                non_circulating: row.non_circulating,
+                non_circulating_accounts: non_circulating_accounts.to_vec(),
+            })
+        })
+        .await?;
+
+    if exclude_non_circulating_accounts_list {
+        supply.non_circulating_accounts.clear();
+    }
+
+    let with_context = WithContext::<JsonValue>::new(Context::new(slot))
+        .as_json_value(serde_json::to_value(supply)?);
+
+    Ok(with_context.into())
+}
+
+/// The handler for `getLargestAccounts` method. The accounts are read at the finalized slot
+/// whatever the commitment level, and cached in [crate::SUPPLY_CACHE] like [get_supply].
+pub async fn get_largest_accounts(parameters: Option<&Parameters>) -> RpcResult<JsonValue> {
+    let filter = parameters.and_then(|parameters| parameters.filter);
+
+    let pg_client = crate::PgConnection::client().await?;
+
+    let finalized = CurrentSlot::new()
+        .add_commitment(Commitment::Finalized)
+        .query(&pg_client)
+        .await?;
+
+    let (slot, accounts) = crate::SUPPLY_CACHE
+        .largest_accounts
+        .get(filter, finalized.slot, move |slot| async move {
+            let rows = GetLargestAccountsQuery::new()
+                .add_context_slot(slot)
+                .add_non_circulating_accounts(
+                    crate::USER_CONFIG.rpc_config().non_circulating_accounts(),
+                )
+                .add_filter(filter)
+                .query(&pg_client)
+                .await?;
+
+            Ok(rows
+                .into_iter()
+                .map(|row| RpcAccountBalance {
+                    address: row.address,
+                    lamports: row.lamports,
+                })
+                .collect::<Vec<RpcAccountBalance>>())
+        })
+        .await?;
+
+    let with_context = WithContext::<JsonValue>::new(Context::new(slot))
+        .as_json_value(serde_json::to_value(accounts)?);
+
+    Ok(with_context.into())
+}
+
+/// The handler for `getMinimumBalanceForRentExemption` method. The `Rent` sysvar is read at the
+/// commitment level, if it is not in the database the rent of the `[rpc]` section is used.
+pub async fn get_minimum_balance_for_rent_exemption(
+    data_len: usize,
+    parameters: Option<&Parameters>,
+) -> RpcResult<u64> {
+    let mut ga_query = GetAccountInfoQuery::new();
+    ga_query
+        .add_public_key(crate::SYSVAR_RENT)
+        .add_commitment(Commitment::get_commitment(parameters));
+
+    let pg_client = crate::PgConnection::client().await?;
+
+    let rent = match ga_query.query(&pg_client).await?.value {
+        Some(account) => Rent::unpack(&account.data).map_err(|_| {
+            ProxyError::Internal("The `Rent` sysvar could not be unpacked.".to_owned())
+        })?,
+        None => crate::USER_CONFIG.rpc_config().fallback_rent(),
+    };
+
+    Ok(rent.minimum_balance(data_len))
+}
+
+/// The handler for `getEpochInfo` method, the epoch of the highest slot at the commitment level.
+/// The block height is read from the `block` table of the Geyser plugin, see [BlockHeight].
+/// The databases do not record the transaction count so it is left out.
+pub async fn get_epoch_info(parameters: Option<&Parameters>) -> RpcResult<RpcEpochInfo> {
+    let commitment = Commitment::get_commitment(parameters);
+    let min_context_slot = parameters.and_then(|parameters| parameters.min_context_slot);
+
+    let pg_client = crate::PgConnection::client().await?;
+
+    let (context, epoch_schedule) =
+        load_epoch_schedule(&pg_client, commitment, min_context_slot).await?;
+
+    let (epoch, slot_index) = epoch_schedule.epoch_and_slot_index(context.slot);
+
+    let block_height = BlockHeight::new(context.slot).query(&pg_client).await?;
+
+    Ok(RpcEpochInfo {
+        epoch,
+        slot_index,
+        slots_in_epoch: epoch_schedule.slots_in_epoch(epoch),
+        absolute_slot: context.slot,
+        block_height: block_height.unwrap_or_default(),
+        transaction_count: None,
+    })
+}
+
+/// Load the `EpochSchedule` sysvar, the context slot is the highest slot at the commitment level
+async fn load_epoch_schedule(
+    pg_client: &Client,
+    commitment: Commitment,
+    min_context_slot: Option<u64>,
+) -> RpcResult<(Context, EpochSchedule)> {
+    let mut ga_query = GetAccountInfoQuery::new();
+    ga_query
+        .add_public_key(crate::SYSVAR_EPOCH_SCHEDULE)
+        .add_commitment(commitment)
+        .add_min_context_slot(min_context_slot);
+
+    let row = ga_query.query(pg_client).await?;
+
+    let account = row.value.ok_or_else(|| {
+        ProxyError::Internal("The `EpochSchedule` sysvar is not in the database.".to_owned())
+    })?;
+
+    let epoch_schedule = EpochSchedule::unpack(&account.data).map_err(|_| {
+        ProxyError::Internal("The `EpochSchedule` sysvar could not be unpacked.".to_owned())
+    })?;
+
+    Ok((row.context, epoch_schedule))
+}
+
+/// The validated parameters of a `getProgramAccounts` request
+#[derive(Debug)]
+pub struct GetProgramAccountsRequest {
+    base58_public_key: String,
+    commitment: Commitment,
+    encoding: Encoding,
+    min_context_slot: Option<u64>,
+    filters: Option<Vec<Filter>>,
+    data_slice: Option<DataSlice>,
+    with_context: bool,
+}
+
+impl GetProgramAccountsRequest {
+    /// Validate the public key and the parameters of the request
+    pub fn parse(base58_public_key: String, parameters: Option<Parameters>) -> RpcResult<Self> {
+        PubKey::parse(&base58_public_key)?;
+
+        if crate::USER_CONFIG
+            .rpc_config()
+            .is_excluded_key(&base58_public_key)
+        {
+            return Err(ProxyError::KeyExcludedFromSecondaryIndex(base58_public_key).into());
+        }
+
+        let mut request = GetProgramAccountsRequest {
+            base58_public_key,
+            commitment: Commitment::get_commitment(parameters.as_ref()),
+            encoding: Encoding::get_encoding(parameters.as_ref()),
+            min_context_slot: Option::None,
+            filters: Option::None,
+            data_slice: Option::None,
+            with_context: false,
+        };
+
+        if let Some(has_parameters) = parameters {
+            if let Some(has_filter) = has_parameters.filters {
+                Filter::verify(&has_filter)?;
+
+                request.filters.replace(has_filter);
             }
+
+            request.min_context_slot = has_parameters.min_context_slot;
+            request.data_slice = has_parameters.data_slice;
+            request.with_context = has_parameters.with_context.unwrap_or_default();
         }
-        if let Some(has_filter) = has_parameters.filters {
-            // Check if the number of `Filters` is greater than 4
-            if has_filter.len() > 4 {
-                return Err(JsonrpseeError::Custom(
-                    "Too many filters provided; max 4".to_owned(),
-                ));
+
+        request
+            .encoding
+            .check_data_slice(request.data_slice.as_ref())?;
+
+        Ok(request)
+    }
+
+    /// Build the `getProgramAccounts` request of `getTokenAccountsByOwner` or
+    /// `getTokenAccountsByDelegate`, the token accounts are selected with the same filters
+    /// as a Solana RPC node and the response always has a context.
+    /// The token program of a `mint` selector is the owner of the mint at the commitment level,
+    /// read with `pg_client` which then runs the query.
+    pub async fn token_accounts(
+        pg_client: &Client,
+        token_accounts_by: TokenAccountsBy,
+        base58_public_key: &str,
+        token_accounts_filter: TokenAccountsFilter,
+        parameters: Option<Parameters>,
+    ) -> RpcResult<Self> {
+        let public_key = PubKey::parse(base58_public_key)?;
+
+        let commitment = Commitment::get_commitment(parameters.as_ref());
+
+        let (program_id, mint) = match token_accounts_filter {
+            TokenAccountsFilter::ProgramId(program_id) => {
+                PubKey::parse(&program_id)?;
+
+                if !crate::is_token_program(&program_id) {
+                    return Err(ProxyError::InvalidParams(
+                        "Invalid param: unrecognized Token program id".to_owned(),
+                    )
+                    .into());
+                }
+
+                (program_id, Option::None)
             }
+            TokenAccountsFilter::Mint(mint) => {
+                let mint_public_key = PubKey::parse(&mint)?;
 
-            filters.replace(has_filter);
+                let token_mint =
+                    load_mint(pg_client, &mint, commitment, Option::None, MINT_NOT_FOUND).await?;
+
+                (token_mint.program_id, Some(mint_public_key))
+            }
+        };
+
+        let mut filters = match token_accounts_by {
+            TokenAccountsBy::Owner => vec![Filter::memcmp_bytes(
+                crate::TOKEN_ACCOUNT_OWNER_OFFSET,
+                public_key.0.to_vec(),
+            )],
+            TokenAccountsBy::Delegate => vec![
+                // The `COption` tag of a delegate that is set
+                Filter::memcmp_bytes(crate::TOKEN_ACCOUNT_DELEGATE_OFFSET, vec![1, 0, 0, 0]),
+                Filter::memcmp_bytes(
+                    crate::TOKEN_ACCOUNT_DELEGATE_OFFSET + 4,
+                    public_key.0.to_vec(),
+                ),
+            ],
+        };
+
+        if let Some(mint) = mint {
+            filters.push(Filter::memcmp_bytes(
+                crate::TOKEN_ACCOUNT_MINT_OFFSET,
+                mint.0.to_vec(),
+            ));
         }
 
-        if let Some(req_commitment) = has_parameters.commitment {
-            commitment = req_commitment;
+        filters.push(Filter::token_accounts(&program_id));
+
+        let mut request = GetProgramAccountsRequest {
+            base58_public_key: program_id,
+            commitment,
+            encoding: Encoding::get_encoding(parameters.as_ref()),
+            min_context_slot: Option::None,
+            filters: Some(filters),
+            data_slice: Option::None,
+            with_context: true,
+        };
+
+        if let Some(has_parameters) = parameters {
+            request.min_context_slot = has_parameters.min_context_slot;
+            request.data_slice = has_parameters.data_slice;
         }
 
-        min_context_slot = has_parameters.min_context_slot;
+        request
+            .encoding
+            .check_data_slice(request.data_slice.as_ref())?;
 
-        data_slice = has_parameters.data_slice
+        Ok(request)
     }
 
-    let gpa = GetProgramAccounts::new()
-        .add_public_key(base58_public_key)
-        .add_commitment(commitment.queryable())
-        .add_min_context_slot(min_context_slot)
-        .add_filters(filters)
-        .add_data_slice(data_slice);
+    /// The encoding of the account data
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// The commitment level of the request
+    pub fn commitment(&self) -> Commitment {
+        self.commitment
+    }
+
+    /// The minimum slot the request can be evaluated at
+    pub fn min_context_slot(&self) -> Option<u64> {
+        self.min_context_slot
+    }
+
+    /// Start the query on `pg_client`, see [GetProgramAccounts::load_data]
+    pub async fn query(&self, pg_client: PgClient) -> RpcResult<GetProgramAccountsRow> {
+        let rows = GetProgramAccounts::new()
+            .add_public_key(&self.base58_public_key)
+            .add_commitment(self.commitment)
+            .add_encoding(self.encoding)
+            .add_min_context_slot(self.min_context_slot)
+            .add_filters(self.filters.as_deref().unwrap_or_default())
+            .add_data_slice(self.data_slice)
+            .add_with_context(self.with_context)
+            .load_data(pg_client)
+            .await?;
 
-    let rows = gpa.load_data().await?;
+        Ok(rows)
+    }
+}
+
+/// The key of the token accounts selected by [GetProgramAccountsRequest::token_accounts]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenAccountsBy {
+    /// The owner of the token accounts, `getTokenAccountsByOwner`
+    Owner,
+    /// The delegate of the token accounts, `getTokenAccountsByDelegate`
+    Delegate,
+}
 
-    let outcome = GetProgramAccountsRow::from_row(rows, encoding)?;
+/// Handler the for `getProgramAccounts`, buffers every account in memory.
+/// A single `getProgramAccounts` request is streamed by the HTTP server instead,
+/// this handler only serves the requests in a batch.
+pub async fn get_program_accounts(
+    request: &GetProgramAccountsRequest,
+    pg_client: PgClient,
+) -> RpcResult<JsonValue> {
+    let mut rows = request.query(pg_client).await?;
+    let mut encoder = AccountEncoder::new(request.encoding);
+
+    let mut accounts = Vec::<JsonValue>::new();
+    loop {
+        let batch = encoder.next_accounts(&mut rows).await?;
+        if batch.is_empty() {
+            break;
+        }
 
-    if outcome.is_empty() {
-        Ok(Option::None)
-    } else if let Some(context) = current_slot {
-        let with_context =
-            WithContext::<Vec<JsonValue>>::new(context).as_json_value(outcome.into());
+        accounts.extend(batch);
+    }
 
-        Ok(Some(with_context.into()))
-    } else {
-        Ok(Some(outcome.into()))
+    match rows.context.take() {
+        Some(context) => Ok(WithContext::<Vec<JsonValue>>::new(context)
+            .as_json_value(accounts.into())
+            .into()),
+        None => Ok(accounts.into()),
     }
 }
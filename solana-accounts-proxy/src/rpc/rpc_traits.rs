@@ -1,4 +1,4 @@
-use crate::Parameters;
+use crate::{Parameters, RpcEpochInfo, RpcVersionInfo, TokenAccountsFilter, UiEpochSchedule};
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use serde_json::Value as JsonValue;
 
@@ -26,5 +26,94 @@ pub trait RpcProxy {
         &self,
         public_keys: Vec<String>,
         parameters: Option<Parameters>,
-    ) -> RpcResult<String>;
+    ) -> RpcResult<JsonValue>;
+
+    /// Processes the `getBalance` method
+    #[method(name = "getBalance", aliases = ["getBalance"])]
+    async fn get_balance(
+        &self,
+        public_key: String,
+        parameters: Option<Parameters>,
+    ) -> RpcResult<JsonValue>;
+
+    /// Processes the `getTokenAccountsByOwner` method
+    #[method(name = "getTokenAccountsByOwner", aliases = ["getTokenAccountsByOwner"])]
+    async fn get_token_accounts_by_owner(
+        &self,
+        public_key: String,
+        token_accounts_filter: TokenAccountsFilter,
+        parameters: Option<Parameters>,
+    ) -> RpcResult<JsonValue>;
+
+    /// Processes the `getTokenAccountsByDelegate` method
+    #[method(name = "getTokenAccountsByDelegate", aliases = ["getTokenAccountsByDelegate"])]
+    async fn get_token_accounts_by_delegate(
+        &self,
+        public_key: String,
+        token_accounts_filter: TokenAccountsFilter,
+        parameters: Option<Parameters>,
+    ) -> RpcResult<JsonValue>;
+
+    /// Processes the `getTokenAccountBalance` method
+    #[method(name = "getTokenAccountBalance", aliases = ["getTokenAccountBalance"])]
+    async fn get_token_account_balance(
+        &self,
+        public_key: String,
+        parameters: Option<Parameters>,
+    ) -> RpcResult<JsonValue>;
+
+    /// Processes the `getTokenSupply` method
+    #[method(name = "getTokenSupply", aliases = ["getTokenSupply"])]
+    async fn get_token_supply(
+        &self,
+        mint: String,
+        parameters: Option<Parameters>,
+    ) -> RpcResult<JsonValue>;
+
+    /// Processes the `getTokenLargestAccounts` method
+    #[method(name = "getTokenLargestAccounts", aliases = ["getTokenLargestAccounts"])]
+    async fn get_token_largest_accounts(
+        &self,
+        mint: String,
+        parameters: Option<Parameters>,
+    ) -> RpcResult<JsonValue>;
+
+    /// Processes the `getSlot` method
+    #[method(name = "getSlot", aliases = ["getSlot"])]
+    async fn get_slot(&self, parameters: Option<Parameters>) -> RpcResult<u64>;
+
+    /// Processes the `getHealth` method
+    #[method(name = "getHealth", aliases = ["getHealth"])]
+    async fn get_health(&self) -> RpcResult<String>;
+
+    /// Processes the `getVersion` method
+    #[method(name = "getVersion", aliases = ["getVersion"])]
+    async fn get_version(&self) -> RpcResult<RpcVersionInfo>;
+
+    /// Processes the `getMinimumBalanceForRentExemption` method
+    #[method(
+        name = "getMinimumBalanceForRentExemption",
+        aliases = ["getMinimumBalanceForRentExemption"]
+    )]
+    async fn get_minimum_balance_for_rent_exemption(
+        &self,
+        data_len: usize,
+        parameters: Option<Parameters>,
+    ) -> RpcResult<u64>;
+
+    /// Processes the `getEpochInfo` method
+    #[method(name = "getEpochInfo", aliases = ["getEpochInfo"])]
+    async fn get_epoch_info(&self, parameters: Option<Parameters>) -> RpcResult<RpcEpochInfo>;
+
+    /// Processes the `getSupply` method
+    #[method(name = "getSupply", aliases = ["getSupply"])]
+    async fn get_supply(&self, parameters: Option<Parameters>) -> RpcResult<JsonValue>;
+
+    /// Processes the `getLargestAccounts` method
+    #[method(name = "getLargestAccounts", aliases = ["getLargestAccounts"])]
+    async fn get_largest_accounts(&self, parameters: Option<Parameters>) -> RpcResult<JsonValue>;
+
+    /// Processes the `getEpochSchedule` method
+    #[method(name = "getEpochSchedule", aliases = ["getEpochSchedule"])]
+    async fn get_epoch_schedule(&self) -> RpcResult<UiEpochSchedule>;
 }
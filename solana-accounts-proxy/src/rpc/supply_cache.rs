@@ -0,0 +1,308 @@
+use crate::{LargestAccountsFilter, ProxyResult, RpcAccountBalance, RpcSupply};
+use std::{
+    collections::HashMap,
+    future::Future,
+    hash::Hash,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+lazy_static::lazy_static! {
+    /// The results of `getSupply` and `getLargestAccounts`, both read every account
+    /// so they are computed at most once per finalized slot and per refresh interval
+    pub static ref SUPPLY_CACHE: SupplyCache = SupplyCache::new(Duration::from_secs(
+        crate::USER_CONFIG.rpc_config().supply_refresh_interval(),
+    ));
+}
+
+/// The cached results of `getSupply` and `getLargestAccounts`
+#[derive(Debug)]
+pub struct SupplyCache {
+    /// The value of `getSupply`
+    pub supply: SlotCache<(), RpcSupply>,
+    /// The value of `getLargestAccounts` per filter
+    pub largest_accounts: SlotCache<Option<LargestAccountsFilter>, Vec<RpcAccountBalance>>,
+}
+
+impl SupplyCache {
+    /// Empty caches whose values are refreshed at most once per `refresh_interval`
+    pub fn new(refresh_interval: Duration) -> Self {
+        // Only one query reading the whole `accounts` table runs at a time
+        let refresh = Arc::new(tokio::sync::Mutex::new(()));
+
+        SupplyCache {
+            supply: SlotCache::new(refresh.clone(), refresh_interval),
+            largest_accounts: SlotCache::new(refresh, refresh_interval),
+        }
+    }
+}
+
+/// Values computed at a finalized slot. A value is served until a value computed at
+/// a later slot replaces it, and the caches sharing the `refresh` guard compute
+/// a single value at a time.
+#[derive(Debug)]
+pub struct SlotCache<K, V> {
+    entries: Arc<Mutex<HashMap<K, CacheEntry<V>>>>,
+    refresh: Arc<tokio::sync::Mutex<()>>,
+    refresh_interval: Duration,
+}
+
+/// A cached value, the slot it was computed at and when it was cached
+#[derive(Debug, Clone)]
+struct CacheEntry<V> {
+    slot: u64,
+    cached_at: Instant,
+    value: V,
+}
+
+impl<K, V> SlotCache<K, V>
+where
+    K: Eq + Hash + Send + 'static,
+    V: Clone + Send + 'static,
+{
+    /// An empty cache computing its values while holding `refresh`,
+    /// a value is not refreshed before it is `refresh_interval` old
+    pub fn new(refresh: Arc<tokio::sync::Mutex<()>>, refresh_interval: Duration) -> Self {
+        SlotCache {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            refresh,
+            refresh_interval,
+        }
+    }
+
+    /// The value of `key` and the slot it was computed at. A value computed before `slot` is
+    /// returned right away and, once it is older than the refresh interval, `compute` refreshes
+    /// it in the background unless a value is already being computed.
+    /// Without a value the request waits for `compute`.
+    pub async fn get<F, Fut>(&self, key: K, slot: u64, compute: F) -> ProxyResult<(u64, V)>
+    where
+        F: FnOnce(u64) -> Fut,
+        Fut: Future<Output = ProxyResult<V>> + Send + 'static,
+    {
+        if let Some(entry) = self.entry(&key) {
+            let cached_slot = entry.slot;
+
+            if cached_slot < slot && entry.cached_at.elapsed() >= self.refresh_interval {
+                if let Ok(guard) = self.refresh.clone().try_lock_owned() {
+                    let entries = self.entries.clone();
+                    let refreshed = compute(slot);
+
+                    tokio::spawn(async move {
+                        match refreshed.await {
+                            Ok(value) => SlotCache::insert(&entries, key, slot, value),
+                            Err(error) => tracing::error!(
+                                "Failed to refresh the value cached at slot {}: {:?}",
+                                cached_slot,
+                                error
+                            ),
+                        }
+
+                        drop(guard);
+                    });
+                }
+            }
+
+            return Ok((cached_slot, entry.value));
+        }
+
+        let _guard = self.refresh.lock().await;
+
+        // Computed by the request that held the guard
+        if let Some(cached) = self.cached(&key) {
+            return Ok(cached);
+        }
+
+        let value = compute(slot).await?;
+        SlotCache::insert(&self.entries, key, slot, value.clone());
+
+        Ok((slot, value))
+    }
+
+    fn cached(&self, key: &K) -> Option<(u64, V)> {
+        self.entry(key).map(|entry| (entry.slot, entry.value))
+    }
+
+    fn entry(&self, key: &K) -> Option<CacheEntry<V>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(key)
+            .cloned()
+    }
+
+    /// Cache the value of `key` computed at `slot`, a value of a later slot is kept
+    fn insert(entries: &Mutex<HashMap<K, CacheEntry<V>>>, key: K, slot: u64, value: V) {
+        let mut entries = entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        match entries.get(&key) {
+            Some(entry) if entry.slot > slot => (),
+            _ => {
+                entries.insert(
+                    key,
+                    CacheEntry {
+                        slot,
+                        cached_at: Instant::now(),
+                        value,
+                    },
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProxyError;
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    #[tokio::test]
+    async fn cache_per_finalized_slot() -> anyhow::Result<()> {
+        let cache = Arc::new(SupplyCache::new(Duration::ZERO));
+        let scans = Arc::new(AtomicUsize::new(0));
+
+        // Without a value every request waits for a single computation
+        let requests = (0..4).map(|_| {
+            let (cache, scans) = (cache.clone(), scans.clone());
+
+            tokio::spawn(async move {
+                cache
+                    .supply
+                    .get((), 100, |slot| {
+                        scan(scans, slot, Duration::from_millis(100))
+                    })
+                    .await
+            })
+        });
+        for request in futures_util::future::join_all(requests).await {
+            assert_eq!(request?.map_err(to_anyhow)?, (100, supply(100)));
+        }
+        assert_eq!(scans.load(Ordering::SeqCst), 1);
+
+        // The same finalized slot is served from the cache
+        let cached = cache
+            .supply
+            .get((), 100, |_| async { unreachable!() })
+            .await;
+        assert_eq!(cached.map_err(to_anyhow)?, (100, supply(100)));
+
+        // A later slot is served the last value while a single refresh runs in the background
+        for _ in 0..3 {
+            let stale = cache
+                .supply
+                .get((), 101, |slot| {
+                    scan(scans.clone(), slot, Duration::from_millis(100))
+                })
+                .await;
+            assert_eq!(stale.map_err(to_anyhow)?, (100, supply(100)));
+        }
+
+        // The other cache waits for the refresh, only one scan runs at a time
+        let largest = cache
+            .largest_accounts
+            .get(None, 101, |slot| async move {
+                Ok(vec![RpcAccountBalance {
+                    address: "Stake11111111111111111111111111111111111111".to_owned(),
+                    lamports: slot,
+                }])
+            })
+            .await
+            .map_err(to_anyhow)?;
+        assert_eq!(largest.0, 101);
+
+        let refreshed = cache
+            .supply
+            .get((), 101, |_| async { unreachable!() })
+            .await;
+        assert_eq!(refreshed.map_err(to_anyhow)?, (101, supply(101)));
+        assert_eq!(scans.load(Ordering::SeqCst), 2);
+
+        // A slower refresh of an earlier slot does not replace a later value
+        SlotCache::insert(&cache.supply.entries, (), 100, supply(100));
+        let cached = cache
+            .supply
+            .get((), 101, |_| async { unreachable!() })
+            .await;
+        assert_eq!(cached.map_err(to_anyhow)?, (101, supply(101)));
+
+        // A failed refresh keeps serving the last value
+        let stale = cache
+            .supply
+            .get((), 102, |_| async {
+                Err(ProxyError::Internal("failed".to_owned()))
+            })
+            .await;
+        assert_eq!(stale.map_err(to_anyhow)?, (101, supply(101)));
+        let _guard = cache.supply.refresh.lock().await;
+        assert_eq!(cache.supply.cached(&()), Some((101, supply(101))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn refresh_interval() -> anyhow::Result<()> {
+        let cache = SupplyCache::new(Duration::from_millis(200));
+
+        let computed = cache
+            .supply
+            .get((), 100, |slot| async move { Ok(supply(slot)) })
+            .await;
+        assert_eq!(computed.map_err(to_anyhow)?, (100, supply(100)));
+
+        // A later slot is not refreshed before the value is as old as the interval
+        let stale = cache
+            .supply
+            .get((), 101, |_| async { unreachable!() })
+            .await;
+        assert_eq!(stale.map_err(to_anyhow)?, (100, supply(100)));
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        // The same slot is never refreshed
+        let cached = cache
+            .supply
+            .get((), 100, |_| async { unreachable!() })
+            .await;
+        assert_eq!(cached.map_err(to_anyhow)?, (100, supply(100)));
+
+        let stale = cache
+            .supply
+            .get((), 101, |slot| async move { Ok(supply(slot)) })
+            .await;
+        assert_eq!(stale.map_err(to_anyhow)?, (100, supply(100)));
+
+        let _guard = cache.supply.refresh.lock().await;
+        assert_eq!(cache.supply.cached(&()), Some((101, supply(101))));
+
+        Ok(())
+    }
+
+    async fn scan(
+        scans: Arc<AtomicUsize>,
+        slot: u64,
+        duration: Duration,
+    ) -> ProxyResult<RpcSupply> {
+        scans.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(duration).await;
+
+        Ok(supply(slot))
+    }
+
+    fn supply(total: u64) -> RpcSupply {
+        RpcSupply {
+            total,
+            circulating: total,
+            non_circulating: 0,
+            non_circulating_accounts: Vec::new(),
+        }
+    }
+
+    fn to_anyhow(error: ProxyError) -> anyhow::Error {
+        anyhow::anyhow!("{:?}", error)
+    }
+}
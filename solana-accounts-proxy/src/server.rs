@@ -0,0 +1,303 @@
+use crate::{
+    GetProgramAccountsRequest, GpaStream, Parameters, RpcProxyImpl, RpcProxyServer, Upstream,
+};
+use hyper::{
+    body::HttpBody,
+    header::{self, HeaderValue},
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use jsonrpsee::{
+    core::server::rpc_module::Methods,
+    types::{
+        error::ErrorCode, ErrorObject, ErrorResponse, Id, Notification, Params,
+        Request as JsonRpcRequest,
+    },
+};
+use serde_json::value::RawValue;
+use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+use tokio::task::JoinHandle;
+
+/// The maximum size of a request body in bytes, same as the `jsonrpsee` HTTP server
+pub const MAX_REQUEST_BODY_SIZE: usize = 10 * 1024 * 1024;
+
+/// Create a HTTP server to serve RPC requests.
+/// A single `getProgramAccounts` request is streamed with [GpaStream],
+/// every other request is answered by the `jsonrpsee` methods of [RpcProxyServer].
+/// The calls the proxy cannot answer are forwarded to the `upstream` Solana RPC node.
+pub async fn http_server(
+    socket_addr: SocketAddr,
+    upstream: Option<Arc<Upstream>>,
+) -> anyhow::Result<(SocketAddr, JoinHandle<Result<(), hyper::Error>>)> {
+    let methods: Methods = RpcProxyImpl.into_rpc().into();
+
+    let make_service = make_service_fn(move |_| {
+        let methods = methods.clone();
+        let upstream = upstream.clone();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle_request(methods.clone(), upstream.clone(), request)
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&socket_addr)?.serve(make_service);
+    let addr = server.local_addr();
+
+    Ok((addr, tokio::spawn(server)))
+}
+
+/// A JSON response, the CORS headers are added by the server
+pub fn json_response(body: impl Into<Body>) -> Response<Body> {
+    let mut response = Response::new(body.into());
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json; charset=utf-8"),
+    );
+
+    response
+}
+
+async fn handle_request(
+    methods: Methods,
+    upstream: Option<Arc<Upstream>>,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    let mut response = match *request.method() {
+        Method::POST => match read_body(request.into_body()).await {
+            Ok(body) => process_body(&methods, upstream.as_deref(), &body).await,
+            Err(response) => response,
+        },
+        Method::OPTIONS => {
+            let mut response = Response::new(Body::empty());
+            let headers = response.headers_mut();
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_METHODS,
+                HeaderValue::from_static("POST, OPTIONS"),
+            );
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_HEADERS,
+                HeaderValue::from_static("*"),
+            );
+
+            response
+        }
+        _ => status_response(StatusCode::METHOD_NOT_ALLOWED),
+    };
+
+    response.headers_mut().insert(
+        header::ACCESS_CONTROL_ALLOW_ORIGIN,
+        HeaderValue::from_static("*"),
+    );
+
+    Ok(response)
+}
+
+async fn read_body(mut body: Body) -> Result<Vec<u8>, Response<Body>> {
+    let mut bytes = Vec::new();
+
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|_| status_response(StatusCode::BAD_REQUEST))?;
+
+        if bytes.len() + chunk.len() > MAX_REQUEST_BODY_SIZE {
+            return Err(status_response(StatusCode::PAYLOAD_TOO_LARGE));
+        }
+
+        bytes.extend_from_slice(&chunk);
+    }
+
+    Ok(bytes)
+}
+
+async fn process_body(
+    methods: &Methods,
+    upstream: Option<&Upstream>,
+    body: &[u8],
+) -> Response<Body> {
+    let body = match std::str::from_utf8(body) {
+        Ok(body) => body,
+        Err(_) => return error_response(ErrorCode::ParseError),
+    };
+
+    if body.trim_start().starts_with('[') {
+        return process_batch(methods, upstream, body).await;
+    }
+
+    // A streamed response cannot be replaced by the response of the upstream node, so a request
+    // that may be forwarded because of its `minContextSlot` is answered by the `jsonrpsee` method
+    let forwards_min_context_slot =
+        upstream.is_some_and(|upstream| upstream.forwards_min_context_slot());
+
+    if let Some((id, request)) = program_accounts_request(body)
+        .filter(|(_, request)| !forwards_min_context_slot || request.min_context_slot().is_none())
+    {
+        return GpaStream::respond(
+            id,
+            request,
+            crate::USER_CONFIG.rpc_config().gpa_stream_buffer_size(),
+        )
+        .await;
+    }
+
+    match process_call(methods, upstream, body).await {
+        Ok(Some(response)) => json_response(response),
+        Ok(None) => status_response(StatusCode::OK),
+        Err(error_code) => error_response(error_code),
+    }
+}
+
+async fn process_batch(
+    methods: &Methods,
+    upstream: Option<&Upstream>,
+    body: &str,
+) -> Response<Body> {
+    let calls = match serde_json::from_str::<Vec<&RawValue>>(body) {
+        Ok(calls) => calls,
+        Err(_) => return error_response(ErrorCode::ParseError),
+    };
+
+    if calls.is_empty() {
+        return error_response(ErrorCode::InvalidRequest);
+    }
+
+    // The calls run concurrently and are answered in the order of the batch
+    let responses = futures_util::future::join_all(
+        calls
+            .into_iter()
+            .map(|call| process_call(methods, upstream, call.get())),
+    )
+    .await
+    .into_iter()
+    .filter_map(|response| match response {
+        Ok(response) => response,
+        Err(error_code) => Some(error_json(error_code)),
+    })
+    .collect::<Vec<String>>();
+
+    // A batch of notifications is not answered
+    if responses.is_empty() {
+        return status_response(StatusCode::OK);
+    }
+
+    json_response(format!("[{}]", responses.join(",")))
+}
+
+/// The response of a call, `None` for a notification which is not answered
+async fn process_call(
+    methods: &Methods,
+    upstream: Option<&Upstream>,
+    call: &str,
+) -> Result<Option<String>, ErrorCode> {
+    let response = match methods.raw_json_request(call).await {
+        Ok((response, _)) => response,
+        Err(_) => {
+            if serde_json::from_str::<Notification<Option<&RawValue>>>(call).is_ok() {
+                return Ok(None);
+            }
+
+            match serde_json::from_str::<&RawValue>(call) {
+                Ok(_) => return Err(ErrorCode::InvalidRequest),
+                Err(_) => return Err(ErrorCode::ParseError),
+            }
+        }
+    };
+
+    // Only errors are parsed to find out whether the call is forwarded
+    if let Some(upstream) =
+        upstream.filter(|upstream| !response.success && upstream.forwards(&response.result))
+    {
+        if let Some(forwarded) = upstream.forward(call).await {
+            return Ok(Some(forwarded));
+        }
+    }
+
+    Ok(Some(response.result))
+}
+
+/// Parse a single `getProgramAccounts` request. Invalid parameters are left to
+/// the `jsonrpsee` method which returns the same error as the other methods.
+fn program_accounts_request(body: &str) -> Option<(Id<'static>, GetProgramAccountsRequest)> {
+    let call = serde_json::from_str::<JsonRpcRequest>(body).ok()?;
+
+    if call.method != "getProgramAccounts" && call.method != "proxy_getProgramAccounts" {
+        return Option::None;
+    }
+
+    let params = Params::new(call.params.map(|params| params.get()));
+    let mut params = params.sequence();
+    let base58_public_key: String = params.next().ok()?;
+    let parameters: Option<Parameters> = params.optional_next().ok()?;
+
+    let request = GetProgramAccountsRequest::parse(base58_public_key, parameters).ok()?;
+
+    Some((call.id.into_owned(), request))
+}
+
+fn error_json(error_code: ErrorCode) -> String {
+    serde_json::to_string(&ErrorResponse::borrowed(
+        ErrorObject::from(error_code),
+        Id::Null,
+    ))
+    .unwrap_or_default()
+}
+
+fn error_response(error_code: ErrorCode) -> Response<Body> {
+    json_response(error_json(error_code))
+}
+
+fn status_response(status_code: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = status_code;
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn batch_and_notifications() -> anyhow::Result<()> {
+        let methods: Methods = RpcProxyImpl.into_rpc().into();
+
+        let notification = r#"{"jsonrpc":"2.0","method":"getSlot"}"#;
+        assert_eq!(process_call(&methods, None, notification).await, Ok(None));
+
+        let response = process_body(&methods, None, notification.as_bytes()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(hyper::body::to_bytes(response.into_body())
+            .await?
+            .is_empty());
+
+        let batch = format!("[{notification},{notification}]");
+        let response = process_body(&methods, None, batch.as_bytes()).await;
+        assert!(hyper::body::to_bytes(response.into_body())
+            .await?
+            .is_empty());
+
+        // The notifications are dropped and the responses keep the order of the calls
+        let batch = format!(
+            r#"[{{"jsonrpc":"2.0","id":1,"method":"unknown"}},{notification},"invalid",{{"jsonrpc":"2.0","id":2,"method":"unknown"}}]"#
+        );
+        let response = process_body(&methods, None, batch.as_bytes()).await;
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        let responses: Vec<serde_json::Value> = serde_json::from_slice(&body)?;
+
+        let ids = responses
+            .iter()
+            .map(|response| response["id"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [1.into(), serde_json::Value::Null, 2.into()]);
+        assert_eq!(
+            responses[1]["error"]["code"],
+            ErrorCode::InvalidRequest.code()
+        );
+        assert_eq!(
+            responses[2]["error"]["code"],
+            ErrorCode::MethodNotFound.code()
+        );
+
+        Ok(())
+    }
+}
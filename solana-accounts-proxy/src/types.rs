@@ -1,8 +1,9 @@
 use core::fmt;
-use jsonrpsee::core::{Error as JsonrpseeError, RpcResult};
+use jsonrpsee::core::RpcResult;
 use serde::Deserialize;
+use serde_json::Value as SerdeJsonValue;
 
-use crate::{ProxyError, ProxyResult};
+use crate::{ProxyError, ProxyResult, MAX_BASE58_BYTES};
 
 /// Holds and ed25519 public key for a Solana program or account
 #[derive(Deserialize)]
@@ -15,9 +16,10 @@ impl PubKey {
         let decoded_bytes = match bs58::decode(&value).into_vec() {
             Ok(value) => value,
             Err(_) => {
-                return Err(JsonrpseeError::Custom(
+                return Err(ProxyError::InvalidParams(
                     "The encoded public key is not valid Base58 format".to_owned(),
-                ))
+                )
+                .into())
             }
         };
 
@@ -31,7 +33,7 @@ impl PubKey {
                 error.push_str(decoded_bytes_len.to_string().as_str());
                 error.push_str("` bytes instead of `32 bytes`.");
 
-                return Err(JsonrpseeError::Custom(error));
+                return Err(ProxyError::InvalidParams(error).into());
             }
         };
 
@@ -63,11 +65,19 @@ pub struct Parameters {
     pub filters: Option<Vec<Filter>>,
     /// wrap the result in an RpcResponse JSON object.
     pub with_context: Option<bool>,
+    /// Selects the circulating or the non circulating accounts of `getLargestAccounts`
+    pub filter: Option<LargestAccountsFilter>,
+    /// Leave the list of non circulating accounts out of `getSupply`
+    pub exclude_non_circulating_accounts_list: Option<bool>,
 }
 
 /// Which format the proxy server should use when transmitting a response data to a client
-#[derive(Debug, Deserialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum Encoding {
+    /// Deprecated encoding still sent by older clients, the data is a bare `base58` string
+    /// instead of a `[data, encoding]` array
+    #[serde(rename = "binary")]
+    Binary,
     /// Use Base58 encoding
     #[serde(rename = "base58")]
     Base58,
@@ -83,25 +93,43 @@ pub enum Encoding {
 }
 
 impl Encoding {
+    /// The encoding of the accounts when a request has none. Like Solana, `getAccountInfo`,
+    /// `getMultipleAccounts` and `getProgramAccounts` default to the legacy `binary` encoding.
+    pub const DEFAULT: Encoding = Encoding::Binary;
+
     /// Check which encoding format to use on the data field
     pub fn get_encoding(parameters: Option<&Parameters>) -> Encoding {
-        if let Some(parameters) = parameters {
-            match &parameters.encoding {
-                None => Encoding::Base58,
-                Some(encoding) => *encoding,
-            }
-        } else {
-            Encoding::Base58
+        parameters
+            .and_then(|parameters| parameters.encoding)
+            .unwrap_or(Encoding::DEFAULT)
+    }
+
+    /// The encoding of the accounts that cannot be parsed by `jsonParsed`
+    pub fn fallback(&self) -> Encoding {
+        match self {
+            Self::JsonParsed => Encoding::Base64,
+            encoding => *encoding,
         }
     }
 
-    /// Encode data to the chosen format
+    /// Only the binary encodings can return a slice of the account data
+    pub fn check_data_slice(&self, data_slice: Option<&DataSlice>) -> ProxyResult<()> {
+        match (self, data_slice) {
+            (Self::JsonParsed, Some(_)) => Err(ProxyError::InvalidRequest(
+                "Sliced account data can only be encoded using binary (base 58) or base64 encoding."
+                    .to_owned(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Encode data to the chosen format. Like Solana, data of more than [MAX_BASE58_BYTES]
+    /// is rejected by the `base58` and `binary` encodings.
     pub fn encode(&self, data: &[u8]) -> RpcResult<String> {
         match self {
-            Self::Base58 => {
-                if data.len() > 129 {
-                    // FIXME Use correct JSON response code of `-32001`
-                    return Err(JsonrpseeError::ResourceNameAlreadyTaken("Encoded binary (base 58) data should be less than 128 bytes, please use Base64 encoding."));
+            Self::Base58 | Self::Binary => {
+                if data.len() > MAX_BASE58_BYTES {
+                    return Err(ProxyError::Base58DataTooLarge.into());
                 }
 
                 tracing::trace!("ENCODING DATA CHUNK AS Base58");
@@ -120,50 +148,45 @@ impl Encoding {
             Self::Base64Zstd => {
                 tracing::trace!("ENCODING DATA CHUNK AS Base64+zstd");
 
-                let mut buffer = data.to_vec();
-                let encoder = match zstd::Encoder::new(&mut buffer, 3) {
-                    Ok(data) => data,
-                    Err(error) => return Err(JsonrpseeError::Custom(error.to_string())),
-                };
-
-                match encoder.finish() {
-                    Ok(data) => data,
-                    Err(error) => return Err(JsonrpseeError::Custom(error.to_string())),
-                };
-
-                let data = base64::encode(&buffer);
+                let compressed = Encoding::zstd_compress(
+                    data,
+                    crate::USER_CONFIG.rpc_config().zstd_compression_level(),
+                )?;
+                let data = base64::encode(compressed);
 
                 tracing::trace!("FINISHED ENCODING DATA CHUNK AS Base64+zstd");
 
                 Ok(data)
             }
-            Self::JsonParsed => {
-                tracing::trace!("FINISHED ENCODING DATA CHUNK AS JsonParsed");
-                let to_json: json::JsonValue = data.into();
-
-                let data = to_json.to_string();
-
-                tracing::trace!("FINISHED ENCODING DATA CHUNK AS JsonParsed");
-
-                Ok(data)
-            }
+            // Accounts that cannot be parsed are returned as `base64`, see [crate::parse_account_data]
+            Self::JsonParsed => Encoding::Base64.encode(data),
         }
     }
 
+    /// Compress data into a single zstd frame that can be decompressed by web3.js
+    pub fn zstd_compress(data: &[u8], level: i32) -> ProxyResult<Vec<u8>> {
+        zstd::stream::encode_all(data, level)
+            .map_err(|error| ProxyError::Internal(error.to_string()))
+    }
+
     /// Decode data from a method parameter,
     /// `NOTE:` Only `base64` and `base58` formats, all other formats result in an RPC error.
     pub fn decode(&self, data: &[u8]) -> ProxyResult<Vec<u8>> {
         match self {
             Self::Base58 => match bs58::decode(data).into_vec() {
                 Ok(decoded_data) => Ok(decoded_data),
-                Err(error) => Err(ProxyError::Client(error.to_string())),
+                Err(error) => Err(ProxyError::InvalidParams(error.to_string())),
+            },
+            Self::Base64 => match base64::decode(data) {
+                Ok(decoded_data) => Ok(decoded_data),
+                Err(error) => Err(ProxyError::InvalidParams(error.to_string())),
             },
             _ => {
                 let mut to_rpc_error = "Unsupported data encoding format `".to_owned();
                 to_rpc_error.push_str(self.to_str());
                 to_rpc_error.push_str("` for the method.");
 
-                Err(ProxyError::Client(to_rpc_error.to_string()))
+                Err(ProxyError::InvalidParams(to_rpc_error.to_string()))
             }
         }
     }
@@ -174,21 +197,40 @@ impl Encoding {
         match self {
             Self::Base58 => match bs58::decode(data).into_vec() {
                 Ok(decoded_data) => Ok(decoded_data),
-                Err(error) => Err(ProxyError::Client(error.to_string())),
+                Err(error) => Err(ProxyError::InvalidParams(error.to_string())),
+            },
+            Self::Base64 => match base64::decode(data) {
+                Ok(decoded_data) => Ok(decoded_data),
+                Err(error) => Err(ProxyError::InvalidParams(error.to_string())),
             },
             _ => {
                 let mut to_rpc_error = "Unsupported data encoding format `".to_owned();
                 to_rpc_error.push_str(self.to_str());
                 to_rpc_error.push_str("` for the method.");
 
-                Err(ProxyError::Client(to_rpc_error.to_string()))
+                Err(ProxyError::InvalidParams(to_rpc_error.to_string()))
             }
         }
     }
 
+    /// Encode data into the `data` field of an account, a `[data, encoding]` array
+    /// or a bare string with the legacy `binary` encoding
+    pub fn encode_json(&self, data: &[u8]) -> RpcResult<SerdeJsonValue> {
+        let encoded = self.encode(data)?;
+
+        match self {
+            Self::Binary => Ok(encoded.into()),
+            encoding => Ok(SerdeJsonValue::Array(vec![
+                encoded.into(),
+                encoding.fallback().to_str().into(),
+            ])),
+        }
+    }
+
     /// Used to return the encoding type in the JSON response
     pub fn to_str(&self) -> &str {
         match self {
+            Self::Binary => "binary",
             Self::Base58 => "base58",
             Self::Base64 => "base64",
             Self::Base64Zstd => "base64+zstd",
@@ -198,7 +240,7 @@ impl Encoding {
 }
 
 /// Whether a block has been confirmed, is being processed or has been finalized
-#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 #[derive(postgres_types::ToSql)]
 pub enum Commitment {
@@ -219,15 +261,26 @@ impl Commitment {
             Self::Finalized => "Finalized",
         }
     }
+    /// Convert to a subquery returning the highest slot visible at this commitment level.
+    /// A `processed` read sees every slot, a `confirmed` read sees confirmed and finalized
+    /// slots and a `finalized` read only sees finalized slots.
+    pub fn max_slot_subquery<'a>(&self) -> &'a str {
+        match self {
+            Self::Processed => "(SELECT MAX(slot) FROM slots)",
+            Self::Confirmed => "(SELECT MAX(slot) FROM slots WHERE LOWER(status::VARCHAR) IN ('confirmed', 'finalized'))",
+            Self::Finalized => "(SELECT MAX(slot) FROM slots WHERE LOWER(status::VARCHAR) = 'finalized')",
+        }
+    }
+
     /// Returns the commitment level to use when executing the query
-    pub fn get_commitment<'a>(parameters: Option<&Parameters>) -> &'a str {
+    pub fn get_commitment(parameters: Option<&Parameters>) -> Commitment {
         if let Some(parameters) = parameters {
             match parameters.commitment {
-                Some(commitment) => commitment.queryable(),
-                None => Commitment::Finalized.queryable(),
+                Some(commitment) => commitment,
+                None => Commitment::Finalized,
             }
         } else {
-            Commitment::Finalized.queryable()
+            Commitment::Finalized
         }
     }
 }
@@ -252,6 +305,19 @@ pub struct DataSlice {
     pub length: usize,
 }
 
+impl DataSlice {
+    /// Convert to the 1-based `(start, length)` arguments of the SQL `SUBSTRING` function
+    /// so only the slice is sent by the database. Values that do not fit an SQL `INTEGER`
+    /// are clamped, which Postgres resolves to an empty slice or the rest of the data
+    /// just like Solana does since accounts are far smaller.
+    pub fn as_sql_range(&self) -> (i32, i32) {
+        let start = i32::try_from(self.offset.saturating_add(1)).unwrap_or(i32::MAX);
+        let length = i32::try_from(self.length).unwrap_or(i32::MAX);
+
+        (start, length)
+    }
+}
+
 /// Filters that data
 #[derive(Debug, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -261,62 +327,472 @@ pub enum Filter {
     /// Maps to a list of [MemCmp]s
     #[serde(rename = "memcmp")]
     Memcmp(MemCmpData),
+    /// Only the initialized SPL Token and Token-2022 accounts, with or without extensions.
+    /// Only built by [Filter::token_accounts], clients cannot send it.
+    #[serde(skip_deserializing)]
+    TokenAccountState,
 }
 
 impl Filter {
-    /// Get the data size from the filters
-    pub fn data_size(filters: &[Filter]) -> ProxyResult<u64> {
-        match filters.get(0) {
-            Some(filter) => match filter {
-                Filter::DataSize(data_size) => Ok(*data_size),
-                _ => Err(ProxyError::Client(
-                    "Invalid Format for `Filters`. First index should be the `dataSize`".to_owned(),
-                )),
-            },
-            None => Err(ProxyError::Client(
-                "Expected a `Filter` at index 1".to_owned(),
-            )),
+    /// The maximum number of filters of a request, same as Solana
+    pub const MAX_FILTERS: usize = 4;
+
+    /// Check the filters of a request before the query is built,
+    /// the error names the index of the first invalid filter
+    pub fn verify(filters: &[Filter]) -> ProxyResult<()> {
+        if filters.len() > Filter::MAX_FILTERS {
+            return Err(ProxyError::InvalidParams(format!(
+                "Too many filters provided; max {}",
+                Filter::MAX_FILTERS
+            )));
         }
+
+        for (index, filter) in filters.iter().enumerate() {
+            if let Filter::Memcmp(memcmp_data) = filter {
+                if let Err(reason) = memcmp_data.decode_bytes() {
+                    return Err(ProxyError::InvalidParams(format!(
+                        "Invalid `memcmp` filter at index {}: {}",
+                        index, reason
+                    )));
+                }
+            }
+        }
+
+        Ok(())
     }
 
-    /// Get `MemCmp`s
-    pub fn memcmps(filters: Vec<Filter>) -> ProxyResult<Vec<MemCmpData>> {
-        let mut values = Vec::<MemCmpData>::new();
+    /// A `memcmp` filter on raw bytes
+    pub fn memcmp_bytes(offset: usize, bytes: Vec<u8>) -> Filter {
+        Filter::Memcmp(MemCmpData {
+            offset,
+            bytes: MemCmpBytes::Raw(bytes),
+            encoding: Some(MemCmpEncoding::Bytes),
+        })
+    }
 
-        if filters.len() > 4 {
-            return Err(ProxyError::Client(
-                "Too many filters provided; max 4".to_owned(),
-            ));
+    /// The filter of the token accounts of a token program used by a Solana RPC node,
+    /// Token-2022 accounts with extensions are longer than the SPL Token accounts
+    pub fn token_accounts(program_id: &str) -> Filter {
+        if program_id == crate::SPL_TOKEN_PROGRAM_ID {
+            Filter::DataSize(crate::TOKEN_ACCOUNT_LENGTH as u64)
+        } else {
+            Filter::TokenAccountState
         }
+    }
+}
 
-        filters.into_iter().skip(1).for_each(|filter| match filter {
-            Filter::Memcmp(inner_data) => values.push(inner_data),
-            _ => (),
-        });
+/// Selects the token accounts of `getTokenAccountsByOwner` and `getTokenAccountsByDelegate`
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum TokenAccountsFilter {
+    /// The token accounts of a mint, the token program is the owner of the mint
+    Mint(String),
+    /// The token accounts of the SPL Token or the Token-2022 program
+    ProgramId(String),
+}
 
-        Ok(values)
+/// Selects the accounts of `getLargestAccounts` using the `non_circulating_accounts` of the `[rpc]` section
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum LargestAccountsFilter {
+    /// The accounts that are not in the list of non circulating accounts
+    Circulating,
+    /// The accounts of the list of non circulating accounts
+    NonCirculating,
+}
+
+/// The maximum number of decoded bytes of a `memcmp` filter, same as Solana
+pub const MAX_MEMCMP_BYTES: usize = 128;
+/// The maximum length of `base58` encoded `memcmp` bytes, the encoding of [MAX_MEMCMP_BYTES]
+pub const MAX_MEMCMP_BASE58_SIZE: usize = 175;
+/// The maximum length of `base64` encoded `memcmp` bytes, the encoding of [MAX_MEMCMP_BYTES]
+pub const MAX_MEMCMP_BASE64_SIZE: usize = 172;
+
+/// The encoding of the bytes of a `memcmp` filter
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MemCmpEncoding {
+    /// Deprecated name of `base58` still sent by older clients
+    Binary,
+    /// The bytes are a `base58` string
+    Base58,
+    /// The bytes are a `base64` string
+    Base64,
+    /// The bytes are a JSON array of numbers
+    Bytes,
+}
+
+impl MemCmpEncoding {
+    /// Used in the error messages
+    pub fn to_str(&self) -> &str {
+        match self {
+            Self::Binary => "binary",
+            Self::Base58 => "base58",
+            Self::Base64 => "base64",
+            Self::Bytes => "bytes",
+        }
     }
 }
 
+/// The bytes of a `memcmp` filter, either an encoded string or an array of raw bytes
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum MemCmpBytes {
+    /// A `base58` or `base64` string
+    Encoded(String),
+    /// An array of raw bytes
+    Raw(Vec<u8>),
+}
+
 ///  The comparison data of [MemCmp]
-#[derive(Debug, Deserialize, Default, Clone)]
+#[derive(Debug, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct MemCmpData {
     /// offset into program account data to start comparison
     pub offset: usize,
-    /// data to match, as encoded string
-    pub bytes: String,
-    /// encoding for filter bytes data, either "base58" or "base64".
+    /// data to match, as encoded string or as an array of bytes
+    pub bytes: MemCmpBytes,
+    /// encoding for filter bytes data, either "base58", "base64" or "bytes".
     /// Data is limited in size to 128 or fewer decoded bytes.
-    pub encoding: Option<Encoding>,
+    pub encoding: Option<MemCmpEncoding>,
 }
 
 impl MemCmpData {
     /// Decode the bytes of the `MemCmp` data
     pub fn decode(&self) -> ProxyResult<Vec<u8>> {
-        match self.encoding {
-            Some(encoding) => encoding.decode_str(&self.bytes),
-            None => Encoding::Base58.decode_str(&self.bytes),
+        self.decode_bytes().map_err(ProxyError::InvalidParams)
+    }
+
+    /// The encoded length is checked before decoding so an oversized string is never decoded.
+    /// An array of bytes defaults to the `bytes` encoding and a string to `base58`.
+    fn decode_bytes(&self) -> Result<Vec<u8>, String> {
+        let decoded = match (&self.bytes, self.encoding) {
+            (MemCmpBytes::Raw(bytes), None | Some(MemCmpEncoding::Bytes)) => bytes.clone(),
+            (
+                MemCmpBytes::Encoded(bytes),
+                None | Some(MemCmpEncoding::Base58) | Some(MemCmpEncoding::Binary),
+            ) => {
+                if bytes.len() > MAX_MEMCMP_BASE58_SIZE {
+                    return Err(format!(
+                        "the `base58` encoded bytes are longer than {} characters",
+                        MAX_MEMCMP_BASE58_SIZE
+                    ));
+                }
+
+                bs58::decode(bytes)
+                    .into_vec()
+                    .map_err(|error| format!("the bytes are not valid `base58`, {}", error))?
+            }
+            (MemCmpBytes::Encoded(bytes), Some(MemCmpEncoding::Base64)) => {
+                if bytes.len() > MAX_MEMCMP_BASE64_SIZE {
+                    return Err(format!(
+                        "the `base64` encoded bytes are longer than {} characters",
+                        MAX_MEMCMP_BASE64_SIZE
+                    ));
+                }
+
+                base64::decode(bytes)
+                    .map_err(|error| format!("the bytes are not valid `base64`, {}", error))?
+            }
+            (MemCmpBytes::Encoded(_), Some(encoding)) => {
+                return Err(format!(
+                    "the `{}` encoding expects an array of bytes",
+                    encoding.to_str()
+                ))
+            }
+            (MemCmpBytes::Raw(_), Some(encoding)) => {
+                return Err(format!(
+                    "the `{}` encoding expects a string",
+                    encoding.to_str()
+                ))
+            }
+        };
+
+        if decoded.len() > MAX_MEMCMP_BYTES {
+            return Err(format!(
+                "the decoded bytes are longer than {} bytes",
+                MAX_MEMCMP_BYTES
+            ));
+        }
+
+        Ok(decoded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use sha2::{Digest, Sha256};
+    use std::time::Instant;
+
+    /// The magic number at the start of every zstd frame
+    const ZSTD_MAGIC_NUMBER: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+    /// The largest account a Solana program can allocate
+    const MAX_PERMITTED_DATA_LENGTH: usize = 10 * 1024 * 1024;
+
+    #[test]
+    fn decode_memcmp_bytes() -> anyhow::Result<()> {
+        let filters = parse(json!([
+            { "memcmp": { "offset": 0, "bytes": "1Ldp" } },
+            { "memcmp": { "offset": 0, "bytes": "1Ldp", "encoding": "base58" } },
+            { "memcmp": { "offset": 0, "bytes": "1Ldp", "encoding": "binary" } },
+            { "memcmp": { "offset": 0, "bytes": "AAECAw==", "encoding": "base64" } },
+            { "memcmp": { "offset": 0, "bytes": [0, 1, 2, 3], "encoding": "bytes" } },
+            { "memcmp": { "offset": 0, "bytes": [0, 1, 2, 3] } },
+        ]))?;
+
+        for filter in &filters {
+            match filter {
+                Filter::Memcmp(memcmp_data) => assert_eq!(
+                    memcmp_data
+                        .decode()
+                        .map_err(|error| anyhow::anyhow!("{:?}", error))?,
+                    vec![0, 1, 2, 3]
+                ),
+                Filter::DataSize(_) | Filter::TokenAccountState => unreachable!(),
+            }
+        }
+
+        // At most 4 filters are verified at once
+        for filters in filters.chunks(Filter::MAX_FILTERS) {
+            Filter::verify(filters).map_err(|error| anyhow::anyhow!("{:?}", error))?;
+        }
+
+        // The filter of the initialized token accounts is internal to the token methods
+        assert!(parse(json!(["tokenAccountState", { "dataSize": 165 }])).is_err());
+        assert!(matches!(
+            Filter::token_accounts(crate::SPL_TOKEN_2022_PROGRAM_ID),
+            Filter::TokenAccountState
+        ));
+
+        // Exactly the maximum number of decoded bytes
+        let filters = parse(json!([
+            { "memcmp": { "offset": 0, "bytes": "1".repeat(MAX_MEMCMP_BYTES) } },
+            { "memcmp": { "offset": 0, "bytes": base64_zeros(MAX_MEMCMP_BYTES), "encoding": "base64" } },
+            { "memcmp": { "offset": 0, "bytes": vec![0u8; MAX_MEMCMP_BYTES], "encoding": "bytes" } },
+        ]))?;
+        Filter::verify(&filters).map_err(|error| anyhow::anyhow!("{:?}", error))?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_invalid_filters() -> anyhow::Result<()> {
+        for (memcmp_data, expected_message) in [
+            (
+                json!({ "offset": 0, "bytes": "1".repeat(176) }),
+                "Invalid `memcmp` filter at index 1: the `base58` encoded bytes are longer than 175 characters",
+            ),
+            (
+                json!({ "offset": 0, "bytes": "1".repeat(MAX_MEMCMP_BYTES + 1), "encoding": "base58" }),
+                "Invalid `memcmp` filter at index 1: the decoded bytes are longer than 128 bytes",
+            ),
+            (
+                json!({ "offset": 0, "bytes": base64_zeros(MAX_MEMCMP_BYTES + 2), "encoding": "base64" }),
+                "Invalid `memcmp` filter at index 1: the `base64` encoded bytes are longer than 172 characters",
+            ),
+            (
+                json!({ "offset": 0, "bytes": base64_zeros(MAX_MEMCMP_BYTES + 1), "encoding": "base64" }),
+                "Invalid `memcmp` filter at index 1: the decoded bytes are longer than 128 bytes",
+            ),
+            (
+                json!({ "offset": 0, "bytes": vec![0u8; MAX_MEMCMP_BYTES + 1] }),
+                "Invalid `memcmp` filter at index 1: the decoded bytes are longer than 128 bytes",
+            ),
+            (
+                json!({ "offset": 0, "bytes": "0OIl" }),
+                "Invalid `memcmp` filter at index 1: the bytes are not valid `base58`, provided string contained invalid character '0' at byte 0",
+            ),
+            (
+                json!({ "offset": 0, "bytes": "AA=A", "encoding": "base64" }),
+                "Invalid `memcmp` filter at index 1: the bytes are not valid `base64`, Invalid byte 61, offset 2.",
+            ),
+            (
+                json!({ "offset": 0, "bytes": "1Ldp", "encoding": "bytes" }),
+                "Invalid `memcmp` filter at index 1: the `bytes` encoding expects an array of bytes",
+            ),
+            (
+                json!({ "offset": 0, "bytes": [0, 1], "encoding": "base64" }),
+                "Invalid `memcmp` filter at index 1: the `base64` encoding expects a string",
+            ),
+        ] {
+            let filters = parse(json!([{ "dataSize": 165 }, { "memcmp": memcmp_data }]))?;
+
+            match Filter::verify(&filters) {
+                Err(ProxyError::InvalidParams(message)) => assert_eq!(message, expected_message),
+                outcome => anyhow::bail!("Expected `{}`, got `{:?}`", expected_message, outcome),
+            }
+        }
+
+        // More than 4 filters
+        let filters = parse(json!(vec![json!({ "dataSize": 165 }); 5]))?;
+        match Filter::verify(&filters) {
+            Err(ProxyError::InvalidParams(message)) => {
+                assert_eq!(message, "Too many filters provided; max 4")
+            }
+            outcome => anyhow::bail!("Expected too many filters, got `{:?}`", outcome),
+        }
+
+        Ok(())
+    }
+
+    /// Every account method defaults to `binary` like Solana, with or without a configuration object
+    #[test]
+    fn default_to_binary_encoding() -> anyhow::Result<()> {
+        assert_eq!(Encoding::get_encoding(Option::None), Encoding::Binary);
+
+        for (parameters, encoding) in [
+            (json!({}), Encoding::Binary),
+            (json!({ "commitment": "confirmed" }), Encoding::Binary),
+            (json!({ "encoding": "binary" }), Encoding::Binary),
+            (json!({ "encoding": "base58" }), Encoding::Base58),
+            (json!({ "encoding": "base64" }), Encoding::Base64),
+            (json!({ "encoding": "base64+zstd" }), Encoding::Base64Zstd),
+            (json!({ "encoding": "jsonParsed" }), Encoding::JsonParsed),
+        ] {
+            let parameters: Parameters = serde_json::from_value(parameters)?;
+            assert_eq!(Encoding::get_encoding(Some(&parameters)), encoding);
         }
+        assert!(serde_json::from_value::<Parameters>(json!({ "encoding": "hex" })).is_err());
+
+        Ok(())
+    }
+
+    /// The data can be sliced with every encoding but `jsonParsed`
+    #[test]
+    fn check_data_slice_encoding() {
+        let data_slice = DataSlice {
+            offset: 0,
+            length: 1,
+        };
+
+        for encoding in [
+            Encoding::Binary,
+            Encoding::Base58,
+            Encoding::Base64,
+            Encoding::Base64Zstd,
+        ] {
+            assert!(encoding.check_data_slice(Some(&data_slice)).is_ok());
+        }
+
+        assert_eq!(
+            Encoding::JsonParsed
+                .check_data_slice(Some(&data_slice))
+                .map_err(|error| error.code()),
+            Err(-32600)
+        );
+        assert!(Encoding::JsonParsed.check_data_slice(Option::None).is_ok());
+    }
+
+    /// The `base64+zstd` encoding round-trips through a zstd decoder
+    /// for empty, small and large accounts at the supported compression levels
+    #[test]
+    fn zstd_round_trip() -> anyhow::Result<()> {
+        let accounts = [
+            Vec::new(),
+            vec![0u8; 165],
+            program_account(165),
+            program_account(64 * 1024),
+            program_account(MAX_PERMITTED_DATA_LENGTH),
+        ];
+
+        for level in [-5, 1, 3, 19] {
+            for data in &accounts {
+                let compressed = compress(data, level)?;
+
+                assert_eq!(compressed[..4], ZSTD_MAGIC_NUMBER);
+                assert_eq!(&zstd::stream::decode_all(compressed.as_slice())?, data);
+
+                // The response carries the frame as `base64`
+                let encoded = base64::encode(&compressed);
+                let decoded = zstd::stream::decode_all(base64::decode(encoded)?.as_slice())?;
+                assert_eq!(&decoded, data);
+            }
+        }
+
+        // A zeroed account compresses to a few bytes
+        let zeroed = compress(&vec![0u8; MAX_PERMITTED_DATA_LENGTH], 3)?;
+        assert!(zeroed.len() < 1024);
+
+        Ok(())
+    }
+
+    /// Time the compression of large program accounts at a few compression levels,
+    /// run with `cargo test --release zstd_benchmark -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn zstd_benchmark() -> anyhow::Result<()> {
+        for size in [10 * 1024, 1024 * 1024, MAX_PERMITTED_DATA_LENGTH] {
+            let data = program_account(size);
+
+            for level in [1, 3, 9] {
+                let iterations = (MAX_PERMITTED_DATA_LENGTH / size).clamp(1, 100);
+
+                let now = Instant::now();
+                let mut compressed_len = 0;
+                for _ in 0..iterations {
+                    compressed_len = compress(&data, level)?.len();
+                }
+                let elapsed = now.elapsed() / iterations as u32;
+
+                println!(
+                    "ZSTD BENCHMARK [{} bytes] level {} - {:?} per account, {:.1} MiB/s, ratio {:.2}",
+                    size,
+                    level,
+                    elapsed,
+                    size as f64 / (1024.0 * 1024.0) / elapsed.as_secs_f64(),
+                    size as f64 / compressed_len as f64
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse(filters: SerdeJsonValue) -> anyhow::Result<Vec<Filter>> {
+        Ok(serde_json::from_value(filters)?)
+    }
+
+    /// The `base64` encoding of `num_of_bytes` zero bytes
+    fn base64_zeros(num_of_bytes: usize) -> String {
+        let mut encoded = "A".repeat(num_of_bytes / 3 * 4);
+
+        match num_of_bytes % 3 {
+            1 => encoded.push_str("AA=="),
+            2 => encoded.push_str("AAA="),
+            _ => (),
+        }
+
+        encoded
+    }
+
+    fn compress(data: &[u8], level: i32) -> anyhow::Result<Vec<u8>> {
+        Encoding::zstd_compress(data, level).map_err(|error| anyhow::anyhow!("{:?}", error))
+    }
+
+    /// Account data shaped like the accounts of a large program, records of
+    /// pseudorandom keys followed by small integers and zeroed padding
+    fn program_account(size: usize) -> Vec<u8> {
+        let mut data = Vec::with_capacity(size);
+        let mut record = 0u64;
+
+        while data.len() < size {
+            let key = Sha256::new()
+                .chain_update(size.to_le_bytes())
+                .chain_update(record.to_le_bytes())
+                .finalize();
+
+            data.extend_from_slice(&key);
+            data.extend_from_slice(&record.to_le_bytes());
+            data.extend_from_slice(&[0u8; 24]);
+
+            record += 1;
+        }
+
+        data.truncate(size);
+
+        data
     }
 }
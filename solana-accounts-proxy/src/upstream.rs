@@ -0,0 +1,367 @@
+use crate::UpstreamConfig;
+use hyper::{
+    client::HttpConnector,
+    header::{self, HeaderValue},
+    Body, Client, Method, Request, Uri,
+};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use jsonrpsee::types::{error::ErrorCode, ErrorObject};
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Forwards the calls the proxy cannot answer to a Solana RPC node, see [UpstreamConfig].
+/// A call is forwarded verbatim when the proxy does not implement its method, or when its
+/// `minContextSlot` is ahead of the databases if `forward_min_context_slot` is enabled.
+#[derive(Debug)]
+pub struct Upstream {
+    client: Client<HttpsConnector<HttpConnector>>,
+    url: Uri,
+    timeout: Duration,
+    forward_min_context_slot: bool,
+    forwarded: AtomicU64,
+    timeouts: AtomicU64,
+    errors: AtomicU64,
+}
+
+/// The number of calls forwarded to the Solana RPC node since the proxy started
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpstreamStats {
+    /// The calls answered by the Solana RPC node
+    pub forwarded: u64,
+    /// The calls the Solana RPC node did not answer in time
+    pub timeouts: u64,
+    /// The calls that failed or were answered with an invalid response
+    pub errors: u64,
+}
+
+/// The error of a JSON-RPC response, the rest of the response is ignored
+#[derive(Deserialize)]
+struct UpstreamErrorResponse<'a> {
+    #[serde(borrow)]
+    error: ErrorObject<'a>,
+}
+
+impl Upstream {
+    /// The interval between two logs of the [UpstreamStats]
+    pub const STATS_LOG_INTERVAL: Duration = Duration::from_secs(60);
+
+    /// Create the client of the Solana RPC node, no client is created without a `url`
+    pub fn new(upstream_config: &UpstreamConfig) -> anyhow::Result<Option<Self>> {
+        let url = match upstream_config.url() {
+            Some(url) => url.parse::<Uri>()?,
+            None => return Ok(Option::None),
+        };
+
+        let connector = HttpsConnectorBuilder::new()
+            .with_webpki_roots()
+            .https_or_http()
+            .enable_http1()
+            .build();
+
+        Ok(Some(Upstream {
+            client: Client::builder().build(connector),
+            url,
+            timeout: Duration::from_secs(upstream_config.timeout()),
+            forward_min_context_slot: upstream_config.forward_min_context_slot(),
+            forwarded: AtomicU64::new(0),
+            timeouts: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+        }))
+    }
+
+    /// Whether requests whose `minContextSlot` is ahead of the databases are forwarded
+    pub fn forwards_min_context_slot(&self) -> bool {
+        self.forward_min_context_slot
+    }
+
+    /// Whether a call answered by the proxy with the error `response` is forwarded
+    pub fn forwards(&self, response: &str) -> bool {
+        let code = match serde_json::from_str::<UpstreamErrorResponse>(response) {
+            Ok(error_response) => error_response.error.code(),
+            Err(_) => return false,
+        };
+
+        code == ErrorCode::MethodNotFound.code()
+            || (self.forward_min_context_slot && code == crate::MIN_CONTEXT_SLOT_NOT_REACHED_CODE)
+    }
+
+    /// Forward the `call` verbatim and return the response of the Solana RPC node.
+    /// Timeouts and errors are logged and counted, the caller then answers the call itself.
+    pub async fn forward(&self, call: &str) -> Option<String> {
+        match tokio::time::timeout(self.timeout, self.request(call)).await {
+            Ok(Ok(response)) => {
+                self.forwarded.fetch_add(1, Ordering::Relaxed);
+
+                Some(response)
+            }
+            Ok(Err(error)) => {
+                self.errors.fetch_add(1, Ordering::Relaxed);
+                tracing::error!("UPSTREAM_ERROR: {}", error);
+
+                Option::None
+            }
+            Err(_) => {
+                self.timeouts.fetch_add(1, Ordering::Relaxed);
+                tracing::error!(
+                    "UPSTREAM_TIMEOUT: no response after {} seconds",
+                    self.timeout.as_secs()
+                );
+
+                Option::None
+            }
+        }
+    }
+
+    /// The number of calls forwarded since the proxy started
+    pub fn stats(&self) -> UpstreamStats {
+        UpstreamStats {
+            forwarded: self.forwarded.load(Ordering::Relaxed),
+            timeouts: self.timeouts.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Log the [UpstreamStats] every [Upstream::STATS_LOG_INTERVAL] in the background
+    /// when calls have been forwarded since the last log
+    pub fn spawn_stats_log(self: Arc<Self>) {
+        tokio::spawn(async move {
+            let mut logged = self.stats();
+
+            loop {
+                tokio::time::sleep(Upstream::STATS_LOG_INTERVAL).await;
+
+                let stats = self.stats();
+                if stats != logged {
+                    tracing::info!(
+                        "UPSTREAM_STATS: forwarded: {}, timeouts: {}, errors: {}",
+                        stats.forwarded,
+                        stats.timeouts,
+                        stats.errors
+                    );
+
+                    logged = stats;
+                }
+            }
+        });
+    }
+
+    async fn request(&self, call: &str) -> anyhow::Result<String> {
+        let mut request = Request::new(Body::from(call.to_owned()));
+        *request.method_mut() = Method::POST;
+        *request.uri_mut() = self.url.clone();
+        request.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+
+        let response = self.client.request(request).await?;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+
+        if !status.is_success() {
+            anyhow::bail!("The Solana RPC node answered with the status `{}`", status);
+        }
+
+        // The response is inserted in the responses of a batch so it must be a JSON value
+        let response = String::from_utf8(body.to_vec())?;
+        serde_json::from_str::<&RawValue>(&response)?;
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{http_server, ProxyConfig};
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Response, Server, StatusCode,
+    };
+    use std::{
+        convert::Infallible,
+        net::SocketAddr,
+        sync::{Arc, Mutex},
+    };
+
+    /// The calls received by the mock Solana RPC node
+    type ReceivedCalls = Arc<Mutex<Vec<String>>>;
+
+    /// The calls the proxy cannot answer are forwarded verbatim to a mock Solana RPC node
+    /// and the timeouts and errors of the node fall back to the response of the proxy
+    #[tokio::test]
+    async fn forward_to_upstream() -> anyhow::Result<()> {
+        let received = ReceivedCalls::default();
+        let mock_addr = mock_upstream(received.clone())?;
+
+        let config: ProxyConfig = toml::from_str(&format!(
+            r#"
+            [socket]
+            ip = "127.0.0.1"
+            port = 0
+
+            [postgres]
+            user = "postgres"
+            dbname = "geyser"
+            host = "postgres.invalid"
+
+            [upstream]
+            url = "http://{}"
+            timeout = 1
+            "#,
+            mock_addr
+        ))?;
+        assert_eq!(config.upstream_config().timeout(), 1);
+        assert!(!config.upstream_config().forward_min_context_slot());
+
+        let upstream = Upstream::new(config.upstream_config())?
+            .map(Arc::new)
+            .ok_or_else(|| anyhow::anyhow!("The upstream node is not configured"))?;
+        let (proxy_addr, _server) =
+            http_server("127.0.0.1:0".parse()?, Some(upstream.clone())).await?;
+
+        // A method the proxy does not implement is forwarded verbatim and its response is relayed
+        let call = r#"{"jsonrpc":"2.0","id":7,"method":"getBlockHeight","params":[{"commitment":"finalized"}]}"#;
+        assert_eq!(
+            post(proxy_addr, call).await?,
+            r#"{"jsonrpc":"2.0","result":"getBlockHeight","id":7}"#
+        );
+        assert_eq!(received_calls(&received), [call]);
+
+        // Only the unsupported calls of a batch are forwarded, an invalid public key is answered by the proxy
+        let batch = r#"[
+            {"jsonrpc":"2.0","id":1,"method":"getAccountInfo","params":["invalid"]},
+            {"jsonrpc":"2.0","id":2,"method":"getLatestBlockhash"}
+        ]"#;
+        let responses = serde_json::from_str::<serde_json::Value>(&post(proxy_addr, batch).await?)?;
+        assert_eq!(responses[0]["error"]["code"], -32602);
+        assert_eq!(
+            responses[1],
+            serde_json::json!({ "jsonrpc": "2.0", "result": "getLatestBlockhash", "id": 2 })
+        );
+        assert_eq!(
+            received_calls(&received)[1],
+            r#"{"jsonrpc":"2.0","id":2,"method":"getLatestBlockhash"}"#
+        );
+
+        // The node does not answer in time, a failed request and an invalid response are errors.
+        // The proxy then answers the call itself.
+        for method in ["slowMethod", "failingMethod", "invalidJsonMethod"] {
+            let call = format!(r#"{{"jsonrpc":"2.0","id":3,"method":"{}"}}"#, method);
+            let response =
+                serde_json::from_str::<serde_json::Value>(&post(proxy_addr, &call).await?)?;
+
+            assert_eq!(response["error"]["code"], -32601);
+            assert_eq!(response["error"]["message"], "Method not found");
+        }
+
+        assert_eq!(
+            upstream.stats(),
+            UpstreamStats {
+                forwarded: 2,
+                timeouts: 1,
+                errors: 2,
+            }
+        );
+
+        // No node is configured without a URL
+        let config: ProxyConfig = toml::from_str(
+            r#"
+            [socket]
+            ip = "127.0.0.1"
+            port = 0
+
+            [postgres]
+            user = "postgres"
+            dbname = "geyser"
+            host = "postgres.invalid"
+            "#,
+        )?;
+        assert!(Upstream::new(config.upstream_config())?.is_none());
+
+        Ok(())
+    }
+
+    /// A mock Solana RPC node answering every call with the name of its method
+    fn mock_upstream(received: ReceivedCalls) -> anyhow::Result<SocketAddr> {
+        let make_service = make_service_fn(move |_| {
+            let received = received.clone();
+
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                    let received = received.clone();
+
+                    async move {
+                        let body = hyper::body::to_bytes(request.into_body()).await?;
+                        let call = String::from_utf8_lossy(&body).to_string();
+                        received
+                            .lock()
+                            .unwrap_or_else(|poisoned| poisoned.into_inner())
+                            .push(call.clone());
+
+                        let call =
+                            serde_json::from_str::<serde_json::Value>(&call).unwrap_or_default();
+                        let method = call["method"].as_str().unwrap_or_default();
+
+                        let response = match method {
+                            "slowMethod" => {
+                                tokio::time::sleep(Duration::from_secs(3)).await;
+
+                                Response::new(Body::empty())
+                            }
+                            "failingMethod" => {
+                                let mut response =
+                                    Response::new(Body::from("Internal Server Error"));
+                                *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+
+                                response
+                            }
+                            "invalidJsonMethod" => Response::new(Body::from("not json")),
+                            method => Response::new(Body::from(format!(
+                                r#"{{"jsonrpc":"2.0","result":"{}","id":{}}}"#,
+                                method, call["id"]
+                            ))),
+                        };
+
+                        Ok::<_, hyper::Error>(response)
+                    }
+                }))
+            }
+        });
+
+        let server = Server::try_bind(&"127.0.0.1:0".parse()?)?.serve(make_service);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+
+        Ok(addr)
+    }
+
+    async fn post(proxy_addr: SocketAddr, body: &str) -> anyhow::Result<String> {
+        let body = body.to_owned();
+
+        // `minreq` blocks so it runs outside of the runtime serving the proxy and the mock node
+        tokio::task::spawn_blocking(move || {
+            let response = minreq::post(format!("http://{}", proxy_addr))
+                .with_header("Content-Type", "application/json")
+                .with_body(body)
+                .send()?;
+
+            Ok(response.as_str()?.to_owned())
+        })
+        .await?
+    }
+
+    fn received_calls(received: &ReceivedCalls) -> Vec<String> {
+        received
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
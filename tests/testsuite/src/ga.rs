@@ -8,6 +8,7 @@ pub struct Ga<'gpa> {
     pubkey: Cow<'gpa, str>,
     encoding: Cow<'gpa, str>,
     commitment: Option<Cow<'gpa, str>>,
+    min_context_slot: Option<u64>,
 }
 
 impl<'gpa> Ga<'gpa> {
@@ -33,22 +34,34 @@ impl<'gpa> Ga<'gpa> {
         self
     }
 
+    pub fn add_min_context_slot(mut self, min_context_slot: u64) -> Self {
+        self.min_context_slot = Some(min_context_slot);
+
+        self
+    }
+
     pub fn to_json_string(&self) -> String {
         let commitment = match self.commitment.as_ref() {
             Some(commitment) => commitment.to_string(),
             None => "finalized".to_owned(),
         };
 
+        let mut parameters = json::object! {
+            encoding: self.encoding.to_string(),
+            commitment: commitment.as_str(),
+        };
+
+        if let Some(min_context_slot) = self.min_context_slot {
+            parameters["minContextSlot"] = min_context_slot.into();
+        }
+
         json::object! {
             jsonrpc:"2.0",
             id: 1,
             method:"getAccountInfo",
             params: json::array![
                 self.pubkey.to_string(),
-                json::object!{
-                    encoding: self.encoding.to_string(),
-                    commitment: commitment.as_str(),
-                }
+                parameters
             ]
         }
         .to_string()
@@ -57,7 +70,7 @@ impl<'gpa> Ga<'gpa> {
     pub async fn req_from_rpcpool(
         &self,
         config: TestsuiteConfig,
-    ) -> anyhow::Result<RpcResult<WithContext<RpcAccount>>> {
+    ) -> anyhow::Result<RpcResult<WithContext<Option<RpcAccount>>>> {
         let mainnet_url = config.url().clone();
 
         let response = minreq::post(mainnet_url)
@@ -65,15 +78,15 @@ impl<'gpa> Ga<'gpa> {
             .with_body(self.to_json_string())
             .send()?;
 
-        Ok(serde_json::from_str::<RpcResult<WithContext<RpcAccount>>>(
-            response.as_str()?,
-        )?)
+        Ok(serde_json::from_str::<
+            RpcResult<WithContext<Option<RpcAccount>>>,
+        >(response.as_str()?)?)
     }
 
     pub async fn req_from_proxy(
         &self,
         proxy_config_file: &Path,
-    ) -> anyhow::Result<RpcResult<WithContext<RpcAccount>>> {
+    ) -> anyhow::Result<RpcResult<WithContext<Option<RpcAccount>>>> {
         let mut file = File::open(proxy_config_file).await?;
         let mut contents = String::new();
         file.read_to_string(&mut contents).await?;
@@ -89,8 +102,8 @@ impl<'gpa> Ga<'gpa> {
             .with_body(self.to_json_string())
             .send()?;
 
-        Ok(serde_json::from_str::<RpcResult<WithContext<RpcAccount>>>(
-            response.as_str()?,
-        )?)
+        Ok(serde_json::from_str::<
+            RpcResult<WithContext<Option<RpcAccount>>>,
+        >(response.as_str()?)?)
     }
 }
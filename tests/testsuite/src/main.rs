@@ -1,6 +1,7 @@
 #![forbid(unsafe_code)]
 
 use solana_accounts_proxy::log;
+use std::{future::Future, pin::Pin};
 
 mod gpa_tests;
 pub use gpa_tests::*;
@@ -8,6 +9,9 @@ pub use gpa_tests::*;
 mod ga;
 pub use ga::*;
 
+mod gma;
+pub use gma::*;
+
 mod utils;
 pub use utils::*;
 
@@ -31,10 +35,35 @@ async fn main() {
 
     let para_test = ParallelTest::new(&config, proxy_file_absolute_path);
 
-    match para_test.run_gpa().await {
-        Ok(_) => (),
-        Err(error) => {
-            eprintln!("{:?}", error);
+    // Every test runs, the testsuite then fails if any of them failed
+    let mut failures = 0usize;
+
+    let tests: [(&str, Pin<Box<dyn Future<Output = anyhow::Result<()>> + '_>>); 11] = [
+        ("gpa", Box::pin(para_test.run_gpa())),
+        (
+            "ga_missing_account",
+            Box::pin(para_test.run_ga_missing_account()),
+        ),
+        ("error_codes", Box::pin(para_test.run_error_codes())),
+        ("balance", Box::pin(para_test.run_balance())),
+        ("token_accounts", Box::pin(para_test.run_token_accounts())),
+        ("token_amounts", Box::pin(para_test.run_token_amounts())),
+        ("cluster_info", Box::pin(para_test.run_cluster_info())),
+        ("epoch_info", Box::pin(para_test.run_epoch_info())),
+        ("supply", Box::pin(para_test.run_supply())),
+        ("gma", Box::pin(para_test.run_gma())),
+        ("gpa_stream", Box::pin(para_test.run_gpa_stream())),
+    ];
+    for (name, test) in tests {
+        if let Err(error) = test.await {
+            eprintln!("{} FAILED: {:?}", name, error);
+            failures += 1;
         }
     }
+
+    if failures > 0 {
+        eprintln!("{} TESTS FAILED", failures);
+
+        std::process::exit(1);
+    }
 }
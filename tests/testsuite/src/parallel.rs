@@ -1,12 +1,14 @@
 use std::path::PathBuf;
 
-use crate::{Ga, RpcAccount, RpcAccountInfo, TestsuiteConfig};
+use crate::{Ga, Gma, RpcAccount, RpcAccountInfo, TestsuiteConfig};
 use solana_accounts_proxy::{RpcResult, WithContext};
 
-type GaResponse = RpcResult<WithContext<RpcAccount>>;
+type GaResponse = RpcResult<WithContext<Option<RpcAccount>>>;
 
 type GpaResponse = RpcResult<WithContext<Vec<RpcAccountInfo>>>;
 
+type GmaResponse = RpcResult<WithContext<Vec<Option<RpcAccount>>>>;
+
 #[derive(Debug)]
 pub struct ParallelTest<'gpa> {
     testsuite_config: &'gpa TestsuiteConfig,
@@ -58,6 +60,567 @@ impl<'gpa> ParallelTest<'gpa> {
         Ok(())
     }
 
+    pub async fn run_ga_missing_account(&self) -> anyhow::Result<()> {
+        // The SHA-256 hash of `solana-accounts-proxy missing account`
+        // so it is a valid 32 byte key that does not exist on chain
+        let pubkey = "9U9v3NZSN1A9tydbpttb6pojAgdARt1vN7sCHSp2fBQc";
+
+        for commitment in ["processed", "confirmed", "finalized"] {
+            let ga_data = Ga::new()
+                .add_pubkey(pubkey)
+                .add_commitment(commitment)
+                .add_encoding("base64");
+
+            let spawn_ga_data = ga_data.clone();
+            let spawn_config = self.testsuite_config.clone();
+
+            let rpcpool_thread = tokio::spawn(async move {
+                tracing::debug!("Fetching missing account `getAccountInfo` for rpcpool in thread");
+                let rpcpool_outcome = spawn_ga_data.req_from_rpcpool(spawn_config).await?;
+
+                Ok::<GaResponse, anyhow::Error>(rpcpool_outcome)
+            });
+
+            let proxy_outcome = ga_data
+                .req_from_proxy(&self.proxy_file_absolute_path)
+                .await?;
+
+            let rpcpool_outcome = rpcpool_thread.await??;
+
+            println!(
+                "MISSING ACCOUNT `{}` PROXY SLOT [{}] - RPCPOOL SLOT [{}]",
+                commitment, proxy_outcome.result.context.slot, rpcpool_outcome.result.context.slot
+            );
+
+            assert_eq!(rpcpool_outcome.jsonrpc, proxy_outcome.jsonrpc);
+            assert_eq!(rpcpool_outcome.id, proxy_outcome.id);
+            assert!(rpcpool_outcome.result.value.is_none());
+            assert!(proxy_outcome.result.value.is_none());
+            assert!(proxy_outcome.result.context.slot > 0);
+        }
+
+        Ok(())
+    }
+
+    pub async fn run_error_codes(&self) -> anyhow::Result<()> {
+        // A `minContextSlot` far ahead of the tip of the chain, maps to `-32016`
+        let min_context_slot = Ga::new()
+            .add_pubkey("ZETAxsqBRek56DhiGXrn75yj2NHU3aYUnxvHXpkf3aD")
+            .add_encoding("base64")
+            .add_min_context_slot(u64::MAX / 2)
+            .to_json_string();
+
+        // More public keys than the default `max_multiple_accounts`, maps to `-32602`
+        let too_many_inputs = (0..=100)
+            .fold(Gma::new(), |gma, _| {
+                gma.add_pubkey("ZETAxsqBRek56DhiGXrn75yj2NHU3aYUnxvHXpkf3aD")
+            })
+            .add_encoding("base64")
+            .to_json_string();
+
+        // More than 4 filters, rejected before the `getProgramAccounts` response is streamed
+        let too_many_filters = json::object! {
+            jsonrpc: "2.0",
+            id: 1,
+            method: "getProgramAccounts",
+            params: json::array![
+                "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                json::object! {
+                    encoding: "base64",
+                    filters: (0..5).map(|_| json::object! { dataSize: 165 }).collect::<Vec<json::JsonValue>>()
+                }
+            ]
+        }
+        .to_string();
+
+        for (expected_code, body) in [
+            (-32016, min_context_slot),
+            (-32602, too_many_inputs),
+            (-32602, too_many_filters),
+        ] {
+            let (rpcpool_outcome, proxy_outcome) =
+                crate::req_errors(self.testsuite_config, &self.proxy_file_absolute_path, body)
+                    .await?;
+
+            println!(
+                "ERROR CODE PROXY [{}] - RPCPOOL [{}]",
+                proxy_outcome.error.code, rpcpool_outcome.error.code
+            );
+
+            assert_eq!(rpcpool_outcome.jsonrpc, proxy_outcome.jsonrpc);
+            assert_eq!(rpcpool_outcome.id, proxy_outcome.id);
+            assert_eq!(rpcpool_outcome.error.code, expected_code);
+            assert_eq!(proxy_outcome.error.code, expected_code);
+            assert_eq!(rpcpool_outcome.error.message, proxy_outcome.error.message);
+            assert_eq!(
+                rpcpool_outcome.error.data.is_some(),
+                proxy_outcome.error.data.is_some()
+            );
+        }
+
+        Ok(())
+    }
+
+    pub async fn run_balance(&self) -> anyhow::Result<()> {
+        // The last public key is the same missing account as `run_ga_missing_account`
+        for pubkey in [
+            "ZETAxsqBRek56DhiGXrn75yj2NHU3aYUnxvHXpkf3aD",
+            "SysvarRent111111111111111111111111111111111",
+            "9U9v3NZSN1A9tydbpttb6pojAgdARt1vN7sCHSp2fBQc",
+        ] {
+            let body = json::object! {
+                jsonrpc: "2.0",
+                id: 1,
+                method: "getBalance",
+                params: json::array![pubkey, json::object! { commitment: "finalized" }]
+            }
+            .to_string();
+
+            let (rpcpool_outcome, proxy_outcome) = crate::req_results::<WithContext<u64>>(
+                self.testsuite_config,
+                &self.proxy_file_absolute_path,
+                body,
+            )
+            .await?;
+
+            println!(
+                "BALANCE `{}` PROXY [{}] - RPCPOOL [{}]",
+                pubkey, proxy_outcome.result.value, rpcpool_outcome.result.value
+            );
+
+            assert_eq!(rpcpool_outcome.jsonrpc, proxy_outcome.jsonrpc);
+            assert_eq!(rpcpool_outcome.id, proxy_outcome.id);
+            assert_eq!(rpcpool_outcome.result.value, proxy_outcome.result.value);
+            assert!(proxy_outcome.result.context.slot > 0);
+        }
+
+        Ok(())
+    }
+
+    pub async fn run_token_accounts(&self) -> anyhow::Result<()> {
+        // The same missing account as `run_ga_missing_account` owns no token accounts
+        let missing = "9U9v3NZSN1A9tydbpttb6pojAgdARt1vN7sCHSp2fBQc";
+
+        for method in ["getTokenAccountsByOwner", "getTokenAccountsByDelegate"] {
+            for program_id in [
+                "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+            ] {
+                let body = json::object! {
+                    jsonrpc: "2.0",
+                    id: 1,
+                    method: method,
+                    params: json::array![
+                        missing,
+                        json::object! { programId: program_id },
+                        json::object! { commitment: "finalized", encoding: "jsonParsed" }
+                    ]
+                }
+                .to_string();
+
+                let (rpcpool_outcome, proxy_outcome) =
+                    crate::req_results::<WithContext<Vec<serde_json::Value>>>(
+                        self.testsuite_config,
+                        &self.proxy_file_absolute_path,
+                        body,
+                    )
+                    .await?;
+
+                println!(
+                    "{} `{}` PROXY [{}] - RPCPOOL [{}]",
+                    method,
+                    program_id,
+                    proxy_outcome.result.value.len(),
+                    rpcpool_outcome.result.value.len()
+                );
+
+                assert_eq!(rpcpool_outcome.id, proxy_outcome.id);
+                assert_eq!(
+                    rpcpool_outcome.result.value.len(),
+                    proxy_outcome.result.value.len()
+                );
+                assert!(proxy_outcome.result.context.slot > 0);
+            }
+
+            // A program that is not a token program and a mint that does not exist, maps to `-32602`
+            for selector in [
+                json::object! { programId: "11111111111111111111111111111111" },
+                json::object! { mint: missing },
+            ] {
+                let body = json::object! {
+                    jsonrpc: "2.0",
+                    id: 1,
+                    method: method,
+                    params: json::array![missing, selector]
+                }
+                .to_string();
+
+                let (rpcpool_outcome, proxy_outcome) =
+                    crate::req_errors(self.testsuite_config, &self.proxy_file_absolute_path, body)
+                        .await?;
+
+                assert_eq!(rpcpool_outcome.error.code, -32602);
+                assert_eq!(proxy_outcome.error.code, -32602);
+                assert_eq!(rpcpool_outcome.error.message, proxy_outcome.error.message);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn run_token_amounts(&self) -> anyhow::Result<()> {
+        // The USDC mint, its supply and largest accounts change between the requests
+        let mint = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+        let body = json::object! {
+            jsonrpc: "2.0",
+            id: 1,
+            method: "getTokenSupply",
+            params: json::array![mint, json::object! { commitment: "finalized" }]
+        }
+        .to_string();
+
+        let (rpcpool_outcome, proxy_outcome) =
+            crate::req_results::<WithContext<serde_json::Value>>(
+                self.testsuite_config,
+                &self.proxy_file_absolute_path,
+                body,
+            )
+            .await?;
+
+        println!(
+            "TOKEN SUPPLY PROXY [{}] - RPCPOOL [{}]",
+            proxy_outcome.result.value["uiAmountString"],
+            rpcpool_outcome.result.value["uiAmountString"]
+        );
+
+        assert_eq!(
+            rpcpool_outcome.result.value["decimals"],
+            proxy_outcome.result.value["decimals"]
+        );
+
+        let body = json::object! {
+            jsonrpc: "2.0",
+            id: 1,
+            method: "getTokenLargestAccounts",
+            params: json::array![mint, json::object! { commitment: "finalized" }]
+        }
+        .to_string();
+
+        let (rpcpool_outcome, proxy_outcome) =
+            crate::req_results::<WithContext<Vec<serde_json::Value>>>(
+                self.testsuite_config,
+                &self.proxy_file_absolute_path,
+                body,
+            )
+            .await?;
+
+        assert_eq!(
+            rpcpool_outcome.result.value.len(),
+            proxy_outcome.result.value.len()
+        );
+        assert_eq!(
+            rpcpool_outcome.result.value[0]["address"],
+            proxy_outcome.result.value[0]["address"]
+        );
+
+        // The token account with the largest amount
+        let body = json::object! {
+            jsonrpc: "2.0",
+            id: 1,
+            method: "getTokenAccountBalance",
+            params: json::array![proxy_outcome.result.value[0]["address"].as_str().unwrap_or_default()]
+        }
+        .to_string();
+
+        let (rpcpool_outcome, proxy_outcome) =
+            crate::req_results::<WithContext<serde_json::Value>>(
+                self.testsuite_config,
+                &self.proxy_file_absolute_path,
+                body,
+            )
+            .await?;
+
+        assert_eq!(
+            rpcpool_outcome.result.value["decimals"],
+            proxy_outcome.result.value["decimals"]
+        );
+
+        // A missing account and an account that is not a mint, maps to `-32602`
+        let missing = "9U9v3NZSN1A9tydbpttb6pojAgdARt1vN7sCHSp2fBQc";
+        for (method, pubkey) in [
+            ("getTokenAccountBalance", missing),
+            ("getTokenSupply", missing),
+            ("getTokenLargestAccounts", missing),
+            ("getTokenAccountBalance", mint),
+            (
+                "getTokenSupply",
+                "SysvarRent111111111111111111111111111111111",
+            ),
+        ] {
+            let body = json::object! {
+                jsonrpc: "2.0",
+                id: 1,
+                method: method,
+                params: json::array![pubkey]
+            }
+            .to_string();
+
+            let (rpcpool_outcome, proxy_outcome) =
+                crate::req_errors(self.testsuite_config, &self.proxy_file_absolute_path, body)
+                    .await?;
+
+            println!(
+                "{} ERROR PROXY [{}] - RPCPOOL [{}]",
+                method, proxy_outcome.error.message, rpcpool_outcome.error.message
+            );
+
+            assert_eq!(rpcpool_outcome.error.code, -32602);
+            assert_eq!(proxy_outcome.error.code, -32602);
+        }
+
+        Ok(())
+    }
+
+    pub async fn run_cluster_info(&self) -> anyhow::Result<()> {
+        for commitment in ["finalized", "confirmed", "processed"] {
+            let body = json::object! {
+                jsonrpc: "2.0",
+                id: 1,
+                method: "getSlot",
+                params: json::array![json::object! { commitment: commitment }]
+            }
+            .to_string();
+
+            let (rpcpool_outcome, proxy_outcome) = crate::req_results::<u64>(
+                self.testsuite_config,
+                &self.proxy_file_absolute_path,
+                body,
+            )
+            .await?;
+
+            println!(
+                "SLOT `{}` PROXY [{}] - RPCPOOL [{}]",
+                commitment, proxy_outcome.result, rpcpool_outcome.result
+            );
+
+            assert!(proxy_outcome.result > 0);
+        }
+
+        let body = json::object! { jsonrpc: "2.0", id: 1, method: "getHealth" }.to_string();
+        let (rpcpool_outcome, proxy_outcome) = crate::req_results::<String>(
+            self.testsuite_config,
+            &self.proxy_file_absolute_path,
+            body,
+        )
+        .await?;
+        assert_eq!(rpcpool_outcome.result, "ok");
+        assert_eq!(proxy_outcome.result, "ok");
+
+        let body = json::object! { jsonrpc: "2.0", id: 1, method: "getVersion" }.to_string();
+        let (rpcpool_outcome, proxy_outcome) = crate::req_results::<serde_json::Value>(
+            self.testsuite_config,
+            &self.proxy_file_absolute_path,
+            body,
+        )
+        .await?;
+
+        println!(
+            "VERSION PROXY [{}] - RPCPOOL [{}]",
+            proxy_outcome.result, rpcpool_outcome.result
+        );
+
+        assert!(rpcpool_outcome.result["solana-core"].is_string());
+        assert!(proxy_outcome.result["solana-core"].is_string());
+        assert!(proxy_outcome.result["solana-accounts-proxy"].is_string());
+
+        Ok(())
+    }
+
+    pub async fn run_epoch_info(&self) -> anyhow::Result<()> {
+        // The empty account, a mint and a token account
+        for data_len in [0, 82, 165] {
+            let body = json::object! {
+                jsonrpc: "2.0",
+                id: 1,
+                method: "getMinimumBalanceForRentExemption",
+                params: json::array![data_len]
+            }
+            .to_string();
+
+            let (rpcpool_outcome, proxy_outcome) = crate::req_results::<u64>(
+                self.testsuite_config,
+                &self.proxy_file_absolute_path,
+                body,
+            )
+            .await?;
+
+            assert_eq!(rpcpool_outcome.result, proxy_outcome.result);
+        }
+
+        let body = json::object! { jsonrpc: "2.0", id: 1, method: "getEpochSchedule" }.to_string();
+        let (rpcpool_outcome, proxy_outcome) = crate::req_results::<serde_json::Value>(
+            self.testsuite_config,
+            &self.proxy_file_absolute_path,
+            body,
+        )
+        .await?;
+
+        assert_eq!(rpcpool_outcome.result, proxy_outcome.result);
+
+        let body = json::object! {
+            jsonrpc: "2.0",
+            id: 1,
+            method: "getEpochInfo",
+            params: json::array![json::object! { commitment: "finalized" }]
+        }
+        .to_string();
+
+        let (rpcpool_outcome, proxy_outcome) = crate::req_results::<serde_json::Value>(
+            self.testsuite_config,
+            &self.proxy_file_absolute_path,
+            body,
+        )
+        .await?;
+
+        println!(
+            "EPOCH INFO PROXY [{}] - RPCPOOL [{}]",
+            proxy_outcome.result, rpcpool_outcome.result
+        );
+
+        // The databases may lag behind the RPC node by a few slots
+        let rpcpool_epoch = rpcpool_outcome.result["epoch"].as_u64().unwrap_or_default();
+        let proxy_epoch = proxy_outcome.result["epoch"].as_u64().unwrap_or_default();
+        assert!(rpcpool_epoch.abs_diff(proxy_epoch) <= 1);
+        assert!(
+            proxy_outcome.result["slotIndex"].as_u64()
+                < proxy_outcome.result["slotsInEpoch"].as_u64()
+        );
+        assert!(proxy_outcome.result["blockHeight"].is_u64());
+
+        Ok(())
+    }
+
+    pub async fn run_supply(&self) -> anyhow::Result<()> {
+        let body = json::object! {
+            jsonrpc: "2.0",
+            id: 1,
+            method: "getSupply",
+            params: json::array![json::object! { excludeNonCirculatingAccountsList: true }]
+        }
+        .to_string();
+
+        let (rpcpool_outcome, proxy_outcome) =
+            crate::req_results::<WithContext<serde_json::Value>>(
+                self.testsuite_config,
+                &self.proxy_file_absolute_path,
+                body,
+            )
+            .await?;
+
+        println!(
+            "SUPPLY PROXY [{}] - RPCPOOL [{}]",
+            proxy_outcome.result.value, rpcpool_outcome.result.value
+        );
+
+        // The databases may not hold every account of the cluster so only the shape is compared
+        for supply in [&rpcpool_outcome.result.value, &proxy_outcome.result.value] {
+            let total = supply["total"].as_u64().unwrap_or_default();
+            let circulating = supply["circulating"].as_u64().unwrap_or_default();
+            let non_circulating = supply["nonCirculating"].as_u64().unwrap_or_default();
+
+            assert_eq!(total, circulating + non_circulating);
+            assert_eq!(supply["nonCirculatingAccounts"], serde_json::json!([]));
+        }
+
+        let body = json::object! {
+            jsonrpc: "2.0",
+            id: 1,
+            method: "getLargestAccounts",
+            params: json::array![json::object! { filter: "circulating" }]
+        }
+        .to_string();
+
+        let (rpcpool_outcome, proxy_outcome) =
+            crate::req_results::<WithContext<Vec<serde_json::Value>>>(
+                self.testsuite_config,
+                &self.proxy_file_absolute_path,
+                body,
+            )
+            .await?;
+
+        for accounts in [&rpcpool_outcome.result.value, &proxy_outcome.result.value] {
+            let lamports = accounts
+                .iter()
+                .map(|account| account["lamports"].as_u64().unwrap_or_default())
+                .collect::<Vec<u64>>();
+
+            assert!(lamports.len() <= 20);
+            assert!(lamports.windows(2).all(|pair| pair[0] >= pair[1]));
+        }
+
+        Ok(())
+    }
+
+    pub async fn run_gma(&self) -> anyhow::Result<()> {
+        // The last public key is the same missing account as `run_ga_missing_account`
+        let pubkeys = [
+            "ZETAxsqBRek56DhiGXrn75yj2NHU3aYUnxvHXpkf3aD",
+            "11111111111111111111111111111111",
+            "9U9v3NZSN1A9tydbpttb6pojAgdARt1vN7sCHSp2fBQc",
+        ];
+
+        let gma_data = pubkeys
+            .iter()
+            .fold(Gma::new(), |gma, pubkey| gma.add_pubkey(pubkey))
+            .add_commitment("finalized")
+            .add_encoding("base64");
+
+        let spawn_gma_data = gma_data.clone();
+        let spawn_config = self.testsuite_config.clone();
+
+        let rpcpool_thread = tokio::spawn(async move {
+            tracing::debug!("Fetching `getMultipleAccounts` for rpcpool in thread");
+            let rpcpool_outcome = spawn_gma_data.req_from_rpcpool(spawn_config).await?;
+            tracing::debug!("Finished running a thread `gMA`");
+
+            Ok::<GmaResponse, anyhow::Error>(rpcpool_outcome)
+        });
+
+        let proxy_outcome = gma_data
+            .req_from_proxy(&self.proxy_file_absolute_path)
+            .await?;
+
+        let rpcpool_outcome = rpcpool_thread.await??;
+
+        println!(
+            "PROXY SLOT [{}] - RPCPOOL SLOT [{}]",
+            proxy_outcome.result.context.slot, rpcpool_outcome.result.context.slot
+        );
+
+        assert_eq!(rpcpool_outcome.jsonrpc, proxy_outcome.jsonrpc);
+        assert_eq!(rpcpool_outcome.id, proxy_outcome.id);
+        assert_eq!(proxy_outcome.result.value.len(), pubkeys.len());
+        assert!(proxy_outcome.result.value[2].is_none());
+
+        for (proxy_account, rpcpool_account) in proxy_outcome
+            .result
+            .value
+            .iter()
+            .zip(rpcpool_outcome.result.value.iter())
+        {
+            assert_eq!(proxy_account.is_some(), rpcpool_account.is_some());
+
+            if let (Some(proxy_account), Some(rpcpool_account)) = (proxy_account, rpcpool_account) {
+                assert_eq!(proxy_account.owner, rpcpool_account.owner);
+                assert_eq!(proxy_account.executable, rpcpool_account.executable);
+                assert_eq!(proxy_account.data.1, rpcpool_account.data.1);
+            }
+        }
+
+        Ok(())
+    }
+
     pub async fn run_gpa(&self) -> anyhow::Result<()> {
         //TODO Iterate over all values
         let gpa_data = self.testsuite_config.gpa_data[0].clone();
@@ -172,4 +735,72 @@ impl<'gpa> ParallelTest<'gpa> {
 
         Ok(())
     }
+
+    pub async fn run_gpa_stream(&self) -> anyhow::Result<()> {
+        let gpa_data = self.testsuite_config.gpa_data[0].clone();
+
+        let mut gpa_tests = crate::GetProgramAccountsTests::new();
+        gpa_tests
+            .add_program_id(&gpa_data.0)
+            .add_commitment(&gpa_data.1.commitment)
+            .add_encoding(&gpa_data.1.encoding)
+            .add_with_context(true);
+
+        let proxy_url = crate::proxy_url(&self.proxy_file_absolute_path).await?;
+
+        // A single request is streamed with a chunked body
+        let streamed = minreq::post(&proxy_url)
+            .with_header(crate::CONTENT_TYPE, crate::APPLICATION_JSON)
+            .with_body(gpa_tests.to_json_string())
+            .send()?;
+
+        assert_eq!(
+            streamed
+                .headers
+                .get("transfer-encoding")
+                .map(String::as_str),
+            Some("chunked")
+        );
+        let streamed = serde_json::from_str::<GpaResponse>(streamed.as_str()?)?;
+
+        // A request in a batch is buffered by the `jsonrpsee` method
+        let buffered = minreq::post(&proxy_url)
+            .with_header(crate::CONTENT_TYPE, crate::APPLICATION_JSON)
+            .with_body(format!("[{}]", gpa_tests.to_json_string()))
+            .send()?;
+        let mut buffered = serde_json::from_str::<Vec<GpaResponse>>(buffered.as_str()?)?;
+        assert_eq!(buffered.len(), 1);
+        let buffered = buffered.remove(0);
+
+        println!(
+            "STREAMED ACCOUNTS [{}] - BUFFERED ACCOUNTS [{}]",
+            streamed.result.value.len(),
+            buffered.result.value.len()
+        );
+
+        assert_eq!(streamed.jsonrpc, buffered.jsonrpc);
+        assert_eq!(streamed.id, buffered.id);
+
+        let mut streamed_pubkeys = streamed
+            .result
+            .value
+            .iter()
+            .map(|account_info| account_info.pubkey.as_str())
+            .collect::<Vec<&str>>();
+        streamed_pubkeys.sort_unstable();
+
+        let mut buffered_pubkeys = buffered
+            .result
+            .value
+            .iter()
+            .map(|account_info| account_info.pubkey.as_str())
+            .collect::<Vec<&str>>();
+        buffered_pubkeys.sort_unstable();
+
+        if streamed.result.context.slot == buffered.result.context.slot {
+            assert_eq!(streamed_pubkeys, buffered_pubkeys);
+        }
+
+        Ok(())
+    }
 }
@@ -1,4 +1,5 @@
 use serde::Deserialize;
+use solana_accounts_proxy::RpcResult;
 use std::path::{Path, PathBuf};
 use tokio::{fs::File, io::AsyncReadExt};
 
@@ -49,6 +50,75 @@ impl TestsuiteConfig {
     }
 }
 
+/// The error response of an RPC request
+#[derive(Debug, Deserialize)]
+pub struct RpcErrorResponse {
+    pub jsonrpc: String,
+    pub id: u8,
+    pub error: RpcErrorObject,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RpcErrorObject {
+    pub code: i64,
+    pub message: String,
+    pub data: Option<serde_json::Value>,
+}
+
+/// Send the same request body to rpcpool and the proxy server and return both error responses
+pub async fn req_errors(
+    config: &TestsuiteConfig,
+    proxy_config_file: &Path,
+    body: String,
+) -> anyhow::Result<(RpcErrorResponse, RpcErrorResponse)> {
+    let mut file = File::open(proxy_config_file).await?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).await?;
+
+    let proxy_config = toml::from_str::<solana_accounts_proxy::ProxyConfig>(&contents)?;
+
+    let mut proxy_url = String::new();
+    proxy_url.push_str("http://");
+    proxy_url.push_str(proxy_config.get_socketaddr().to_string().as_str());
+
+    let rpcpool_response = minreq::post(config.url().clone())
+        .with_header(CONTENT_TYPE, APPLICATION_JSON)
+        .with_body(body.clone())
+        .send()?;
+
+    let proxy_response = minreq::post(proxy_url)
+        .with_header(CONTENT_TYPE, APPLICATION_JSON)
+        .with_body(body)
+        .send()?;
+
+    Ok((
+        serde_json::from_str::<RpcErrorResponse>(rpcpool_response.as_str()?)?,
+        serde_json::from_str::<RpcErrorResponse>(proxy_response.as_str()?)?,
+    ))
+}
+
+/// Send the same request body to rpcpool and the proxy server and return both results
+pub async fn req_results<T: serde::de::DeserializeOwned>(
+    config: &TestsuiteConfig,
+    proxy_config_file: &Path,
+    body: String,
+) -> anyhow::Result<(RpcResult<T>, RpcResult<T>)> {
+    let rpcpool_response = minreq::post(config.url().clone())
+        .with_header(CONTENT_TYPE, APPLICATION_JSON)
+        .with_body(body.clone())
+        .send()?;
+
+    let proxy_response = minreq::post(proxy_url(proxy_config_file).await?)
+        .with_header(CONTENT_TYPE, APPLICATION_JSON)
+        .with_body(body)
+        .send()?;
+
+    Ok((
+        serde_json::from_str::<RpcResult<T>>(rpcpool_response.as_str()?)?,
+        serde_json::from_str::<RpcResult<T>>(proxy_response.as_str()?)?,
+    ))
+}
+
 #[derive(Debug, Deserialize, Default, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct GaData {
@@ -83,6 +153,17 @@ pub struct DataSlice {
     pub length: usize,
 }
 
+/// The URL of the proxy server from its configuration file
+pub async fn proxy_url(proxy_config_file: &Path) -> anyhow::Result<String> {
+    let mut file = File::open(proxy_config_file).await?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).await?;
+
+    let proxy_config = toml::from_str::<solana_accounts_proxy::ProxyConfig>(&contents)?;
+
+    Ok(format!("http://{}", proxy_config.get_socketaddr()))
+}
+
 /// Return a [Result] containing the path to the configuration
 /// file of the proxy server
 pub async fn load_binary(proxy_config: &Path, binary_name: &Path) -> anyhow::Result<PathBuf> {